@@ -1,182 +1,488 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashSet},
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::ser;
+
 use crate::{
-    datatypes::Element,
+    datatypes::{Duplicates, Element, Precision},
     error::{Error, Result},
-    Value,
+    reader::datatypes::Position,
+    ser::{NonFinite, Options},
+    Number, Value,
 };
 
+/// A single element value, either a regular value or raw bytes which are
+/// written as is
+#[derive(Debug, Clone)]
+enum Part {
+    Value(Value),
+
+    Bytes(Vec<u8>),
+
+    /// Line protocol written as is, without escaping
+    Raw(Vec<u8>),
+}
+
+impl Part {
+    /// The unescaped bytes of a string like part, or None for other values
+    fn as_str_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Part::Value(Value::String(s)) => Some(s.as_bytes()),
+            Part::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The inner value as a string, borrowed if it already is a string
+    fn as_str(&self) -> Cow<'_, str> {
+        match self {
+            Part::Value(Value::String(s)) => Cow::Borrowed(s),
+            Part::Value(v) => Cow::Owned(v.as_string()),
+            Part::Bytes(b) | Part::Raw(b) => String::from_utf8_lossy(b),
+        }
+    }
+
+    /// The inner value, with bytes converted into a string
+    fn as_value(&self) -> Cow<'_, Value> {
+        match self {
+            Part::Value(v) => Cow::Borrowed(v),
+            Part::Bytes(b) | Part::Raw(b) => {
+                Cow::Owned(Value::String(String::from_utf8_lossy(b).into_owned()))
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Part::Value(Value::None) => true,
+            Part::Value(Value::String(s)) => s.is_empty(),
+            Part::Value(_) => false,
+            Part::Bytes(b) | Part::Raw(b) => b.is_empty(),
+        }
+    }
+
+    /// Key used to sort tags, only allocates for values which are not strings
+    fn sort_key(&self) -> Cow<'_, [u8]> {
+        match self.as_str_bytes() {
+            Some(s) => Cow::Borrowed(s),
+            None => {
+                let mut key = Vec::new();
+                self.write(false, &mut key);
+                Cow::Owned(key)
+            }
+        }
+    }
+
+    /// Write the part into `buf` without escaping
+    ///
+    /// Numbers are written in their line protocol representation if `lp` is
+    /// set, otherwise as plain numbers, see [Value::as_string]
+    fn write(&self, lp: bool, buf: &mut Vec<u8>) {
+        let value = match self {
+            Part::Value(value) => value,
+            Part::Bytes(b) | Part::Raw(b) => return buf.extend_from_slice(b),
+        };
+
+        match value {
+            Value::None => (),
+            Value::String(s) => buf.extend_from_slice(s.as_bytes()),
+            Value::Boolean(b) => buf.extend_from_slice(if *b { b"true" } else { b"false" }),
+            Value::Number(Number::Float(n)) => match lp || !n.is_finite() {
+                // Writing into a vector can not fail
+                true => write!(buf, "{n}").unwrap(),
+                false => buf.extend_from_slice(ryu::Buffer::new().format_finite(*n).as_bytes()),
+            },
+            Value::Number(Number::Integer(n)) => {
+                buf.extend_from_slice(itoa::Buffer::new().format(*n).as_bytes());
+                if lp {
+                    buf.push(b'i');
+                }
+            }
+            Value::Number(Number::UInteger(n)) => {
+                buf.extend_from_slice(itoa::Buffer::new().format(*n).as_bytes());
+                if lp {
+                    buf.push(b'i');
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 struct LineBuilder {
-    measurement: Option<Value>,
+    measurement: Option<Part>,
 
-    tags: Option<Vec<Value>>,
+    tags: Vec<Part>,
 
-    fields: Option<Vec<Value>>,
+    fields: Vec<Part>,
 
-    timestamp: Option<Value>,
+    timestamp: Option<Part>,
 }
 
 impl LineBuilder {
-    fn set_measurement(&mut self, measurement: Value) {
+    fn set_measurement(&mut self, measurement: Part) {
         self.measurement = Some(measurement)
     }
 
-    fn add_tag(&mut self, tag: Value) {
-        self.tags.get_or_insert(Vec::new()).push(tag);
+    fn add_tag(&mut self, tag: Part) {
+        self.tags.push(tag);
     }
 
     fn remove_tag(&mut self) {
-        if let Some(tags) = &mut self.tags {
-            tags.pop();
-        }
+        self.tags.pop();
     }
 
-    fn add_field(&mut self, field: Value) {
-        self.fields.get_or_insert(Vec::new()).push(field);
+    fn add_field(&mut self, field: Part) {
+        self.fields.push(field);
     }
 
     fn remove_field(&mut self) {
-        if let Some(fields) = &mut self.fields {
-            fields.pop();
-        }
+        self.fields.pop();
     }
 
-    fn set_timestamp(&mut self, timestamp: Value) {
+    fn set_timestamp(&mut self, timestamp: Part) {
         self.timestamp = Some(timestamp)
     }
 
+    fn has_timestamp(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    /// Clear the builder for the next line, keeping the allocated capacity
     fn reset(&mut self) {
-        *self = LineBuilder::default();
+        self.measurement = None;
+        self.tags.clear();
+        self.fields.clear();
+        self.timestamp = None;
     }
 
-    fn escape_key(&self, value: &Value) -> String {
-        match value {
-            Value::String(s) => s
-                .replace("=", r"\=")
-                .replace(",", r"\,")
-                .replace(" ", r"\ "),
-            _ => value.as_string(),
+    fn escape_measurement(&self, part: &Part, options: &Options, buf: &mut Vec<u8>) {
+        match part.as_str_bytes() {
+            Some(s) => escape_measurement(s, options, buf),
+            None => part.write(true, buf),
         }
     }
 
-    fn escape_tag(&self, value: &Value) -> String {
-        match value {
-            Value::String(s) => s
-                .replace("=", r"\=")
-                .replace(",", r"\,")
-                .replace(" ", r"\ "),
-            _ => value.to_string(),
+    fn escape_key(&self, part: &Part, options: &Options, buf: &mut Vec<u8>) {
+        match part.as_str_bytes() {
+            Some(s) => escape(s, b"=, ", options.telegraf, buf),
+            None => part.write(false, buf),
         }
     }
 
-    fn escape_field_value(&self, value: &Value) -> String {
-        match value {
-            Value::String(s) => {
-                let escaped = s.replace("\\", "\\\\").replace("\"", "\\\"");
-                format!("\"{escaped}\"")
+    fn escape_tag(&self, part: &Part, options: &Options, buf: &mut Vec<u8>) {
+        match part.as_str_bytes() {
+            Some(s) => escape(s, b"=, ", options.telegraf, buf),
+            None => part.write(true, buf),
+        }
+    }
+
+    fn escape_field_value(&self, part: &Part, options: &Options, buf: &mut Vec<u8>) {
+        match part {
+            Part::Value(Value::String(_)) | Part::Bytes(_) => {
+                buf.push(b'"');
+                escape(part.as_str_bytes().unwrap(), b"\\\"", false, buf);
+                buf.push(b'"');
+            }
+            Part::Value(Value::Number(Number::UInteger(n))) if options.unsigned_suffix => {
+                buf.extend_from_slice(itoa::Buffer::new().format(*n).as_bytes());
+                buf.push(b'u');
+            }
+            // Telegraf does not support unsigned integers by default and clamps them
+            Part::Value(Value::Number(Number::UInteger(n))) if options.telegraf => {
+                let n = (*n).min(i64::MAX as u64);
+                buf.extend_from_slice(itoa::Buffer::new().format(n).as_bytes());
+                buf.push(b'i');
             }
-            _ => value.to_string(),
+            Part::Value(Value::Number(Number::Float(n))) => {
+                let n = match options.non_finite {
+                    NonFinite::Replace(replacement) if !n.is_finite() => replacement,
+                    _ => *n,
+                };
+
+                let start = buf.len();
+                // Writing into a vector can not fail
+                match options.float_decimals {
+                    Some(decimals) => write!(buf, "{n:.decimals$}").unwrap(),
+                    None => write!(buf, "{n}").unwrap(),
+                }
+
+                if options.decimal_point && !buf[start..].contains(&b'.') {
+                    buf.extend_from_slice(b".0");
+                }
+            }
+            _ => part.write(true, buf),
         }
     }
 
-    fn build(&mut self) -> Result<String> {
-        let mut line = String::new();
+    /// Build the line into `line`, which is cleared beforehand
+    fn build(&self, options: &Options, line: &mut Vec<u8>) -> Result<()> {
+        line.clear();
         match self.measurement {
-            Some(ref measurement) => line.push_str(&measurement.to_string()),
+            Some(ref measurement) => {
+                if let Some(ref prefix) = options.measurement_prefix {
+                    escape_measurement(prefix.as_bytes(), options, line);
+                }
+
+                self.escape_measurement(measurement, options, line);
+
+                if let Some(ref suffix) = options.measurement_suffix {
+                    escape_measurement(suffix.as_bytes(), options, line);
+                }
+            }
             None => return Err(Error::missing_element("measurement")),
         }
 
-        if let Some(tags) = self
+        // We should not reach a state where the tag set is uneven but I am untrusting
+        if !self.tags.len().is_multiple_of(2) {
+            return Err(Error::uneven_set("tag"));
+        }
+
+        let mut write_tag = |tag: &[Part]| {
+            line.push(b',');
+            self.escape_key(&tag[0], options, line);
+            line.push(b'=');
+            self.escape_tag(&tag[1], options, line);
+        };
+
+        if options.duplicates == Duplicates::Error {
+            check_duplicates(&self.tags)?;
+        }
+
+        // Telegraf skips tags which InfluxDB would reject
+        let shadowed = shadowed_entries(&self.tags, options.duplicates);
+        let tags = self
             .tags
-            .as_ref()
-            .and_then(|v| if !v.is_empty() { Some(v) } else { None })
-        {
-            // We should not reach a state where the tag set is uneven but I am untrusting
-            let tag_set: Vec<&[Value]> = tags.chunks(2).collect();
-            if !tag_set.iter().all(|c| c.len() == 2) {
-                return Err(Error::uneven_set("tag"));
+            .chunks_exact(2)
+            .enumerate()
+            .filter(|(i, _)| !shadowed[*i])
+            .map(|(_, t)| t)
+            .filter(|t| !options.telegraf || !(t[0].is_empty() || t[1].is_empty()))
+            .filter(|t| !is_dropped(&t[0], &options.drop_tags));
+
+        match options.sort_tags {
+            true => {
+                let mut tags: Vec<&[Part]> = tags.collect();
+                tags.sort_by(|a, b| a[0].sort_key().cmp(&b[0].sort_key()));
+                tags.into_iter().for_each(&mut write_tag);
             }
+            false => tags.for_each(&mut write_tag),
+        }
 
-            let tags: Vec<String> = tag_set
-                .into_iter()
-                .map(|t| {
-                    let key = self.escape_key(t.first().unwrap());
-                    let value = self.escape_tag(t.get(1).unwrap());
+        if self.fields.is_empty() {
+            return Err(Error::missing_element("fields"));
+        }
 
-                    format!("{key}={value}")
-                })
-                .collect();
+        // We should not reach a state where the field set is uneven but I am untrusting
+        if !self.fields.len().is_multiple_of(2) {
+            return Err(Error::uneven_set("field"));
+        }
 
-            line = format!("{line},{}", tags.join(","))
+        if options.duplicates == Duplicates::Error {
+            check_duplicates(&self.fields)?;
         }
 
-        match self.fields {
-            Some(ref fields) => {
-                if fields.is_empty() {
-                    return Err(Error::missing_element("fields"));
-                }
+        let mut has_fields = false;
+        let shadowed = shadowed_entries(&self.fields, options.duplicates);
+        for (i, field) in self.fields.chunks_exact(2).enumerate() {
+            if is_dropped(&field[0], &options.drop_fields) || shadowed[i] {
+                continue;
+            }
 
-                // We should not reach a state where the tag set is uneven but I am untrusting
-                let field_set: Vec<&[Value]> = fields.chunks(2).collect();
-                if !field_set.iter().all(|c| c.len() == 2) {
-                    return Err(Error::uneven_set("field"));
+            // InfluxDB can not represent NaN or infinite floats
+            if let Part::Value(Value::Number(Number::Float(n))) = field[1] {
+                if !n.is_finite() {
+                    match options.non_finite {
+                        NonFinite::Skip => continue,
+                        NonFinite::Replace(replacement) if replacement.is_finite() => (),
+                        _ => return Err(Error::infinite_float()),
+                    }
                 }
+            }
 
-                let fields: Vec<String> = field_set
-                    .into_iter()
-                    .map(|f| {
-                        let key = self.escape_key(f.first().unwrap());
-                        let value = self.escape_field_value(f.get(1).unwrap());
+            line.push(if has_fields { b',' } else { b' ' });
+            self.escape_key(&field[0], options, line);
+            line.push(b'=');
+            self.escape_field_value(&field[1], options, line);
 
-                        format!("{key}={value}")
-                    })
-                    .collect();
+            has_fields = true;
+        }
 
-                line = format!("{line} {}", fields.join(","))
-            }
-            None => return Err(Error::missing_element("fields")),
+        if !has_fields {
+            return Err(Error::missing_element("fields"));
         }
 
         if let Some(ref timestamp) = self.timestamp {
-            line = format!("{line} {}", timestamp.as_string())
+            let is_valid = match timestamp {
+                Part::Value(Value::Number(n)) if !n.is_float() => n
+                    .as_int()
+                    .is_some_and(|t| options.precision.is_valid_timestamp(t)),
+                _ => true,
+            };
+
+            if !is_valid {
+                return Err(Error::timestamp_out_of_range(Position::new()));
+            }
+
+            line.push(b' ');
+            timestamp.write(false, line);
         }
 
-        self.reset();
-        Ok(line)
+        Ok(())
+    }
+}
+
+/// A read-only view of a line about to be written, see
+/// [Options::validate](crate::ser::Options::validate)
+///
+/// Values serialized as bytes are converted into strings, replacing invalid
+/// utf8
+#[derive(Debug, Clone, Copy)]
+pub struct LineView<'a> {
+    line: &'a LineBuilder,
+}
+
+impl<'a> LineView<'a> {
+    /// The measurement of the line
+    pub fn measurement(&self) -> Option<Cow<'a, str>> {
+        self.line.measurement.as_ref().map(Part::as_str)
+    }
+
+    /// The value of the tag `key`
+    pub fn tag(&self, key: &str) -> Option<Cow<'a, str>> {
+        self.tags().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Iterator over the tag keys and values of the line
+    pub fn tags(&self) -> impl Iterator<Item = (Cow<'a, str>, Cow<'a, str>)> + 'a {
+        self.line
+            .tags
+            .chunks_exact(2)
+            .map(|t| (t[0].as_str(), t[1].as_str()))
+    }
+
+    /// The value of the field `key`
+    pub fn field(&self, key: &str) -> Option<Cow<'a, Value>> {
+        self.fields().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Iterator over the field keys and values of the line
+    pub fn fields(&self) -> impl Iterator<Item = (Cow<'a, str>, Cow<'a, Value>)> + 'a {
+        self.line
+            .fields
+            .chunks_exact(2)
+            .map(|f| (f[0].as_str(), f[1].as_value()))
+    }
+
+    /// The timestamp of the line, if it is set and an integer
+    pub fn timestamp(&self) -> Option<i64> {
+        match self.line.timestamp {
+            Some(Part::Value(Value::Number(ref n))) if !n.is_float() => n.as_int(),
+            _ => None,
+        }
     }
 }
 
 pub(crate) struct Builder {
     builder: LineBuilder,
 
-    lines: Vec<String>,
+    /// Buffer the current line is built into, reused between lines
+    line: Vec<u8>,
 
     curr: Element,
+
+    /// Number of lines built so far, including invalid lines
+    lines: usize,
+
+    options: Options,
 }
 
 impl Builder {
-    pub fn new() -> Self {
+    pub fn new(options: Options) -> Self {
         Self {
             builder: LineBuilder::default(),
-            lines: Vec::new(),
+            line: Vec::new(),
             curr: Element::Measurement,
+            lines: 0,
+            options,
         }
     }
 
-    pub fn output(&self) -> String {
-        self.lines.join("\n")
+    /// Build the current line and reset the builder for the next line, also
+    /// when the line is invalid
+    pub fn build_line(&mut self) -> Result<&[u8]> {
+        if self.options.fill_timestamp && !self.builder.has_timestamp() {
+            let timestamp = current_timestamp(self.options.precision)?;
+            self.builder
+                .set_timestamp(Part::Value(Value::from(timestamp)));
+        }
+
+        self.add_static_tags();
+
+        self.lines += 1;
+        let result = self
+            .validate_line()
+            .and_then(|_| self.builder.build(&self.options, &mut self.line))
+            .and_then(|_| match self.options.max_line_length {
+                Some(max) if self.line.len() > max => Err(Error::line_too_long(self.lines, max)),
+                _ => Ok(()),
+            });
+        self.builder.reset();
+        result.map(|_| self.line.as_slice())
     }
 
-    pub fn build_line(&mut self) -> Result<()> {
-        let line = self.builder.build()?;
-        self.lines.push(line);
+    /// Add the static tags the line does not already have a value for
+    fn add_static_tags(&mut self) {
+        for (key, value) in &self.options.static_tags {
+            let exists = self
+                .builder
+                .tags
+                .chunks_exact(2)
+                .any(|tag| tag[0].as_str() == key.as_str());
+            if !exists {
+                self.builder.add_tag(Part::Value(Value::from(key.as_str())));
+                self.builder
+                    .add_tag(Part::Value(Value::from(value.as_str())));
+            }
+        }
+    }
 
-        Ok(())
+    /// Run the validation hook, if any, on the current line
+    fn validate_line(&self) -> Result<()> {
+        match self.options.validate {
+            Some(ref validate) => validate.call(LineView {
+                line: &self.builder,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Discard the current, partially built, line
+    pub fn reset(&mut self) {
+        self.builder.reset();
+        self.curr = Element::Measurement;
     }
 
     pub fn set_element(&mut self, element: Element) {
         self.curr = element;
     }
 
+    pub fn element(&self) -> &Element {
+        &self.curr
+    }
+
+    pub fn precision(&self) -> Precision {
+        self.options.precision
+    }
+
+    pub fn measurement_from_type(&self) -> bool {
+        self.options.measurement_from_type
+    }
+
     pub fn add_value<T>(&mut self, value: T)
     where
         T: Into<Value>,
@@ -186,6 +492,20 @@ impl Builder {
             return;
         }
 
+        self.add_part(Part::Value(value))
+    }
+
+    /// Add raw bytes which are written as is, apart from escaping
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        self.add_part(Part::Bytes(bytes.to_vec()))
+    }
+
+    /// Add line protocol which is written as is, without escaping
+    pub fn add_raw(&mut self, raw: Vec<u8>) {
+        self.add_part(Part::Raw(raw))
+    }
+
+    fn add_part(&mut self, value: Part) {
         match self.curr {
             Element::Measurement => self.builder.set_measurement(value),
             Element::Tags => self.builder.add_tag(value),
@@ -204,3 +524,133 @@ impl Builder {
         }
     }
 }
+
+/// Fail if any key occurs more than once in a tag or field set
+fn check_duplicates(set: &[Part]) -> Result<()> {
+    let mut keys = HashSet::with_capacity(set.len() / 2);
+    for entry in set.chunks_exact(2) {
+        let key = entry[0].sort_key();
+        if keys.contains(&key) {
+            return Err(Error::duplicate_key(
+                String::from_utf8_lossy(&key),
+                Position::new(),
+            ));
+        }
+        keys.insert(key);
+    }
+
+    Ok(())
+}
+
+/// Which entries of a tag or field set are replaced by another entry with the
+/// same key under the duplicate key policy, by the index of the entry
+fn shadowed_entries(set: &[Part], duplicates: Duplicates) -> Vec<bool> {
+    let len = set.len() / 2;
+    let mut shadowed = vec![false; len];
+    if len < 2 {
+        return shadowed;
+    }
+
+    let mut keys = HashSet::with_capacity(len);
+    let mut mark = |i: usize| shadowed[i] = !keys.insert(set[i * 2].sort_key());
+    match duplicates {
+        Duplicates::Error => (),
+        Duplicates::KeepFirst => (0..len).for_each(&mut mark),
+        Duplicates::KeepLast => (0..len).rev().for_each(&mut mark),
+    }
+
+    shadowed
+}
+
+/// Checks if the tag or field with the key `key` should be left out
+fn is_dropped(key: &Part, dropped: &BTreeSet<String>) -> bool {
+    !dropped.is_empty() && dropped.contains(key.as_str().as_ref())
+}
+
+/// Write the measurement, or a part of it, into `buf`
+fn escape_measurement(s: &[u8], options: &Options, buf: &mut Vec<u8>) {
+    escape(s, b", ", options.telegraf, buf)
+}
+
+/// Get the current system time as a unix timestamp in the given precision
+fn current_timestamp(precision: Precision) -> Result<i64> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|d| i64::try_from(d.as_nanos()).ok())
+        .and_then(|t| Precision::Nanoseconds.convert(t, precision));
+
+    timestamp.ok_or_else(|| ser::Error::custom("system time is out of the timestamp range"))
+}
+
+/// Write a single tag value into `buf`, the same way a line is written with
+/// the default options
+pub(crate) fn write_tag_value(value: Value, buf: &mut Vec<u8>) {
+    LineBuilder::default().escape_tag(&Part::Value(value), &Options::default(), buf)
+}
+
+/// Write a single field value into `buf`, the same way a line is written with
+/// the default options
+pub(crate) fn write_field_value(value: Value, buf: &mut Vec<u8>) {
+    LineBuilder::default().escape_field_value(&Part::Value(value), &Options::default(), buf)
+}
+
+/// Check that a tag can be written into an existing line, see
+/// [Line::set_tag](crate::document::Line::set_tag)
+///
+/// The key and value must not be empty, and can not contain a newline as it
+/// would end the line
+pub(crate) fn check_tag(key: &str, value: &str) -> Result<()> {
+    check_key("tag", key)?;
+    if value.is_empty() {
+        return Err(Error::missing_element("tag value"));
+    }
+    check_newline("tag value", value)
+}
+
+/// Check that a field can be written into an existing line, see
+/// [Line::set_field](crate::document::Line::set_field)
+///
+/// The key must not be empty or contain a newline, the value must be set, and
+/// floats must be finite as InfluxDB can not represent them
+pub(crate) fn check_field(key: &str, value: &Value) -> Result<()> {
+    check_key("field", key)?;
+    match value {
+        Value::None => Err(Error::missing_element("field value")),
+        Value::Number(Number::Float(n)) if !n.is_finite() => Err(Error::infinite_float()),
+        _ => Ok(()),
+    }
+}
+
+fn check_key(set: &str, key: &str) -> Result<()> {
+    if key.is_empty() {
+        return Err(Error::missing_element(format!("{set} key")));
+    }
+    check_newline(&format!("{set} key"), key)
+}
+
+fn check_newline(element: &str, s: &str) -> Result<()> {
+    match s.contains('\n') {
+        true => Err(ser::Error::custom(format!(
+            "invalid {element} `{s}`: contains a newline"
+        ))),
+        false => Ok(()),
+    }
+}
+
+/// Write `s` into `buf`, escaping the characters in `chars` with a backslash
+///
+/// If `control` is set the whitespace control characters are escaped the same
+/// way as Telegraf does
+pub(crate) fn escape(s: &[u8], chars: &[u8], control: bool, buf: &mut Vec<u8>) {
+    for &c in s {
+        match c {
+            b'\t' if control => buf.extend_from_slice(br"\t"),
+            b'\n' if control => buf.extend_from_slice(br"\n"),
+            b'\x0c' if control => buf.extend_from_slice(br"\f"),
+            b'\r' if control => buf.extend_from_slice(br"\r"),
+            c if chars.contains(&c) => buf.extend_from_slice(&[b'\\', c]),
+            c => buf.push(c),
+        }
+    }
+}
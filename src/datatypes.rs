@@ -1,7 +1,8 @@
 use std::str::FromStr;
 
+// Public as it is part of the sealed reader trait, the module itself is private
 #[derive(Debug, Clone)]
-pub(crate) enum Element {
+pub enum Element {
     Measurement,
 
     Tags,
@@ -35,4 +36,115 @@ impl Element {
     pub(crate) fn is_tags(&self) -> bool {
         matches!(self, Element::Tags)
     }
+
+    pub(crate) fn is_fields(&self) -> bool {
+        matches!(self, Element::Fields)
+    }
+
+    pub(crate) fn is_timestamp(&self) -> bool {
+        matches!(self, Element::Timestamp)
+    }
+}
+
+/// Precision of a line protocol timestamp
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// Timestamp is in seconds
+    Seconds,
+
+    /// Timestamp is in milliseconds
+    Milliseconds,
+
+    /// Timestamp is in microseconds
+    Microseconds,
+
+    /// Timestamp is in nanoseconds, the default precision used by InfluxDB
+    #[default]
+    Nanoseconds,
+}
+
+/// Earliest timestamp, in nanoseconds, accepted by InfluxDB
+/// (1677-09-21T00:12:43.145224194Z)
+pub const MIN_TIMESTAMP: i64 = -9_223_372_036_854_775_806;
+
+/// Latest timestamp, in nanoseconds, accepted by InfluxDB
+/// (2262-04-11T23:47:16.854775806Z)
+pub const MAX_TIMESTAMP: i64 = 9_223_372_036_854_775_806;
+
+impl Precision {
+    /// Number of nanoseconds in one unit of this precision
+    const fn as_nanos(&self) -> i64 {
+        match self {
+            Precision::Seconds => 1_000_000_000,
+            Precision::Milliseconds => 1_000_000,
+            Precision::Microseconds => 1_000,
+            Precision::Nanoseconds => 1,
+        }
+    }
+
+    /// Checks if a timestamp in this precision is within the range accepted
+    /// by InfluxDB, see [MIN_TIMESTAMP] and [MAX_TIMESTAMP]
+    pub const fn is_valid_timestamp(&self, timestamp: i64) -> bool {
+        let nanos = self.as_nanos();
+        MIN_TIMESTAMP / nanos <= timestamp && timestamp <= MAX_TIMESTAMP / nanos
+    }
+
+    /// Guesses the precision of a timestamp from its number of digits
+    ///
+    /// Timestamps with up to 10 digits are assumed to be in seconds, up to 13
+    /// digits in milliseconds, up to 16 digits in microseconds, and anything
+    /// longer in nanoseconds. This is only correct for timestamps after
+    /// 2001-09-09T01:46:40Z (10 digits in seconds) and is meant for current
+    /// data from sources which disagree about the precision
+    pub const fn detect(timestamp: i64) -> Precision {
+        let digits = match timestamp.unsigned_abs().checked_ilog10() {
+            Some(n) => n + 1,
+            None => 1,
+        };
+
+        match digits {
+            ..=10 => Precision::Seconds,
+            11..=13 => Precision::Milliseconds,
+            14..=16 => Precision::Microseconds,
+            _ => Precision::Nanoseconds,
+        }
+    }
+
+    /// Converts a timestamp in this precision into the precision `to`
+    ///
+    /// Converting into a coarser precision truncates the timestamp. If the
+    /// converted timestamp does not fit in an i64 None is returned instead
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let timestamp = Precision::Seconds.convert(1577836800, Precision::Milliseconds);
+    ///
+    /// println!("{timestamp:?}");
+    /// // Output: Some(1577836800000)
+    /// ```
+    pub const fn convert(&self, timestamp: i64, to: Precision) -> Option<i64> {
+        let from = self.as_nanos();
+        let to = to.as_nanos();
+
+        match from >= to {
+            true => timestamp.checked_mul(from / to),
+            false => Some(timestamp / (to / from)),
+        }
+    }
+}
+
+/// How keys which occur more than once in the same tag or field set are
+/// handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Duplicates {
+    /// Fail with [ErrorCode::DuplicateKey](crate::ErrorCode::DuplicateKey)
+    #[default]
+    Error,
+
+    /// Keep the first value of the key
+    KeepFirst,
+
+    /// Keep the last value of the key, the behavior of InfluxDB
+    KeepLast,
 }
@@ -1,657 +1,5036 @@
-use std::io;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, io,
+    marker::PhantomData,
+    ops::ControlFlow,
+    sync::Arc,
+};
 
 use regex::Regex;
 use serde::{
-    de::{self, value::StringDeserializer, DeserializeOwned, IntoDeserializer},
+    de::{
+        self,
+        value::{BorrowedStrDeserializer, StringDeserializer},
+        DeserializeOwned, IntoDeserializer,
+    },
     Deserialize,
 };
 
 use crate::{
+    builder,
+    datatypes::{Duplicates, Element, Precision},
+    line_info, point, raw,
     reader::{self, Reader},
-    Value,
+    timestamp,
+    value::datatypes::is_float_str,
+    LpType, Number, Value,
 };
 
 use self::reader::datatypes::Position;
 
-use super::error::{Error, Result};
+use super::error::{Error, ErrorCode, Result};
 
-struct Deserializer<R> {
-    reader: R,
-}
+/// Options used to customize the deserialization of line protocol
+///
+/// # Example
+///
+/// ```rust
+/// let options = Options::new()
+///     .precision(Precision::Seconds)
+///     .target_precision(Precision::Nanoseconds);
+///
+/// let line = "measurement field1=123i 1577836800";
+/// let metric: Metric = serde_influxlp::from_str_with_options(line, &options).unwrap();
+/// // metric.timestamp == 1577836800000000000
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Precision of the timestamps in the input
+    precision: Precision,
 
-impl<'de, R> Deserializer<R>
-where
-    R: Reader<'de>,
-{
-    fn from_reader(reader: R) -> Self {
-        Deserializer { reader }
-    }
+    /// Precision the timestamps are normalized into before deserialization
+    target_precision: Precision,
 
-    fn reader_position(&self) -> Position {
-        self.reader.get_position()
-    }
+    /// Whether the precision of each timestamp is guessed from its length
+    detect_precision: bool,
 
-    fn include_tags(&mut self) {
-        self.reader.include_tags();
-    }
+    /// Whether integers are rejected when deserializing into floats
+    strict_numbers: bool,
 
-    fn has_next_line(&mut self) -> bool {
-        self.reader.has_next_line()
-    }
+    /// Prefix removed from every measurement
+    measurement_prefix: Option<String>,
 
-    fn set_next_line(&mut self) {
-        self.reader.set_next_line()
-    }
+    /// Suffix removed from every measurement
+    measurement_suffix: Option<String>,
 
-    fn has_next_key(&mut self) -> Result<bool> {
-        self.reader.has_next_key()
-    }
+    /// Whether the measurement is checked against the name of the struct
+    measurement_from_type: bool,
 
-    fn get_next_key(&mut self) -> Result<String> {
-        self.reader.get_next_key()
-    }
+    /// Predicate selecting the lines to deserialize by their measurement
+    measurement_filter: Option<Filter>,
 
-    fn get_next_value(&mut self) -> Result<String> {
-        self.reader.get_next_value()
-    }
+    /// Whether fields missing from the field set struct are skipped unparsed
+    project_fields: bool,
 
-    fn discard_next_value(&mut self) -> Result<()> {
-        self.reader.discard_next_value()
-    }
-}
+    /// Whether tag values are always strings when their type is inferred
+    tags_as_strings: bool,
 
-macro_rules! deserialize_integer {
-    ($method:ident, $visit:ident) => {
-        fn $method<V>(self, visitor: V) -> Result<V::Value>
-        where
-            V: de::Visitor<'de>,
-        {
-            let mut value = self.get_next_value()?;
+    /// How keys occurring more than once in the same tag or field set are
+    /// handled, left to the target if None
+    duplicates: Option<Duplicates>,
 
-            // Check if element is a valid number
-            let re = Regex::new(r"^-?\d+i?$").unwrap();
-            let result = match re.is_match(&value) {
-                true => {
-                    // Remove integer indicator
-                    if value.ends_with("i") {
-                        value.pop();
-                    }
+    /// Whether tabs and repeated spaces are accepted between elements
+    lenient_whitespace: bool,
 
-                    value.parse()
-                }
-                false => return Err(Error::invalid_value(value, self.reader_position())),
-            };
+    /// Whether input left after the deserialized value is an error
+    deny_trailing: bool,
 
-            match result {
-                Ok(value) => visitor.$visit(value),
-                Err(_) => Err(Error::invalid_value(value, self.reader_position())),
-            }
-        }
-    };
-}
+    /// Maximum length of a line in bytes
+    max_line_length: Option<usize>,
 
-impl<'de, R: Reader<'de>> de::Deserializer<'de> for &mut Deserializer<R> {
-    type Error = Error;
+    /// Maximum number of lines read by a deserializer
+    max_lines: Option<usize>,
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let value = self.get_next_value()?;
-        let result = Value::from_any_str(&value).visit(visitor);
+    /// Maximum number of tags in a tag set
+    max_tags: Option<usize>,
 
-        match result {
-            Ok(value) => Ok(value),
-            Err(_) => Err(Error::invalid_value(value, self.reader_position())),
-        }
-    }
+    /// Maximum number of fields in a field set
+    max_fields: Option<usize>,
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let element = self.get_next_value()?;
-        let value = match Value::from_bool_str(&element) {
-            Some(value) => value.visit(visitor),
-            None => {
-                return Err(Error::invalid_type(
-                    &element,
-                    "bool",
-                    self.reader_position(),
-                ))
-            }
-        };
+    /// Field keys whose values are always parsed as the given type
+    coerce: HashMap<String, LpType>,
 
-        match value {
-            Ok(value) => Ok(value),
-            Err(_) => Err(Error::invalid_type(
-                &element,
-                "bool",
-                self.reader_position(),
-            )),
-        }
-    }
+    /// Transformation applied to each tag value
+    map_tag: Option<Mapper>,
 
-    deserialize_integer!(deserialize_i8, visit_i8);
-    deserialize_integer!(deserialize_i16, visit_i16);
-    deserialize_integer!(deserialize_i32, visit_i32);
-    deserialize_integer!(deserialize_i64, visit_i64);
-    deserialize_integer!(deserialize_u8, visit_u8);
-    deserialize_integer!(deserialize_u16, visit_u16);
-    deserialize_integer!(deserialize_u32, visit_u32);
-    deserialize_integer!(deserialize_u64, visit_u64);
+    /// Transformation applied to each field value
+    map_field: Option<Mapper>,
+}
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let element = self.get_next_value()?;
-        let value = match element.parse() {
-            Ok(value) => value,
-            Err(_) => return Err(Error::invalid_type(&element, "f32", self.reader_position())),
-        };
+/// A value transformation, see [Options::map_tag] and [Options::map_field]
+#[derive(Clone)]
+struct Mapper(Arc<MapFn>);
+
+type MapFn = dyn Fn(&str, String) -> String + Send + Sync;
 
-        visitor.visit_f32(value)
+impl fmt::Debug for Mapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Mapper")
     }
+}
 
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let element = self.get_next_value()?;
-        let value = match element.parse() {
-            Ok(value) => value,
-            Err(_) => return Err(Error::invalid_type(&element, "f64", self.reader_position())),
-        };
+/// A measurement predicate, see [Options::filter_measurement]
+#[derive(Clone)]
+struct Filter(Arc<FilterFn>);
 
-        visitor.visit_f64(value)
+type FilterFn = dyn Fn(&str) -> bool + Send + Sync;
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Filter")
     }
+}
 
-    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        let element = self.get_next_value()?;
-        let len = element.chars().count();
-        if len != 1 {
-            return Err(Error::invalid_char(element, len, self.reader_position()));
-        }
-        visitor.visit_char(element.chars().next().unwrap())
+impl Options {
+    /// Create a new set of options with the default values
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.get_next_value().and_then(|e| visitor.visit_str(&e))
+    /// Set the precision of the timestamps in the input
+    ///
+    /// Defaults to [Precision::Nanoseconds]
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
     }
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.get_next_value().and_then(|e| visitor.visit_str(&e))
+    /// Set the precision timestamps are normalized into before they are
+    /// deserialized into the target type
+    ///
+    /// Defaults to [Precision::Nanoseconds]
+    pub fn target_precision(mut self, precision: Precision) -> Self {
+        self.target_precision = precision;
+        self
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        Err(Error::unsupported("byte deserialization"))
+    /// Guess the precision of each timestamp from its number of digits, see
+    /// [Precision::detect], instead of using the configured precision
+    ///
+    /// Useful when ingesting data from multiple sources which disagree about
+    /// the precision. The timestamps are then normalized into the target
+    /// precision as usual
+    ///
+    /// Defaults to false
+    pub fn detect_precision(mut self, detect: bool) -> Self {
+        self.detect_precision = detect;
+        self
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        Err(Error::unsupported("byte buffer deserialization"))
+    /// Reject integer values, written with the `i` or `u` suffix, when
+    /// deserializing into `f32` or `f64`
+    ///
+    /// By default integers are accepted and converted into floats, failing
+    /// only if they can not be represented exactly
+    ///
+    /// Defaults to false
+    pub fn strict_numbers(mut self, strict: bool) -> Self {
+        self.strict_numbers = strict;
+        self
     }
 
-    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_some(self)
+    /// Remove `prefix` from the measurement of every line which starts with it
+    ///
+    /// The counterpart of
+    /// [ser::Options::measurement_prefix](crate::ser::Options::measurement_prefix)
+    pub fn strip_measurement_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.measurement_prefix = Some(prefix.into());
+        self
     }
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_unit()
+    /// Remove `suffix` from the measurement of every line which ends with it
+    ///
+    /// The counterpart of
+    /// [ser::Options::measurement_suffix](crate::ser::Options::measurement_suffix)
+    pub fn strip_measurement_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.measurement_suffix = Some(suffix.into());
+        self
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_unit()
+    /// Require the measurement of each line to equal the name of the struct it
+    /// is deserialized into, unless the struct has a `measurement` member of
+    /// its own
+    ///
+    /// The counterpart of
+    /// [ser::Options::measurement_from_type](crate::ser::Options::measurement_from_type).
+    /// Lines with another measurement fail with
+    /// [ErrorCode::InvalidValue](crate::ErrorCode::InvalidValue)
+    ///
+    /// Defaults to false
+    pub fn measurement_from_type(mut self, from_type: bool) -> Self {
+        self.measurement_from_type = from_type;
+        self
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    /// Only deserialize lines whose measurement is one of `measurements`
+    ///
+    /// See [Options::filter_measurement] for more information
+    pub fn only_measurements<I, S>(self, measurements: I) -> Self
     where
-        V: de::Visitor<'de>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
     {
-        Err(Error::unsupported("newtype struct deserialization"))
+        let measurements: HashSet<String> = measurements.into_iter().map(Into::into).collect();
+        self.filter_measurement(move |measurement| measurements.contains(measurement))
     }
 
-    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    /// Only deserialize lines whose measurement `filter` returns true for
+    ///
+    /// The measurement is checked before the rest of the line is parsed, and
+    /// lines which are filtered out are skipped without parsing their tags and
+    /// fields. The measurement is checked after removing the prefix and
+    /// suffix, see [Options::strip_measurement_prefix]. Only applies when
+    /// deserializing multiple lines, e.g., into a `Vec` or with an iterator
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new().filter_measurement(|measurement| measurement.starts_with("cpu"));
+    ///
+    /// let lines = "cpu field1=1i\nmem field1=2i\ncpu_total field1=3i";
+    /// let metrics: Vec<Metric> = serde_influxlp::from_str_with_options(lines, &options).unwrap();
+    /// // metrics.len() == 2
+    /// ```
+    pub fn filter_measurement<F>(mut self, filter: F) -> Self
     where
-        V: de::Visitor<'de>,
+        F: Fn(&str) -> bool + Send + Sync + 'static,
     {
-        visitor.visit_seq(SeqDeserializer::new(self))
+        self.measurement_filter = Some(Filter(Arc::new(filter)));
+        self
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.deserialize_seq(visitor)
+    /// Skip the values of fields which the field set struct has no member for,
+    /// instead of parsing them only to be ignored
+    ///
+    /// Speeds up deserializing a few fields out of lines with many. Only the
+    /// field set of structs is projected, maps still receive every field.
+    /// Unknown fields are then never seen by the struct, so
+    /// `#[serde(deny_unknown_fields)]` has no effect on its field set
+    ///
+    /// Defaults to false
+    pub fn project_fields(mut self, project: bool) -> Self {
+        self.project_fields = project;
+        self
     }
 
-    fn deserialize_tuple_struct<V>(
-        self,
-        _name: &'static str,
-        _len: usize,
-        _visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        Err(Error::unsupported("tuple struct deserialization"))
+    /// Always deserialize tag values as strings where the type of the value is
+    /// inferred, e.g., into [Value] or a `#[serde(flatten)]` map, as tag
+    /// values are always strings in the line protocol
+    ///
+    /// By default the type of tag values is inferred like that of field
+    /// values, so `tag1=123` becomes a number. Tag values are still parsed
+    /// into the type of the member they are deserialized into
+    ///
+    /// Defaults to false
+    pub fn tags_as_strings(mut self, as_strings: bool) -> Self {
+        self.tags_as_strings = as_strings;
+        self
     }
 
-    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_map(self)
+    /// Set how keys which occur more than once in the same tag or field set,
+    /// e.g., `field1=1i,field1=2i`, are handled
+    ///
+    /// By default every occurrence is handed to the target, so maps keep the
+    /// last value while structs fail. Setting a policy makes the outcome the
+    /// same for every target. Each tag and field set is then read in full
+    /// before it is deserialized. [Duplicates::Error] fails with
+    /// [ErrorCode::DuplicateKey](crate::ErrorCode::DuplicateKey) at the
+    /// position of the repeated key
+    pub fn duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = Some(duplicates);
+        self
     }
 
-    fn deserialize_struct<V>(
-        self,
-        _name: &'static str,
-        fields: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        if fields.contains(&"tags") {
-            self.include_tags();
-        };
+    /// Accept tabs and repeated spaces between the measurement, tag set, field
+    /// set, and timestamp, instead of failing with
+    /// [ErrorCode::UnexpectedChar](crate::ErrorCode::UnexpectedChar) or
+    /// misreading the line
+    ///
+    /// Useful for hand-edited files. Windows line endings, `\r\n`, are always
+    /// accepted
+    ///
+    /// Defaults to false
+    pub fn lenient_whitespace(mut self, lenient: bool) -> Self {
+        self.lenient_whitespace = lenient;
+        self
+    }
 
-        visitor.visit_map(self)
+    /// Fail with [ErrorCode::TrailingData](crate::ErrorCode::TrailingData)
+    /// if the input continues after the deserialized value, e.g., when
+    /// deserializing a single struct from input containing several lines
+    ///
+    /// Comment lines and whitespace are allowed. Used by the `from_*`
+    /// functions, see [Deserializer::end] for other uses
+    ///
+    /// Defaults to false, the rest of the input is ignored
+    pub fn deny_trailing(mut self, deny: bool) -> Self {
+        self.deny_trailing = deny;
+        self
     }
 
-    fn deserialize_enum<V>(
-        self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_enum(self)
+    /// Fail with [ErrorCode::LineTooLong](crate::ErrorCode::LineTooLong) on
+    /// lines longer than `max` bytes, not counting the line ending
+    ///
+    /// The rest of a line which is too long is never read, which bounds the
+    /// memory used by a single line. Useful when deserializing untrusted
+    /// input, e.g., the body of a request to an ingest endpoint
+    ///
+    /// Defaults to no limit
+    pub fn max_line_length(mut self, max: usize) -> Self {
+        self.max_line_length = Some(max);
+        self
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        Err(Error::unsupported("identifier deserialization"))
+    /// Fail with [ErrorCode::MaxLines](crate::ErrorCode::MaxLines) when the
+    /// input contains more than `max` lines
+    ///
+    /// Comment lines and empty lines are not counted, lines skipped by the
+    /// measurement filter are. The limit applies to
+    /// each deserializer, lines fed to a [Parser] or deserialized in parallel
+    /// are deserialized one at a time and are therefore not limited
+    ///
+    /// Defaults to no limit
+    pub fn max_lines(mut self, max: usize) -> Self {
+        self.max_lines = Some(max);
+        self
     }
 
-    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.discard_next_value()?;
-        visitor.visit_unit()
+    /// Fail with [ErrorCode::MaxTags](crate::ErrorCode::MaxTags) on lines
+    /// with more than `max` tags
+    ///
+    /// Defaults to no limit
+    pub fn max_tags(mut self, max: usize) -> Self {
+        self.max_tags = Some(max);
+        self
     }
-}
 
-impl<'a, R: Reader<'a>> de::MapAccess<'a> for Deserializer<R> {
-    type Error = Error;
+    /// Fail with [ErrorCode::MaxFields](crate::ErrorCode::MaxFields) on lines
+    /// with more than `max` fields
+    ///
+    /// Defaults to no limit
+    pub fn max_fields(mut self, max: usize) -> Self {
+        self.max_fields = Some(max);
+        self
+    }
 
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    /// Force the values of the field `key` to be parsed as `typ` instead of
+    /// inferring the type from the value
+    ///
+    /// Useful when producers are inconsistent, e.g., a status code which
+    /// should always be a string but is sometimes written as a number. Only
+    /// affects targets which do not specify a type themselves, such as
+    /// [Value]
+    pub fn coerce(mut self, key: impl Into<String>, typ: LpType) -> Self {
+        self.coerce.insert(key.into(), typ);
+        self
+    }
+
+    /// Transform each tag value with `map` before it is deserialized
+    ///
+    /// `map` is called with the key and value of the tag and returns the
+    /// value to use instead. Useful for cleaning up input in one place, such
+    /// as trimming whitespace or decoding percent-encoded values
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new().map_tag(|_, value| value.to_lowercase());
+    ///
+    /// let line = "measurement,tag1=Value field1=123i";
+    /// let metric: Metric = serde_influxlp::from_str_with_options(line, &options).unwrap();
+    /// // metric.tags.tag1 == "value"
+    /// ```
+    pub fn map_tag<F>(mut self, map: F) -> Self
     where
-        K: de::DeserializeSeed<'a>,
+        F: Fn(&str, String) -> String + Send + Sync + 'static,
     {
-        if !self.has_next_key()? {
-            return Ok(None);
-        }
-
-        let key = self.get_next_key()?;
-        seed.deserialize(StringDeserializer::new(key)).map(Some)
+        self.map_tag = Some(Mapper(Arc::new(map)));
+        self
     }
 
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    /// Transform each field value with `map` before it is deserialized
+    ///
+    /// `map` is called with the key of the field and the value as written,
+    /// without the quotes of string values, e.g., `123i` or `true`. The
+    /// returned value is then parsed as usual
+    pub fn map_field<F>(mut self, map: F) -> Self
     where
-        V: de::DeserializeSeed<'a>,
+        F: Fn(&str, String) -> String + Send + Sync + 'static,
     {
-        seed.deserialize(self)
+        self.map_field = Some(Mapper(Arc::new(map)));
+        self
     }
 }
 
-struct SeqDeserializer<'a, R: 'a> {
-    de: &'a mut Deserializer<R>,
+/// A deserializer reading InfluxDB v2 Line protocol
+///
+/// Created from a string, a slice, or a reader. Can be driven manually, e.g.,
+/// with a [DeserializeSeed](serde::de::DeserializeSeed), or turned into an
+/// iterator over the lines with [Deserializer::into_iter]
+///
+/// # Example
+///
+/// ```rust
+/// let mut deserializer = Deserializer::from_str(input).options(options);
+/// let metrics = Vec::<Metric>::deserialize(&mut deserializer)?;
+///
+/// let deserializer = Deserializer::from_reader(File::open("lines.txt")?);
+/// for metric in deserializer.into_iter::<Metric>() {
+///     println!("{:?}", metric?);
+/// }
+/// ```
+pub struct Deserializer<R> {
+    reader: R,
 
-    first: bool,
-}
+    options: Options,
 
-impl<'a, R: 'a> SeqDeserializer<'a, R> {
-    fn new(de: &'a mut Deserializer<R>) -> Self {
-        SeqDeserializer { de, first: true }
-    }
-}
+    /// Type the next field value is coerced into, if any
+    coerce_to: Option<LpType>,
 
-impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for SeqDeserializer<'a, R> {
-    type Error = Error;
+    /// Whether the next value is a value of an element, as opposed to a line
+    /// or the tag / field set
+    at_value: bool,
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
-    where
-        T: de::DeserializeSeed<'de>,
-    {
-        // Skip the check and next line fetching if this is the first access
-        match !self.first {
-            true => {
-                if !self.de.has_next_line() {
-                    return Ok(None);
-                }
+    /// Transformation and key of the next tag or field value, if any
+    map_with: Option<(Mapper, String)>,
 
-                self.de.set_next_line();
-            }
-            false => self.first = false,
-        }
+    /// Measurement of the current line, read ahead to select the variant of an
+    /// enum, which is yet to be passed to the variant
+    measurement: Option<String>,
 
-        seed.deserialize(&mut *self.de).map(Some)
-    }
-}
+    /// Measurement of the current line, parsed ahead to check it against the
+    /// measurement filter
+    parsed_measurement: Option<Vec<u8>>,
 
-impl<'a, R: Reader<'a>> de::EnumAccess<'a> for &mut Deserializer<R> {
-    type Error = Error;
-    type Variant = Self;
+    /// Byte offset of the start of the line the measurement was parsed ahead
+    /// from
+    line_start: usize,
 
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
-    where
-        V: de::DeserializeSeed<'a>,
-    {
-        let variant_name = self.get_next_value()?;
-        seed.deserialize(variant_name.into_deserializer())
-            .map(|v| (v, self))
-    }
+    /// Members of the field set struct being deserialized, when projecting
+    /// fields
+    projection: Option<&'static [&'static str]>,
+
+    /// Remaining keys and values of the tag or field set read in full to
+    /// handle duplicate keys, along with the element they belong to
+    set: Option<(Element, Entries)>,
+
+    /// Value of the read set to deserialize next
+    set_value: Option<Vec<u8>>,
+
+    /// Whether the value of the read set to deserialize next is a quoted
+    /// string field value
+    set_value_quoted: bool,
+
+    /// Position after the value of the read set deserialized last, where its
+    /// errors are reported instead of the position of the reader
+    set_position: Option<Position>,
+
+    /// Number of lines started so far, see [Options::max_lines]
+    lines: usize,
+
+    /// Number of tags read in the current line, see [Options::max_tags]
+    tags: usize,
+
+    /// Number of fields read in the current line, see [Options::max_fields]
+    fields: usize,
 }
 
-impl<'a, R: Reader<'a>> de::VariantAccess<'a> for &mut Deserializer<R> {
-    type Error = Error;
+/// Keys and values of a tag or field set, see [Options::duplicates]
+type Entries = VecDeque<SetEntry>;
 
-    fn unit_variant(self) -> Result<()> {
-        Ok(())
-    }
+/// A key and value of a tag or field set read in full
+struct SetEntry {
+    key: String,
 
-    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
-    where
-        T: de::DeserializeSeed<'a>,
-    {
-        seed.deserialize(self)
-    }
+    value: Vec<u8>,
 
-    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'a>,
-    {
-        de::Deserializer::deserialize_seq(self, visitor)
+    /// Whether the value is a quoted string field value
+    quoted: bool,
+
+    /// Position of the reader before the key
+    key_position: Position,
+
+    /// Position of the reader after the value
+    value_position: Position,
+}
+
+pub use crate::reader::{BufReadReader, IoReader, SliceReader};
+
+/// Input a [Deserializer] reads from, either a [SliceReader], [IoReader], or
+/// [BufReadReader]
+///
+/// Useful for naming a deserializer generically, e.g., `Deserializer<R>`
+/// where `R: Read<'de>`. The trait is sealed, it can not be implemented
+/// outside of this crate
+pub trait Read<'de>: Reader<'de> {}
+
+impl<'de, R> Read<'de> for R where R: Reader<'de> {}
+
+impl<'a> Deserializer<reader::SliceReader<'a>> {
+    /// Create a new deserializer reading from a string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'a str) -> Self {
+        Self::from_slice(s.as_bytes())
     }
 
-    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'a>,
-    {
-        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    /// Create a new deserializer reading from a slice of bytes
+    pub fn from_slice(s: &'a [u8]) -> Self {
+        Self::new(reader::SliceReader::new(s))
     }
 }
 
-/// Deserialize a valid line protocol from a reader into a struct `T`
-///
-/// # Example
-///
-/// Below is an example of the least required for deserialization to succeed
-///
-/// ```rust
-/// use serde_influxlp::Value;
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Fields {
-///     pub field1: i32,
-/// }
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Metric {
-///     pub measurement: String,
-///
-///     pub fields: Fields,
-/// }
-///
-/// fn main() {
-///     let file = File::open("lines.txt")?;
-///
-///     let metric: Metric = serde_influxlp::from_slice(file).unwrap();
-///     println!("{metric:#?}");
-///     // Output Metric {
-///     //     measurement: "measurement",
-///     //     fields: Fields {
-///     //         field1: 123,
-///     //     },
-///     // }
-/// }
-/// ```
-pub fn from_reader<T>(r: impl io::Read) -> Result<T>
+impl<R> Deserializer<reader::IoReader<R>>
 where
-    T: DeserializeOwned,
+    R: io::Read,
 {
-    let mut deserializer = Deserializer::from_reader(reader::IoReader::new(r));
-    let value = T::deserialize(&mut deserializer)?;
-
-    Ok(value)
+    /// Create a new deserializer reading from an [io::Read]
+    pub fn from_reader(r: R) -> Self {
+        Self::new(reader::IoReader::new(r))
+    }
 }
 
-/// Deserialize a valid line protocol string as bytes into a struct `T`
-///
-/// # Example
-///
-/// Below is an example of the least required for deserialization to succeed
-///
-/// ```rust
-/// use serde_influxlp::Value;
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Fields {
-///     pub field1: i32,
-/// }
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Metric {
-///     pub measurement: String,
-///
-///     pub fields: Fields,
-/// }
-///
-/// fn main() {
-///     let slice = "measurement field1=123i".as_bytes();
-///
-///     let metric: Metric = serde_influxlp::from_slice(slice).unwrap();
-///     println!("{metric:#?}");
-///     // Output Metric {
-///     //     measurement: "measurement",
-///     //     fields: Fields {
-///     //         field1: 123,
-///     //     },
-///     // }
-/// }
-/// ```
-pub fn from_slice<'a, T>(s: &'a [u8]) -> Result<T>
+impl<R> Deserializer<reader::BufReadReader<R>>
 where
-    T: Deserialize<'a>,
+    R: io::BufRead,
 {
-    let mut deserializer = Deserializer::from_reader(reader::SliceReader::new(s));
-    let value = T::deserialize(&mut deserializer)?;
-
-    Ok(value)
+    /// Create a new deserializer reading from an [io::BufRead]
+    pub fn from_bufread(r: R) -> Self {
+        Self::new(reader::BufReadReader::new(r))
+    }
 }
 
-/// Deserialize a valid line protocol string into a struct `T`
-///
-/// # Example
-///
-/// Below is an example of the least required for deserialization to succeed
-///
-/// ```rust
-/// use serde_influxlp::Value;
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Fields {
-///     pub field1: i32,
-/// }
-///
-/// #[derive(Debug, Serialize, Deserialize)]
-/// pub struct Metric {
-///     pub measurement: String,
-///
-///     pub fields: Fields,
-/// }
-///
-/// fn main() {
-///     let line = "measurement field1=123i";
-///
-///     let metric: Metric = serde_influxlp::from_str(line).unwrap();
-///     println!("{metric:#?}");
-///     // Output Metric {
-///     //     measurement: "measurement",
-///     //     fields: Fields {
-///     //         field1: 123,
-///     //     },
-///     // }
-/// }
-/// ```
-pub fn from_str<'a, T>(s: &'a str) -> Result<T>
+impl<'de, R> Deserializer<R>
 where
-    T: Deserialize<'a>,
+    R: Read<'de>,
 {
-    from_slice(s.as_bytes())
-}
+    /// Use the given [Options] for the rest of the deserialization
+    pub fn options(mut self, options: Options) -> Self {
+        self.reader.set_lenient(options.lenient_whitespace);
+        self.reader.line_length().set_max(options.max_line_length);
+        self.options = options;
+        self
+    }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Check that the whole input has been deserialized, apart from comment
+    /// lines and whitespace
+    ///
+    /// Fails with [ErrorCode::TrailingData](crate::ErrorCode::TrailingData)
+    /// otherwise. Useful after deserializing a single line, where any lines
+    /// following it would be silently ignored
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut deserializer = Deserializer::from_str("metric field1=1i\nmetric field1=2i");
+    /// let metric = Metric::deserialize(&mut deserializer)?;
+    ///
+    /// assert!(deserializer.end().is_err());
+    /// ```
+    pub fn end(&mut self) -> Result<()> {
+        if !self.has_next_line()? {
+            return Ok(());
+        }
 
-    #[derive(Debug, serde::Deserialize)]
-    #[serde(rename_all = "lowercase")]
-    enum Exposure {
-        Public,
-        Private,
+        // The position only moves on to the next line once it is deserialized
+        let mut position = self.reader_position();
+        let line = self.reader.get_line();
+        if position.line < line {
+            position.next_line();
+            position.line = line;
+        }
+
+        Err(Error::trailing_data(position))
     }
 
-    #[derive(Debug, serde::Deserialize)]
-    #[allow(dead_code)]
-    struct Tags {
-        pub tag1: i32,
+    /// Turn the deserializer into an iterator lazily deserializing each line
+    /// into a struct `T`
+    ///
+    /// Comment lines and empty lines are skipped. Iteration stops after the
+    /// first error
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'de, R, T>
+    where
+        T: Deserialize<'de>,
+    {
+        StreamDeserializer {
+            de: self,
+            first: true,
+            failed: false,
+            lifetime: PhantomData,
+            output: PhantomData,
+        }
+    }
 
-        pub tag2: Option<String>,
+    fn new(reader: R) -> Self {
+        Self::with_options(reader, Options::default())
+    }
 
-        pub tag3: Exposure,
+    fn with_options(mut reader: R, options: Options) -> Self {
+        reader.set_lenient(options.lenient_whitespace);
+        reader.line_length().set_max(options.max_line_length);
+
+        Deserializer {
+            reader,
+            options,
+            coerce_to: None,
+            at_value: false,
+            map_with: None,
+            measurement: None,
+            parsed_measurement: None,
+            line_start: 0,
+            projection: None,
+            set: None,
+            set_value: None,
+            set_position: None,
+            set_value_quoted: false,
+            lines: 0,
+            tags: 0,
+            fields: 0,
+        }
     }
 
-    #[derive(Debug, serde::Deserialize)]
-    #[allow(dead_code)]
-    struct Fields {
-        pub field1: i32,
+    fn reader_position(&self) -> Position {
+        match &self.set_position {
+            Some(position) => position.clone(),
+            None => self.reader.get_position(),
+        }
+    }
 
-        pub field2: bool,
+    fn include_tags(&mut self) {
+        self.reader.include_tags();
     }
 
-    #[derive(Debug, serde::Deserialize)]
-    #[allow(dead_code)]
-    struct Metric {
-        pub measurement: String,
+    fn has_next_line(&mut self) -> Result<bool> {
+        let has_next_line = self.reader.has_next_line();
+        self.check_read(Ok(has_next_line))
+    }
 
-        pub tags: Tags,
+    /// Replace the result of reading from a line which is too long, or from
+    /// input which failed to be read, such a line is cut short and either fails
+    /// to parse or is misread
+    fn check_read<T>(&mut self, result: Result<T>) -> Result<T> {
+        if let Some(e) = self.reader.take_io_error() {
+            let mut error = Error::from(e);
+            error.position = self.reader_position();
+            return Err(error);
+        }
 
-        pub fields: Fields,
+        let line_length = self.reader.line_length();
+        match (line_length.is_exceeded(), line_length.max()) {
+            (true, Some(max)) => {
+                let position = self.reader_position();
+                let mut error = Error::line_too_long(position.line, max);
+                error.position = position;
+                Err(error)
+            }
+            _ => result,
+        }
+    }
 
-        pub timestamp: Option<i64>,
+    /// Count a key of the tag or field set `element` against the maximum number
+    /// of tags or fields
+    fn count_key(&mut self, element: &Element, position: &Position) -> Result<()> {
+        match element {
+            Element::Tags => {
+                self.tags += 1;
+                match self.options.max_tags {
+                    Some(max) if self.tags > max => Err(Error::max_tags(max, position.clone())),
+                    _ => Ok(()),
+                }
+            }
+            Element::Fields => {
+                self.fields += 1;
+                match self.options.max_fields {
+                    Some(max) if self.fields > max => Err(Error::max_fields(max, position.clone())),
+                    _ => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
     }
 
-    #[test]
-    fn test_de_from_str() {
-        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789";
-        let result = from_str::<Metric>(line);
-        assert!(result.is_ok());
+    /// Deserialize the next key of a map, see [de::MapAccess::next_key_seed]
+    fn next_key<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        // The measurement used to select the variant of an enum is passed on to the
+        // variant
+        if self.measurement.is_some() {
+            self.at_value = true;
+            return seed
+                .deserialize("measurement".into_deserializer())
+                .map(Some);
+        }
 
-        let lines = r#"
-        metric1,tag1=123,tag3=public field1=321,field2=t 123456789
-        #comment line
+        let mut position;
+        let key = loop {
+            if let Some((_, entries)) = &mut self.set {
+                let Some(entry) = entries.pop_front() else {
+                    self.set = None;
+                    self.set_position = None;
+                    return Ok(None);
+                };
 
-        metric2,tag1=321,tag2=hello\ world,tag3=private field1=123,field2=True 123456789
+                position = entry.key_position;
+                match self.projection {
+                    Some(fields) if !fields.contains(&entry.key.as_str()) => continue,
+                    _ => {
+                        self.set_value = Some(entry.value);
+                        self.set_value_quoted = entry.quoted;
+                        self.set_position = Some(entry.value_position);
+                        break entry.key;
+                    }
+                }
+            }
 
-        #another comment line
+            if !self.has_next_key()? {
+                return Ok(None);
+            }
 
-        "#;
-        let result = from_str::<Vec<Metric>>(lines);
-        assert!(result.is_ok());
+            // The tag and field sets are entered through their own key before the keys of
+            // the actual tags and fields
+            self.at_value = match (
+                self.reader.get_next_element(),
+                self.reader.get_prev_element(),
+            ) {
+                (Element::Tags, prev) => prev.is_tags(),
+                (Element::Fields, prev) => prev.is_fields(),
+                _ => true,
+            };
 
-        let result = result.unwrap();
-        assert_eq!(result.len(), 2);
+            let is_set = self.reader.get_next_element().is_tags()
+                || self.reader.get_next_element().is_fields();
+            if let Some(duplicates) = self.options.duplicates.filter(|_| self.at_value && is_set) {
+                self.read_set(duplicates)?;
+                continue;
+            }
 
-        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789";
-        let result = from_str::<Vec<Metric>>(line);
-        assert!(result.is_ok());
+            position = self.reader_position();
+            if self.at_value {
+                let element = self.reader.get_next_element().clone();
+                self.count_key(&element, &position)?;
+            }
 
-        let result = result.unwrap();
-        assert_eq!(result.len(), 1);
+            let key = self.get_next_key()?;
+            match self.projection {
+                Some(fields) if self.at_value && !fields.contains(&key.as_str()) => {
+                    self.reader.skip_field_value()?;
+                }
+                _ => break key,
+            }
+        };
+        self.coerce_to = match self.next_element() {
+            Element::Fields => self.options.coerce.get(&key).copied(),
+            _ => None,
+        };
 
-        let line = r#"
-                
-        "#;
-        let result = from_str::<Metric>(line);
-        assert!(result.is_err());
+        let map = match self.next_element() {
+            Element::Tags if self.at_value => self.options.map_tag.clone(),
+            Element::Fields if self.at_value => self.options.map_field.clone(),
+            _ => None,
+        };
+        self.map_with = map.map(|map| (map, key.clone()));
+
+        // Structs rejecting unknown keys fail while deserializing the key, which knows
+        // nothing of the position
+        seed.deserialize(StringDeserializer::new(key))
+            .map(Some)
+            .map_err(|mut e: Error| {
+                if matches!(e.code, ErrorCode::UnknownKey { .. }) {
+                    e.position = position;
+                }
+                e
+            })
     }
 
-    #[test]
-    fn test_de_from_reader() {
+    fn set_next_line(&mut self) {
+        self.tags = 0;
+        self.fields = 0;
+        self.at_value = false;
+        self.measurement = None;
+        self.parsed_measurement = None;
+        self.set = None;
+        self.set_value = None;
+        self.set_position = None;
+        self.reader.set_next_line()
+    }
+
+    /// Start deserializing the line the reader is positioned at, returns false
+    /// if no line is left
+    ///
+    /// Counts the line against the maximum number of lines and skips the lines
+    /// whose measurement is rejected by the measurement filter. Only the
+    /// measurement of each line is parsed, it is then kept for the line to be
+    /// deserialized as usual
+    fn begin_line(&mut self) -> Result<bool> {
+        loop {
+            self.lines += 1;
+            if let Some(max) = self.options.max_lines.filter(|max| self.lines > *max) {
+                return Err(Error::max_lines(max, self.reader_position()));
+            }
+
+            let Some(filter) = self.options.measurement_filter.clone() else {
+                return Ok(true);
+            };
+
+            let measurement = self.parse_measurement();
+            let stripped =
+                self.strip_measurement(String::from_utf8_lossy(&measurement).into_owned());
+            if (filter.0)(&stripped) {
+                self.parsed_measurement = Some(measurement);
+                return self.check_read(Ok(true));
+            }
+
+            self.reader.skip_line();
+            if !self.has_next_line()? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Take the next value if it has already been read, i.e., a value of a set
+    /// read by [Deserializer::read_set] or the measurement parsed ahead by
+    /// [Deserializer::begin_line]
+    fn take_read_value(&mut self) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.set_value.take() {
+            return Ok(Some(value));
+        }
+
+        if !self.reader.get_next_element().is_measurement() {
+            return Ok(None);
+        }
+
+        let Some(measurement) = self.parsed_measurement.take() else {
+            return Ok(None);
+        };
+
+        self.reader.determine_next_element()?;
+        Ok(Some(measurement))
+    }
+
+    fn has_next_key(&mut self) -> Result<bool> {
+        self.reader.has_next_key()
+    }
+
+    fn get_next_key(&mut self) -> Result<String> {
+        self.reader.get_next_key()
+    }
+
+    /// Read the rest of the tag or field set, resolving duplicate keys by
+    /// `duplicates`
+    fn read_set(&mut self, duplicates: Duplicates) -> Result<()> {
+        let element = self.reader.get_next_element().clone();
+
+        let mut entries = Entries::new();
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        loop {
+            let key_position = self.reader_position();
+            self.count_key(&element, &key_position)?;
+            let key = self.get_next_key()?;
+            let quoted = element.is_fields() && self.reader.peek_char()? == b'"';
+            let value = self.reader.get_next_value_bytes()?;
+            let value_position = self.reader_position();
+
+            match indices.get(&key) {
+                Some(&index) => match duplicates {
+                    Duplicates::Error => return Err(Error::duplicate_key(key, key_position)),
+                    Duplicates::KeepFirst => {}
+                    Duplicates::KeepLast => {
+                        let entry = &mut entries[index];
+                        (entry.value, entry.quoted) = (value, quoted);
+                        entry.value_position = value_position;
+                    }
+                },
+                None => {
+                    indices.insert(key.clone(), entries.len());
+                    entries.push_back(SetEntry {
+                        key,
+                        value,
+                        quoted,
+                        key_position,
+                        value_position,
+                    });
+                }
+            }
+
+            if !self.has_next_key()? {
+                break;
+            }
+        }
+
+        self.set = Some((element, entries));
+        Ok(())
+    }
+
+    /// The element of the next value, which is behind the reader if the value
+    /// has already been read
+    fn next_element(&self) -> &Element {
+        match (&self.set, &self.set_value) {
+            (Some((element, _)), Some(_)) => element,
+            _ => self.reader.get_next_element(),
+        }
+    }
+
+    fn next_is_timestamp(&self) -> bool {
+        self.next_element().is_timestamp()
+    }
+
+    fn get_next_value(&mut self) -> Result<String> {
+        match self.next_is_timestamp() {
+            true => {
+                let timestamp = self.get_next_timestamp()?;
+                Ok(itoa::Buffer::new().format(timestamp).to_owned())
+            }
+            false => {
+                let is_measurement = self.next_element().is_measurement();
+                let position = self.reader_position();
+                let value = match self.take_read_value()? {
+                    Some(measurement) => reader::into_string(measurement, position)?,
+                    None => self.reader.get_next_value()?,
+                };
+                if is_measurement {
+                    return Ok(self.strip_measurement(value));
+                }
+
+                match self.map_with.take() {
+                    Some((map, key)) => Ok((map.0)(&key, value)),
+                    None => Ok(value),
+                }
+            }
+        }
+    }
+
+    /// Fetch the next value as raw bytes, which are only converted into a
+    /// string when the value has to be transformed
+    fn get_next_value_bytes(&mut self) -> Result<Vec<u8>> {
+        let is_stripped = self.next_element().is_measurement()
+            && (self.options.measurement_prefix.is_some()
+                || self.options.measurement_suffix.is_some());
+
+        if self.map_with.is_some() || is_stripped {
+            return self.get_next_value().map(String::into_bytes);
+        }
+
+        match self.take_read_value()? {
+            Some(measurement) => Ok(measurement),
+            None => self.reader.get_next_value_bytes(),
+        }
+    }
+
+    /// Fetch the next value borrowed from the input, None if it has to be
+    /// transformed or unescaped first
+    fn get_next_value_borrowed(&mut self) -> Result<Option<&'de str>> {
+        let is_stripped = self.next_element().is_measurement()
+            && (self.options.measurement_prefix.is_some()
+                || self.options.measurement_suffix.is_some()
+                || self.parsed_measurement.is_some());
+        if self.map_with.is_some() || is_stripped || self.set_value.is_some() {
+            return Ok(None);
+        }
+
+        let position = self.reader_position();
+        match self.reader.get_next_value_borrowed()? {
+            Some(bytes) => match std::str::from_utf8(bytes) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) => Err(Error::invalid_utf8(e, position)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the next field value keeping the type it is written with, see
+    /// [Value::from_field_str]
+    fn get_next_field_value(&mut self) -> Result<Value> {
+        let value = match self.set_value.take() {
+            Some(value) if self.set_value_quoted => {
+                return reader::into_string(value, self.reader_position()).map(Value::String);
+            }
+            Some(value) => reader::into_string(value, self.reader_position())?,
+            None => {
+                let position = self.reader_position();
+                reader::into_string(self.reader.get_raw(false)?, position)?
+            }
+        };
+
+        Value::from_field_str(&value)
+            .ok_or_else(|| Error::invalid_value(value, self.reader_position()))
+    }
+
+    /// Parse the measurement at the start of the current line, remembering
+    /// where the line starts
+    fn parse_measurement(&mut self) -> Vec<u8> {
+        self.line_start = self.reader.get_offset();
+        self.reader.parse_measurement()
+    }
+
+    /// Parse the measurement of the current line ahead of deserializing it,
+    /// it is then kept for the line to be deserialized as usual
+    fn peek_measurement(&mut self) -> Result<String> {
+        let measurement = match self.parsed_measurement.clone() {
+            Some(measurement) => measurement,
+            None => {
+                let measurement = self.parse_measurement();
+                self.parsed_measurement = Some(measurement.clone());
+                measurement
+            }
+        };
+
+        let measurement = reader::into_string(measurement, self.reader_position())?;
+        Ok(self.strip_measurement(measurement))
+    }
+
+    /// Deserialize the rest of the line exactly as written, see
+    /// [RawLine](crate::RawLine)
+    fn deserialize_raw_line<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let position = self.reader_position();
+
+        // A measurement parsed ahead is escaped again to restore the line
+        let measurement = self.parsed_measurement.take();
+        if measurement.is_none() {
+            if let Some(raw) = self.reader.get_raw_borrowed(true)? {
+                let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+                let raw = std::str::from_utf8(raw).map_err(|e| Error::invalid_utf8(e, position))?;
+                return visitor.visit_newtype_struct(BorrowedStrDeserializer::new(raw));
+            }
+        }
+
+        let mut raw = Vec::new();
+        if let Some(measurement) = measurement {
+            builder::escape(&measurement, b", ", false, &mut raw);
+        }
+        raw.extend(self.reader.get_raw(true)?);
+        if raw.ends_with(b"\r") {
+            raw.pop();
+        }
+
+        let raw = reader::into_string(raw, position)?;
+        visitor.visit_newtype_struct(StringDeserializer::new(raw))
+    }
+
+    /// Deserialize the next value exactly as written, see
+    /// [RawValue](crate::RawValue)
+    fn deserialize_raw_value<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let position = self.reader_position();
+        if let Some(raw) = self.reader.get_raw_borrowed(false)? {
+            let raw = std::str::from_utf8(raw).map_err(|e| Error::invalid_utf8(e, position))?;
+            return visitor.visit_newtype_struct(BorrowedStrDeserializer::new(raw));
+        }
+
+        let raw = reader::into_string(self.reader.get_raw(false)?, position)?;
+        visitor.visit_newtype_struct(StringDeserializer::new(raw))
+    }
+
+    /// Remove the configured prefix and suffix from a measurement
+    fn strip_measurement(&self, mut measurement: String) -> String {
+        if let Some(ref suffix) = self.options.measurement_suffix {
+            if measurement.ends_with(suffix.as_str()) {
+                measurement.truncate(measurement.len() - suffix.len());
+            }
+        }
+
+        if let Some(ref prefix) = self.options.measurement_prefix {
+            if measurement.starts_with(prefix.as_str()) {
+                measurement.drain(..prefix.len());
+            }
+        }
+
+        measurement
+    }
+
+    /// Fetch the timestamp of the current line, converted from the input
+    /// precision into the target precision
+    fn get_next_timestamp(&mut self) -> Result<i64> {
+        let position = self.reader_position();
+        let timestamp = self.reader.get_next_timestamp()?;
+        let precision = match self.options.detect_precision {
+            true => Precision::detect(timestamp),
+            false => self.options.precision,
+        };
+
+        if !precision.is_valid_timestamp(timestamp) {
+            return Err(Error::timestamp_out_of_range(position));
+        }
+
+        precision
+            .convert(timestamp, self.options.target_precision)
+            .ok_or_else(|| Error::timestamp_out_of_range(position))
+    }
+
+    /// Fetch the timestamp of the current line in nanoseconds
+    fn get_next_timestamp_nanos(&mut self) -> Result<i64> {
+        let position = self.reader_position();
+        let timestamp = self.get_next_timestamp()?;
+
+        self.options
+            .target_precision
+            .convert(timestamp, Precision::Nanoseconds)
+            .ok_or_else(|| Error::timestamp_out_of_range(position))
+    }
+
+    /// Skip the remaining elements of the current line, leaving the newline for
+    /// the next line to skip
+    fn discard_rest_of_line(&mut self) {
+        while let Ok(c) = self.reader.peek_char() {
+            match c {
+                b'\n' => break,
+                c if c.is_ascii_whitespace() => self.reader.skip_char(),
+                _ => self.reader.discard_next_element(),
+            }
+        }
+    }
+
+    fn discard_next_value(&mut self) -> Result<()> {
+        self.map_with = None;
+        match self.take_read_value()? {
+            Some(_) => Ok(()),
+            None => self.reader.discard_next_value(),
+        }
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($method:ident, $visit:ident, $typ:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            // Timestamps are parsed directly and then checked if they fit the target type
+            if self.next_is_timestamp() {
+                let position = self.reader_position();
+                let timestamp = self.get_next_timestamp()?;
+                return match <$typ>::try_from(timestamp) {
+                    Ok(timestamp) => visitor.$visit(timestamp),
+                    Err(_) => Err(Error::timestamp_out_of_range(position)),
+                };
+            }
+
+            let mut value = self.get_next_value()?;
+
+            // Check if element is a valid number
+            let re = Regex::new(r"^-?\d+[iu]?$").unwrap();
+            let result = match re.is_match(&value) {
+                true => {
+                    // Remove integer indicator
+                    if value.ends_with(['i', 'u']) {
+                        value.pop();
+                    }
+
+                    value.parse()
+                }
+                false => return Err(Error::invalid_value(value, self.reader_position())),
+            };
+
+            match result {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(Error::invalid_value(value, self.reader_position())),
+            }
+        }
+    };
+}
+
+macro_rules! deserialize_float {
+    ($method:ident, $visit:ident, $typ:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            let element = self.get_next_value()?;
+
+            // Integers are accepted as well unless numbers are strict, as long as the
+            // float represents them exactly
+            let exact = |n: i128| {
+                let float = n as $typ;
+                (float as i128 == n).then_some(float)
+            };
+            let value = match element.ends_with(['i', 'u']) {
+                true if self.options.strict_numbers => None,
+                true => match Value::from_number_str(&element) {
+                    Some(Value::Number(Number::Integer(n))) => exact(n.into()),
+                    Some(Value::Number(Number::UInteger(n))) => exact(n.into()),
+                    _ => None,
+                },
+                false if is_float_str(&element) => element.parse().ok(),
+                false => None,
+            };
+
+            match value {
+                Some(value) => visitor.$visit(value),
+                None => Err(Error::invalid_type(
+                    &element,
+                    stringify!($typ),
+                    self.reader_position(),
+                )),
+            }
+        }
+    };
+}
+
+impl<'de, R: Read<'de>> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.next_is_timestamp() {
+            let timestamp = self.get_next_timestamp()?;
+            return visitor.visit_i64(timestamp);
+        }
+
+        // Lines and the tag and field sets are maps, e.g., when buffered by
+        // `#[serde(flatten)]` or internally tagged enums
+        if !self.at_value {
+            return self.deserialize_map(visitor);
+        }
+
+        // Measurements are always strings, which also allows them to be used as the tag
+        // of an internally tagged enum
+        if self.next_element().is_measurement() {
+            return self.deserialize_string(visitor);
+        }
+
+        if self.options.tags_as_strings && self.next_element().is_tags() {
+            return self.deserialize_string(visitor);
+        }
+
+        let value = self.get_next_value()?;
+        let result = match self.coerce_to.take() {
+            Some(typ) => match Value::from_typed_str(&value, typ) {
+                Some(v) => v.visit(visitor),
+                None => return Err(Error::invalid_type(value, typ, self.reader_position())),
+            },
+            None => Value::from_any_str(&value).visit(visitor),
+        };
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(_) => Err(Error::invalid_value(value, self.reader_position())),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let element = self.get_next_value()?;
+        let value = match Value::from_bool_str(&element) {
+            Some(value) => value.visit(visitor),
+            None => {
+                return Err(Error::invalid_type(
+                    &element,
+                    "bool",
+                    self.reader_position(),
+                ))
+            }
+        };
+
+        match value {
+            Ok(value) => Ok(value),
+            Err(_) => Err(Error::invalid_type(
+                &element,
+                "bool",
+                self.reader_position(),
+            )),
+        }
+    }
+
+    deserialize_integer!(deserialize_i8, visit_i8, i8);
+    deserialize_integer!(deserialize_i16, visit_i16, i16);
+    deserialize_integer!(deserialize_i32, visit_i32, i32);
+    deserialize_integer!(deserialize_i64, visit_i64, i64);
+    deserialize_integer!(deserialize_i128, visit_i128, i128);
+    deserialize_integer!(deserialize_u8, visit_u8, u8);
+    deserialize_integer!(deserialize_u16, visit_u16, u16);
+    deserialize_integer!(deserialize_u32, visit_u32, u32);
+    deserialize_integer!(deserialize_u64, visit_u64, u64);
+    deserialize_integer!(deserialize_u128, visit_u128, u128);
+
+    deserialize_float!(deserialize_f32, visit_f32, f32);
+    deserialize_float!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let element = self.get_next_value()?;
+        let len = element.chars().count();
+        if len != 1 {
+            return Err(Error::invalid_char(element, len, self.reader_position()));
+        }
+        visitor.visit_char(element.chars().next().unwrap())
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Values without escape characters are borrowed from the input when possible,
+        // which allows deserializing into `&str`
+        if !self.next_is_timestamp() {
+            if let Some(value) = self.get_next_value_borrowed()? {
+                return visitor.visit_borrowed_str(value);
+            }
+        }
+
+        self.get_next_value().and_then(|e| visitor.visit_str(&e))
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.get_next_value().and_then(|e| visitor.visit_str(&e))
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // The bytes are passed on as is so values which are not valid utf8 can be
+        // deserialized losslessly
+        let bytes = self.get_next_value_bytes()?;
+        visitor.visit_byte_buf(bytes)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Timestamps are converted from the input precision into nanoseconds
+        if name == timestamp::NAME && self.next_is_timestamp() {
+            let nanos = self.get_next_timestamp_nanos()?;
+            return visitor.visit_newtype_struct(nanos.into_deserializer());
+        }
+
+        // Raw lines and values are provided exactly as written in the input
+        if name == raw::LINE_NAME && !self.at_value && self.next_element().is_measurement() {
+            let result = self.deserialize_raw_line(visitor);
+            return self.check_read(result);
+        }
+
+        let is_read = self.set_value.is_some() || self.parsed_measurement.is_some();
+        if name == raw::VALUE_NAME && self.at_value && !is_read {
+            return self.deserialize_raw_value(visitor);
+        }
+
+        // Field values of points keep their type, unless it is coerced or the value is
+        // transformed, which are inferred as usual
+        let is_transformed = self.coerce_to.is_some() || self.map_with.is_some();
+        let is_field = self.at_value && self.next_element().is_fields();
+        if name == point::FIELD_VALUE_NAME && is_field && !is_transformed {
+            let value = self.get_next_field_value()?;
+            return visitor.visit_newtype_struct(value);
+        }
+
+        // Other newtype structs are transparent wrappers around their value
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Sequences inside of a line can only be values deserialized from their bytes,
+        // e.g., a `Vec<u8>`
+        if self.at_value {
+            let bytes = self.get_next_value_bytes()?;
+            return visitor.visit_seq(de::value::SeqDeserializer::new(bytes.into_iter()));
+        }
+
+        // Sequences in place of the tag or field set are lists of key and value pairs,
+        // which keep the order and duplicates of the keys
+        if self.next_element().is_tags() || self.next_element().is_fields() {
+            return visitor.visit_seq(PairsAccess { de: self });
+        }
+
+        visitor.visit_seq(SeqDeserializer::new(self))
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Tuples in place of a line contain its elements in order, so that a line can
+        // be deserialized without defining a struct
+        if !self.at_value && self.reader.get_next_element().is_measurement() {
+            self.include_tags();
+
+            let value = visitor.visit_seq(LineTupleAccess::new(self))?;
+            self.discard_rest_of_line();
+            return Ok(value);
+        }
+
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::unsupported("tuple struct deserialization"))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Lines are deserialized as maps when they contain `#[serde(flatten)]` members,
+        // which could contain the tags
+        if self.reader.get_next_element().is_measurement() {
+            self.include_tags();
+        }
+
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == line_info::NAME {
+            self.reader.skip_until_valid_line()?;
+            return visitor.visit_map(LineInfoAccess::new(self));
+        }
+
+        if fields.contains(&"tags") {
+            self.include_tags();
+        };
+
+        // The measurement is checked and consumed here as the struct has no member to
+        // deserialize it into
+        if self.options.measurement_from_type
+            && !fields.contains(&"measurement")
+            && self.has_next_key()?
+            && self.reader.get_next_element().is_measurement()
+        {
+            self.get_next_key()?;
+            let measurement = self.get_next_value()?;
+            if measurement != name {
+                return Err(Error::invalid_value(measurement, self.reader_position()));
+            }
+        }
+
+        // Only fields the struct has a member for are parsed
+        let is_field_set = !self.at_value && self.reader.get_next_element().is_fields();
+        if self.options.project_fields && is_field_set {
+            self.projection = Some(fields);
+            let result = visitor.visit_map(&mut *self);
+            self.projection = None;
+
+            return result;
+        }
+
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Enums in place of a line select their variant by the measurement, the tags
+        // have to be included before it is read in case the variant contains them
+        if !self.at_value && self.reader.get_next_element().is_measurement() {
+            self.include_tags();
+        }
+
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Identifiers are read as values, e.g., the measurement as the variant of an
+        // internally tagged enum
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.discard_next_value()?;
+        visitor.visit_unit()
+    }
+}
+
+impl<'a, R: Read<'a>> de::MapAccess<'a> for Deserializer<R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'a>,
+    {
+        let result = self.next_key(seed);
+        self.check_read(result)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'a>,
+    {
+        if let Some(measurement) = self.measurement.take() {
+            return seed.deserialize(StringDeserializer::new(measurement));
+        }
+
+        let result = seed.deserialize(&mut *self);
+        self.check_read(result)
+    }
+}
+
+/// Provides the location of a line along with the line itself to
+/// [WithLineInfo](crate::WithLineInfo)
+struct LineInfoAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+
+    /// Index of the next field in [line_info::FIELDS]
+    index: usize,
+
+    line: usize,
+
+    start: usize,
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> LineInfoAccess<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        let line = de.reader.get_line();
+        let start = match de.parsed_measurement.is_some() {
+            true => de.line_start,
+            false => de.reader.get_offset(),
+        };
+
+        LineInfoAccess {
+            de,
+            index: 0,
+            line,
+            start,
+        }
+    }
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::MapAccess<'de> for LineInfoAccess<'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match line_info::FIELDS.get(self.index) {
+            Some(key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        self.index += 1;
+        match self.index {
+            1 => seed.deserialize(self.line.into_deserializer()),
+            2 => seed.deserialize(self.start.into_deserializer()),
+            3 => seed.deserialize(&mut *self.de),
+            _ => seed.deserialize(self.de.reader.get_offset().into_deserializer()),
+        }
+    }
+}
+
+/// Provides the measurement, tag set, field set, and timestamp of a line as the
+/// elements of a tuple
+///
+/// A missing tag set is an empty map and a missing timestamp is a unit, i.e.,
+/// `None` for an [Option]
+struct LineTupleAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+
+    /// Index of the next element of the tuple
+    index: usize,
+}
+
+impl<'a, R: 'a> LineTupleAccess<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        LineTupleAccess { de, index: 0 }
+    }
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for LineTupleAccess<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        if index > 3 {
+            return Ok(None);
+        }
+        self.index += 1;
+
+        let next = self.de.reader.get_next_element();
+        let present = match index {
+            0 => next.is_measurement(),
+            1 => next.is_tags(),
+            2 => next.is_fields(),
+            _ => next.is_timestamp(),
+        } && self.de.has_next_key()?;
+
+        // The elements are read the same way as the members of a struct
+        if present {
+            de::MapAccess::next_key::<de::IgnoredAny>(&mut *self.de)?;
+            return de::MapAccess::next_value_seed(&mut *self.de, seed).map(Some);
+        }
+
+        match index {
+            1 => {
+                let tags = de::value::MapDeserializer::<_, Error>::new(std::iter::empty::<(
+                    String,
+                    String,
+                )>());
+                seed.deserialize(tags).map(Some)
+            }
+            2 => Err(Error::missing_element("fields")),
+            3 => seed.deserialize(().into_deserializer()).map(Some),
+            _ => Err(Error::missing_element("measurement")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(4 - self.index.min(4))
+    }
+}
+
+/// Provides the tags or fields of a set as a sequence of key and value pairs
+struct PairsAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for PairsAccess<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match de::MapAccess::next_key::<String>(&mut *self.de)? {
+            Some(key) => seed
+                .deserialize(PairDeserializer {
+                    de: &mut *self.de,
+                    key: Some(key),
+                    done: false,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Deserializes a tag or field as a tuple of its key and value
+struct PairDeserializer<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+
+    /// Key of the pair, taken once it has been deserialized
+    key: Option<String>,
+
+    /// Whether the value of the pair has been deserialized
+    done: bool,
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::Deserializer<'de> for PairDeserializer<'a, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for PairDeserializer<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if let Some(key) = self.key.take() {
+            return seed.deserialize(StringDeserializer::new(key)).map(Some);
+        }
+
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+
+        de::MapAccess::next_value_seed(&mut *self.de, seed).map(Some)
+    }
+}
+
+struct SeqDeserializer<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+
+    first: bool,
+}
+
+impl<'a, R: 'a> SeqDeserializer<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        SeqDeserializer { de, first: true }
+    }
+}
+
+impl<'de, 'a, R: Reader<'de> + 'a> de::SeqAccess<'de> for SeqDeserializer<'a, R> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        // Skip the check and next line fetching if this is the first access
+        match !self.first {
+            true => {
+                if !self.de.has_next_line()? {
+                    return Ok(None);
+                }
+
+                self.de.set_next_line();
+            }
+            false => self.first = false,
+        }
+
+        if !self.de.begin_line()? {
+            return Ok(None);
+        }
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'a, R: Read<'a>> de::EnumAccess<'a> for &mut Deserializer<R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: de::DeserializeSeed<'a>,
+    {
+        let is_line = !self.at_value && self.reader.get_next_element().is_measurement();
+
+        let variant_name = self.get_next_value()?;
+        if is_line {
+            self.measurement = Some(variant_name.clone());
+        }
+
+        seed.deserialize(variant_name.into_deserializer())
+            .map(|v| (v, self))
+    }
+}
+
+impl<'a, R: Read<'a>> de::VariantAccess<'a> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        // Lines selecting a unit variant have nothing to deserialize the rest of the
+        // line into
+        if self.measurement.take().is_some() {
+            self.discard_rest_of_line();
+        }
+
+        self.check_read(Ok(()))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'a>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+/// Result of deserializing a single line in permissive mode
+///
+/// See [from_slice_permissive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedOrRaw<T> {
+    /// The line was successfully deserialized
+    Parsed(T),
+
+    /// The line could not be deserialized and is returned verbatim
+    Raw(String),
+}
+
+/// Deserialize each line of a line protocol string as bytes into a struct
+/// `T`, without failing on lines which can not be deserialized
+///
+/// Lines which fail to deserialize are returned verbatim as
+/// [ParsedOrRaw::Raw] so they can be passed on, e.g., to a dead-letter queue.
+/// Invalid UTF-8 in such lines is replaced with the replacement character.
+/// Empty lines and comment lines are skipped
+///
+/// # Example
+///
+/// ```rust
+/// let lines = "metric1 field1=123i\nmetric2 field1=\nmetric3 field1=321i";
+///
+/// let metrics: Vec<ParsedOrRaw<Metric>> = serde_influxlp::from_slice_permissive(lines.as_bytes());
+/// // Output: [Parsed(Metric { .. }), Raw("metric2 field1="), Parsed(Metric { .. })]
+/// ```
+pub fn from_slice_permissive<'a, T>(s: &'a [u8]) -> Vec<ParsedOrRaw<T>>
+where
+    T: Deserialize<'a>,
+{
+    reader::Lines::new(s)
+        .map(|span| {
+            let line = &s[span.range];
+            match from_slice(line) {
+                Ok(value) => ParsedOrRaw::Parsed(value),
+                Err(_) => ParsedOrRaw::Raw(String::from_utf8_lossy(line).into_owned()),
+            }
+        })
+        .collect()
+}
+
+/// Deserialize each line of a line protocol string into a struct `T`, without
+/// failing on lines which can not be deserialized
+///
+/// See [from_slice_permissive] for more information
+pub fn from_str_permissive<'a, T>(s: &'a str) -> Vec<ParsedOrRaw<T>>
+where
+    T: Deserialize<'a>,
+{
+    from_slice_permissive(s.as_bytes())
+}
+
+/// Number of lines of each kind in a line protocol input, see [count_lines]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    /// Number of lines containing data
+    pub data: usize,
+
+    /// Number of comment lines
+    pub comments: usize,
+
+    /// Number of empty, or whitespace only, lines
+    pub empty: usize,
+
+    /// Total size of the lines containing data in bytes, excluding newlines
+    /// and surrounding whitespace
+    pub data_bytes: usize,
+}
+
+/// Count the lines of a line protocol input without deserializing them
+///
+/// The lines are only split, not parsed, so the data lines are not
+/// guaranteed to be valid. Newlines which are escaped, or are part of a
+/// quoted field value, do not end a line just as when deserializing
+///
+/// # Example
+///
+/// ```rust
+/// let input = "# Header\nmetric1 field1=\"multi\nline\"\n\nmetric2 field1=123i\n";
+///
+/// let counts = serde_influxlp::count_lines(input);
+/// // Output: LineCounts { data: 2, comments: 1, empty: 1, data_bytes: 46 }
+/// ```
+pub fn count_lines(input: impl AsRef<[u8]>) -> LineCounts {
+    let mut lines = reader::Lines::new(input.as_ref());
+    let mut counts = LineCounts::default();
+    for span in &mut lines {
+        counts.data += 1;
+        counts.data_bytes += span.range.len();
+    }
+
+    counts.comments = lines.comments;
+    counts.empty = lines.empty;
+    counts
+}
+
+/// Deserialize a valid line protocol from a reader into a struct `T`
+///
+/// The reader is read one byte at a time, use [from_bufread] for large inputs
+///
+/// # Example
+///
+/// Below is an example of the least required for deserialization to succeed
+///
+/// ```rust
+/// use serde_influxlp::Value;
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Fields {
+///     pub field1: i32,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: Fields,
+/// }
+///
+/// fn main() {
+///     let file = File::open("lines.txt")?;
+///
+///     let metric: Metric = serde_influxlp::from_slice(file).unwrap();
+///     println!("{metric:#?}");
+///     // Output Metric {
+///     //     measurement: "measurement",
+///     //     fields: Fields {
+///     //         field1: 123,
+///     //     },
+///     // }
+/// }
+/// ```
+pub fn from_reader<T>(r: impl io::Read) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::new(reader::IoReader::new(r));
+    let value = T::deserialize(&mut deserializer)?;
+
+    Ok(value)
+}
+
+/// Iterator lazily deserializing each line into a struct `T`
+///
+/// Created by [Deserializer::into_iter]
+pub struct StreamDeserializer<'de, R, T> {
+    de: Deserializer<R>,
+
+    /// Whether the first line has been deserialized
+    first: bool,
+
+    /// Whether a line failed to deserialize
+    failed: bool,
+
+    lifetime: PhantomData<&'de ()>,
+
+    output: PhantomData<T>,
+}
+
+impl<'de, R, T> Iterator for StreamDeserializer<'de, R, T>
+where
+    R: Read<'de>,
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.de.has_next_line() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(e));
+            }
+        }
+
+        // The reader is already positioned at the first line
+        match self.first {
+            true => self.first = false,
+            false => self.de.set_next_line(),
+        }
+
+        match self.de.begin_line() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(e));
+            }
+        }
+
+        // The reader is left in the middle of a line on errors so we can not continue
+        let result = T::deserialize(&mut self.de);
+        self.failed = result.is_err();
+
+        Some(result)
+    }
+}
+
+/// Iterator deserializing at most a set number of lines from a reader
+///
+/// Created by [take_lines]
+pub struct TakeLines<R, T>
+where
+    R: io::Read,
+{
+    de: Deserializer<reader::IoReader<R>>,
+
+    /// Whether the first line has been deserialized
+    first: bool,
+
+    /// Number of lines left to deserialize
+    remaining: usize,
+
+    output: PhantomData<T>,
+}
+
+impl<R, T> Iterator for TakeLines<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match self.de.has_next_line() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        }
+
+        // The reader is already positioned at the first line
+        match self.first {
+            true => self.first = false,
+            false => self.de.set_next_line(),
+        }
+
+        match self.de.begin_line() {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        }
+
+        let result = T::deserialize(&mut self.de);
+
+        // The reader is left in the middle of a line on errors so we can not continue
+        self.remaining = match result {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+
+        Some(result)
+    }
+}
+
+/// Lazily deserialize at most `max_lines` lines from a reader into structs
+/// `T`
+///
+/// Comment lines and empty lines do not count towards the limit. The reader
+/// is not read past the last deserialized line, apart from the newline ending
+/// it, so the same reader can be passed again to deserialize the next lines.
+/// Iteration stops after the first error
+///
+/// # Example
+///
+/// ```rust
+/// let mut file = File::open("lines.txt")?;
+///
+/// for metric in serde_influxlp::take_lines::<_, Metric>(&mut file, 1000) {
+///     println!("{:?}", metric?);
+/// }
+/// ```
+pub fn take_lines<R, T>(r: R, max_lines: usize) -> TakeLines<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    TakeLines {
+        de: Deserializer::new(reader::IoReader::new(r)),
+        first: true,
+        remaining: max_lines,
+        output: PhantomData,
+    }
+}
+
+/// Deserialize at most `max_lines` lines from a reader into a vector of
+/// structs `T`
+///
+/// Useful to cap the amount of work done per request or to paginate over
+/// large inputs. See [take_lines] for more information
+///
+/// # Example
+///
+/// ```rust
+/// let mut file = File::open("lines.txt")?;
+///
+/// loop {
+///     let page: Vec<Metric> = serde_influxlp::from_reader_n(&mut file, 1000)?;
+///     if page.is_empty() {
+///         break;
+///     }
+/// }
+/// ```
+pub fn from_reader_n<T>(r: impl io::Read, max_lines: usize) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    take_lines(r, max_lines).collect()
+}
+
+/// Deserialize each line in turn, handing it to `f`
+fn visit_lines<'de, R, T, B, F>(mut de: Deserializer<R>, mut f: F) -> Result<ControlFlow<B>>
+where
+    R: Reader<'de>,
+    T: Deserialize<'de>,
+    F: FnMut(T) -> ControlFlow<B>,
+{
+    // The reader is already positioned at the first line
+    let mut first = true;
+    while de.has_next_line()? {
+        match first {
+            true => first = false,
+            false => de.set_next_line(),
+        }
+
+        if !de.begin_line()? {
+            break;
+        }
+
+        if let ControlFlow::Break(b) = f(T::deserialize(&mut de)?) {
+            return Ok(ControlFlow::Break(b));
+        }
+    }
+
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Deserialize each line into a struct `T`, grouping the lines by their
+/// measurement
+fn group_lines<'de, R, T>(mut de: Deserializer<R>) -> Result<HashMap<String, Vec<T>>>
+where
+    R: Reader<'de>,
+    T: Deserialize<'de>,
+{
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+
+    // The reader is already positioned at the first line
+    let mut first = true;
+    while de.has_next_line()? {
+        match first {
+            true => first = false,
+            false => de.set_next_line(),
+        }
+
+        if !de.begin_line()? {
+            break;
+        }
+
+        let measurement = de.peek_measurement()?;
+        let line = T::deserialize(&mut de)?;
+        groups.entry(measurement).or_default().push(line);
+    }
+
+    Ok(groups)
+}
+
+/// Deserialize each line of a valid line protocol string into a struct `T`,
+/// grouped by the measurement of the lines
+///
+/// The lines of each measurement are kept in the order of the input. `T`
+/// does not need a member for the measurement. Useful for handing the lines
+/// of a batch containing several measurements to a handler per measurement
+///
+/// # Example
+///
+/// ```rust
+/// let input = "cpu,host=a usage=0.5\nmem,host=a used=1024i\ncpu,host=b usage=0.7";
+///
+/// let groups: HashMap<String, Vec<Metric>> = serde_influxlp::from_str_grouped(input)?;
+/// // groups["cpu"].len() == 2
+/// // groups["mem"].len() == 1
+/// ```
+pub fn from_str_grouped<'a, T>(s: &'a str) -> Result<HashMap<String, Vec<T>>>
+where
+    T: Deserialize<'a>,
+{
+    group_lines(Deserializer::new(reader::SliceReader::new(s.as_bytes())))
+}
+
+/// Deserialize each line of a valid line protocol string into a struct `T`,
+/// grouped by the measurement of the lines, using the given [Options]
+///
+/// The measurements are grouped after the prefix and suffix set by
+/// [Options::strip_measurement_prefix] and
+/// [Options::strip_measurement_suffix] are removed. See [from_str_grouped]
+/// for more information
+pub fn from_str_grouped_with_options<'a, T>(
+    s: &'a str,
+    options: &Options,
+) -> Result<HashMap<String, Vec<T>>>
+where
+    T: Deserialize<'a>,
+{
+    let reader = reader::SliceReader::new(s.as_bytes());
+    group_lines(Deserializer::with_options(reader, options.clone()))
+}
+
+/// Deserialize each line of a valid line protocol string into a struct `T`
+/// and hand it to `f` without collecting the lines
+///
+/// `f` can stop the deserialization early by returning
+/// [ControlFlow::Break], whose value is then returned. Deserialization stops
+/// at the first error
+///
+/// # Example
+///
+/// ```rust
+/// let mut total = 0;
+/// let result = serde_influxlp::for_each_line(input, |metric: Metric| {
+///     total += metric.fields.field1;
+///     match total > 1000 {
+///         true => ControlFlow::Break(metric.measurement),
+///         false => ControlFlow::Continue(()),
+///     }
+/// });
+///
+/// println!("{result:?}");
+/// // Output: Ok(Break("metric1"))
+/// ```
+pub fn for_each_line<'a, T, B, F>(s: &'a str, f: F) -> Result<ControlFlow<B>>
+where
+    T: Deserialize<'a>,
+    F: FnMut(T) -> ControlFlow<B>,
+{
+    for_each_line_slice(s.as_bytes(), f)
+}
+
+/// Deserialize each line of a valid line protocol slice into a struct `T`
+/// and hand it to `f` without collecting the lines
+///
+/// See [for_each_line] for more information
+pub fn for_each_line_slice<'a, T, B, F>(s: &'a [u8], f: F) -> Result<ControlFlow<B>>
+where
+    T: Deserialize<'a>,
+    F: FnMut(T) -> ControlFlow<B>,
+{
+    visit_lines(Deserializer::new(reader::SliceReader::new(s)), f)
+}
+
+/// Deserialize each line of a valid line protocol from a reader into a
+/// struct `T` and hand it to `f` without collecting the lines
+///
+/// See [for_each_line] for more information
+pub fn for_each_line_reader<T, B, F>(r: impl io::Read, f: F) -> Result<ControlFlow<B>>
+where
+    T: DeserializeOwned,
+    F: FnMut(T) -> ControlFlow<B>,
+{
+    visit_lines(Deserializer::new(reader::IoReader::new(r)), f)
+}
+
+/// Deserialize a valid line protocol from a reader into a struct `T` using
+/// the given [Options]
+///
+/// See [from_reader] for more information
+pub fn from_reader_with_options<T>(r: impl io::Read, options: &Options) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::with_options(reader::IoReader::new(r), options.clone());
+    let value = T::deserialize(&mut deserializer)?;
+    if options.deny_trailing {
+        deserializer.end()?;
+    }
+
+    Ok(value)
+}
+
+/// Deserialize a valid line protocol from a buffered reader into a struct `T`
+///
+/// Values and lines are scanned directly in the buffer of the reader, instead
+/// of reading one byte at a time like [from_reader]
+///
+/// # Example
+///
+/// ```rust
+/// let file = BufReader::new(File::open("lines.txt")?);
+/// let metrics: Vec<Metric> = serde_influxlp::from_bufread(file)?;
+/// ```
+pub fn from_bufread<T>(r: impl io::BufRead) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_bufread_with_options(r, &Options::default())
+}
+
+/// Deserialize a valid line protocol from a buffered reader into a struct `T`
+/// using the given [Options]
+///
+/// See [from_bufread] for more information
+pub fn from_bufread_with_options<T>(r: impl io::BufRead, options: &Options) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer =
+        Deserializer::with_options(reader::BufReadReader::new(r), options.clone());
+    let value = T::deserialize(&mut deserializer)?;
+    if options.deny_trailing {
+        deserializer.end()?;
+    }
+
+    Ok(value)
+}
+
+/// Deserialize the line at `span` of `input` on its own into a struct `T`
+///
+/// `lines` is the number of lines before it, which count against the maximum
+/// number of lines. Returns None if the measurement filter rejects the line.
+/// Positions, including those given to [WithLineInfo](crate::WithLineInfo),
+/// are relative to the whole input
+fn deserialize_line<'a, T>(
+    input: &'a [u8],
+    span: &reader::LineSpan,
+    lines: usize,
+    options: &Options,
+) -> Option<Result<T>>
+where
+    T: Deserialize<'a>,
+{
+    let reader =
+        reader::SliceReader::at_line(&input[..span.range.end], span.range.start, span.line);
+    let mut deserializer = Deserializer::with_options(reader, options.clone());
+    deserializer.lines = lines;
+    match deserializer.begin_line() {
+        Ok(true) => Some(T::deserialize(&mut deserializer)),
+        Ok(false) => None,
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// Incremental parser for input arriving in arbitrary chunks, e.g., from a
+/// socket
+///
+/// Each chunk is appended to an internal buffer, from which every complete
+/// line is deserialized into a struct `T`. The trailing partial line is kept
+/// until the rest of it arrives. Newlines which are escaped, or are part of a
+/// quoted field value, do not end a line
+///
+/// A partial line longer than [Options::max_line_length] fails with
+/// [ErrorCode::LineTooLong](crate::ErrorCode::LineTooLong), the rest of it
+/// is then skipped as it arrives
+///
+/// # Example
+///
+/// ```rust
+/// let mut parser = Parser::<Metric>::new();
+///
+/// let mut buf = [0; 4096];
+/// loop {
+///     let n = socket.read(&mut buf).await?;
+///     if n == 0 {
+///         break;
+///     }
+///
+///     for metric in parser.feed(&buf[..n]) {
+///         println!("{:?}", metric?);
+///     }
+/// }
+///
+/// if let Some(metric) = parser.finish() {
+///     println!("{:?}", metric?);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Parser<T> {
+    /// Input not yet deserialized, always a partial line between calls
+    buf: Vec<u8>,
+
+    /// Search for the end of the partial line, which has searched all of
+    /// `buf`
+    line_end: reader::LineEnd,
+
+    /// Whether the partial line is too long and is skipped until its end
+    skip: bool,
+
+    options: Options,
+
+    /// Number of newlines in the input deserialized or skipped so far
+    lines: usize,
+
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Parser<T> {
+    fn default() -> Self {
+        Self::with_options(Options::default())
+    }
+}
+
+impl<T> Parser<T> {
+    /// Create a new parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new parser using the given [Options]
+    pub fn with_options(options: Options) -> Self {
+        Parser {
+            buf: Vec::new(),
+            line_end: reader::LineEnd::default(),
+            skip: false,
+            options,
+            lines: 0,
+            output: PhantomData,
+        }
+    }
+
+    /// The buffered input which does not form a complete line yet
+    pub fn remaining(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl<T> Parser<T>
+where
+    T: DeserializeOwned,
+{
+    /// Add `chunk` to the input and deserialize every line it completes
+    ///
+    /// Each line is deserialized on its own, so an invalid line does not stop
+    /// the following lines from being deserialized. Comment lines and empty
+    /// lines are skipped
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Result<T>> {
+        // Only the chunk is searched, the buffered partial line has been searched
+        // already
+        let mut results = Vec::new();
+        let mut rest = chunk;
+        while let Some(end) = self.line_end.find(rest) {
+            let (line, next) = rest.split_at(end + 1);
+            self.buf.extend_from_slice(line);
+            if !std::mem::take(&mut self.skip) {
+                results.extend(self.deserialize_buf());
+            }
+
+            self.skip_buf();
+            rest = next;
+        }
+
+        match self.skip {
+            true => self.lines += count_newlines(rest),
+            false => self.buf.extend_from_slice(rest),
+        }
+
+        if let Some(max) = self.options.max_line_length {
+            if !self.skip && self.buf.trim_ascii_start().len() > max {
+                let line = self.lines + 1;
+                let mut error = Error::line_too_long(line, max);
+                error.position.line = line;
+                results.push(Err(error));
+
+                self.skip_buf();
+                self.skip = true;
+            }
+        }
+
+        results
+    }
+
+    /// Deserialize the buffered partial line, if any, as the input has ended
+    pub fn finish(mut self) -> Option<Result<T>> {
+        match self.skip {
+            true => None,
+            false => self.deserialize_buf(),
+        }
+    }
+
+    /// Deserialize the line in the buffer, None if it is a comment line or an
+    /// empty line
+    fn deserialize_buf(&mut self) -> Option<Result<T>> {
+        let span = reader::Lines::new(&self.buf).next()?;
+        let span = reader::LineSpan {
+            line: self.lines + span.line,
+            ..span
+        };
+        deserialize_line(&self.buf, &span, 0, &self.options)
+    }
+
+    /// Empty the buffer, counting the newlines skipped along with it
+    fn skip_buf(&mut self) {
+        self.lines += count_newlines(&self.buf);
+        self.buf.clear();
+    }
+}
+
+/// Number of newlines in `s`
+fn count_newlines(s: &[u8]) -> usize {
+    s.iter().filter(|&&c| c == b'\n').count()
+}
+
+/// Deserialize a valid line protocol from an async reader into a struct `T`
+///
+/// The input is read until the end before it is deserialized. Use
+/// [lines_async] to deserialize the lines as they are read instead
+///
+/// # Example
+///
+/// ```rust
+/// let mut stream = TcpStream::connect("127.0.0.1:8089").await?;
+/// let metrics: Vec<Metric> = serde_influxlp::from_reader_async(&mut stream).await?;
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn from_reader_async<T>(r: impl tokio::io::AsyncRead + Unpin) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_reader_async_with_options(r, &Options::default()).await
+}
+
+/// Deserialize a valid line protocol from an async reader into a struct `T`
+/// using the given [Options]
+///
+/// See [from_reader_async] for more information
+#[cfg(feature = "tokio")]
+pub async fn from_reader_async_with_options<T>(
+    mut r: impl tokio::io::AsyncRead + Unpin,
+    options: &Options,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut input = Vec::new();
+    r.read_to_end(&mut input).await?;
+    from_slice_with_options(&input, options)
+}
+
+/// Async iterator deserializing each line read from an async reader into a
+/// struct `T`
+///
+/// Created by [lines_async]
+#[cfg(feature = "tokio")]
+pub struct AsyncLines<R, T> {
+    reader: R,
+
+    /// Parser the input read is fed to, finding where each line ends
+    parser: Parser<T>,
+
+    /// Lines deserialized by the parser which are yet to be returned
+    pending: VecDeque<Result<T>>,
+
+    /// Whether the reader is exhausted
+    done: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl<R, T> AsyncLines<R, T>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    T: DeserializeOwned,
+{
+    /// Read and deserialize the next line, skipping comment lines and empty
+    /// lines
+    ///
+    /// Returns None once the reader is exhausted. The position of errors is
+    /// relative to the start of the input
+    pub async fn next_line(&mut self) -> Result<Option<T>> {
+        use tokio::io::AsyncBufReadExt;
+
+        loop {
+            if let Some(result) = self.pending.pop_front() {
+                return result.map(Some);
+            }
+
+            if self.done {
+                return Ok(None);
+            }
+
+            let chunk = self.reader.fill_buf().await?;
+            let len = chunk.len();
+            match len {
+                0 => {
+                    self.done = true;
+                    self.pending
+                        .extend(std::mem::take(&mut self.parser).finish());
+                }
+                _ => self.pending.extend(self.parser.feed(chunk)),
+            }
+            self.reader.consume(len);
+        }
+    }
+}
+
+/// Deserialize each line read from an async reader into a struct `T` as it
+/// is read
+///
+/// Newlines which are escaped, or are part of a quoted field value, do not end
+/// a line, see [Parser]
+///
+/// # Example
+///
+/// ```rust
+/// let stream = BufReader::new(TcpStream::connect("127.0.0.1:8089").await?);
+///
+/// let mut lines = serde_influxlp::lines_async::<_, Metric>(stream);
+/// while let Some(metric) = lines.next_line().await? {
+///     println!("{metric:?}");
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub fn lines_async<R, T>(r: R) -> AsyncLines<R, T>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    T: DeserializeOwned,
+{
+    lines_async_with_options(r, &Options::default())
+}
+
+/// Deserialize each line read from an async reader into a struct `T` as it
+/// is read using the given [Options]
+///
+/// See [lines_async] for more information
+#[cfg(feature = "tokio")]
+pub fn lines_async_with_options<R, T>(r: R, options: &Options) -> AsyncLines<R, T>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    T: DeserializeOwned,
+{
+    AsyncLines {
+        reader: r,
+        parser: Parser::with_options(options.clone()),
+        pending: VecDeque::new(),
+        done: false,
+    }
+}
+
+/// Deserialize a valid line protocol string as bytes into a struct `T`
+///
+/// # Example
+///
+/// Below is an example of the least required for deserialization to succeed
+///
+/// ```rust
+/// use serde_influxlp::Value;
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Fields {
+///     pub field1: i32,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: Fields,
+/// }
+///
+/// fn main() {
+///     let slice = "measurement field1=123i".as_bytes();
+///
+///     let metric: Metric = serde_influxlp::from_slice(slice).unwrap();
+///     println!("{metric:#?}");
+///     // Output Metric {
+///     //     measurement: "measurement",
+///     //     fields: Fields {
+///     //         field1: 123,
+///     //     },
+///     // }
+/// }
+/// ```
+pub fn from_slice<'a, T>(s: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(reader::SliceReader::new(s));
+    let value = T::deserialize(&mut deserializer)?;
+
+    Ok(value)
+}
+
+/// Deserialize a valid line protocol string as bytes into a struct `T` using
+/// the given [Options]
+///
+/// See [from_slice] for more information
+pub fn from_slice_with_options<'a, T>(s: &'a [u8], options: &Options) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::with_options(reader::SliceReader::new(s), options.clone());
+    let value = T::deserialize(&mut deserializer)?;
+    if options.deny_trailing {
+        deserializer.end()?;
+    }
+
+    Ok(value)
+}
+
+/// Deserialize line protocol as bytes, which may contain invalid UTF-8, into
+/// a struct `T`
+///
+/// Invalid UTF-8 sequences are replaced with the replacement character
+/// `U+FFFD` instead of failing, e.g., for input from unreliable senders. The
+/// columns of errors refer to the input after the replacement
+///
+/// # Example
+///
+/// ```rust
+/// let slice = b"measurement,tag1=\xF0\x90\x80 field1=123i";
+///
+/// let metric: Metric = serde_influxlp::from_slice_lossy(slice).unwrap();
+/// // metric.tags.tag1 == "\u{FFFD}"
+/// ```
+pub fn from_slice_lossy<T>(s: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_slice_lossy_with_options(s, &Options::default())
+}
+
+/// Deserialize line protocol as bytes, which may contain invalid UTF-8, into
+/// a struct `T` using the given [Options]
+///
+/// See [from_slice_lossy] for more information
+pub fn from_slice_lossy_with_options<T>(s: &[u8], options: &Options) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let s = String::from_utf8_lossy(s);
+    from_str_with_options(&s, options)
+}
+
+/// Deserialize a valid line protocol string into a struct `T`
+///
+/// # Example
+///
+/// Below is an example of the least required for deserialization to succeed
+///
+/// ```rust
+/// use serde_influxlp::Value;
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Fields {
+///     pub field1: i32,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: Fields,
+/// }
+///
+/// fn main() {
+///     let line = "measurement field1=123i";
+///
+///     let metric: Metric = serde_influxlp::from_str(line).unwrap();
+///     println!("{metric:#?}");
+///     // Output Metric {
+///     //     measurement: "measurement",
+///     //     fields: Fields {
+///     //         field1: 123,
+///     //     },
+///     // }
+/// }
+/// ```
+pub fn from_str<'a, T>(s: &'a str) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_slice(s.as_bytes())
+}
+
+/// Deserialize a valid line protocol string into a struct `T` using the given
+/// [Options]
+///
+/// See [from_str] for more information
+pub fn from_str_with_options<'a, T>(s: &'a str, options: &Options) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_slice_with_options(s.as_bytes(), options)
+}
+
+/// Deserialize each line of a valid line protocol string into a struct `T`,
+/// spreading the lines across threads
+///
+/// The lines are returned in the order of the input. Only worth it for large
+/// inputs, as the lines are first located before any of them are
+/// deserialized
+///
+/// # Example
+///
+/// ```rust
+/// let input = std::fs::read_to_string("dump.txt")?;
+/// let metrics: Vec<Metric> = serde_influxlp::from_str_parallel(&input)?;
+/// ```
+#[cfg(feature = "rayon")]
+pub fn from_str_parallel<'a, T>(s: &'a str) -> Result<Vec<T>>
+where
+    T: Deserialize<'a> + Send,
+{
+    from_slice_parallel_with_options(s.as_bytes(), &Options::default())
+}
+
+/// Deserialize each line of a valid line protocol string into a struct `T`,
+/// spreading the lines across threads, using the given [Options]
+///
+/// See [from_str_parallel] for more information
+#[cfg(feature = "rayon")]
+pub fn from_str_parallel_with_options<'a, T>(s: &'a str, options: &Options) -> Result<Vec<T>>
+where
+    T: Deserialize<'a> + Send,
+{
+    from_slice_parallel_with_options(s.as_bytes(), options)
+}
+
+/// Deserialize each line of a valid line protocol slice into a struct `T`,
+/// spreading the lines across threads
+///
+/// See [from_str_parallel] for more information
+#[cfg(feature = "rayon")]
+pub fn from_slice_parallel<'a, T>(s: &'a [u8]) -> Result<Vec<T>>
+where
+    T: Deserialize<'a> + Send,
+{
+    from_slice_parallel_with_options(s, &Options::default())
+}
+
+/// Deserialize each line of a valid line protocol slice into a struct `T`,
+/// spreading the lines across threads, using the given [Options]
+///
+/// See [from_str_parallel] for more information
+#[cfg(feature = "rayon")]
+pub fn from_slice_parallel_with_options<'a, T>(s: &'a [u8], options: &Options) -> Result<Vec<T>>
+where
+    T: Deserialize<'a> + Send,
+{
+    use rayon::prelude::*;
+
+    let spans: Vec<_> = reader::Lines::new(s).enumerate().collect();
+    spans
+        .into_par_iter()
+        .filter_map(|(lines, span)| deserialize_line(s, &span, lines, options))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ErrorCode;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Exposure {
+        Public,
+        Private,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Tags {
+        pub tag1: i32,
+
+        pub tag2: Option<String>,
+
+        pub tag3: Exposure,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Fields {
+        pub field1: i32,
+
+        pub field2: bool,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Metric {
+        pub measurement: String,
+
+        pub tags: Tags,
+
+        pub fields: Fields,
+
+        pub timestamp: Option<i64>,
+    }
+
+    #[test]
+    fn test_de_from_str() {
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789";
+        let result = from_str::<Metric>(line);
+        assert!(result.is_ok());
+
+        let lines = r#"
+        metric1,tag1=123,tag3=public field1=321,field2=t 123456789
+        #comment line
+
+        metric2,tag1=321,tag2=hello\ world,tag3=private field1=123,field2=True 123456789
+
+        #another comment line
+
+        "#;
+        let result = from_str::<Vec<Metric>>(lines);
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+        assert_eq!(result.len(), 2);
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789";
+        let result = from_str::<Vec<Metric>>(line);
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+        assert_eq!(result.len(), 1);
+
+        let line = r#"
+                
+        "#;
+        let result = from_str::<Metric>(line);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_de_from_reader() {
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789".as_bytes();
+        let result = from_reader::<Metric>(line);
+        assert!(result.is_ok());
+
+        let lines = r#"
+        metric1,tag1=123,tag3=public field1=321,field2=t 123456789
+        #comment line
+
+        metric2,tag1=321,tag2=hello\ world,tag3=private field1=123,field2=True 123456789
+
+        #another comment line
+
+        "#
+        .as_bytes();
+        let result = from_reader::<Vec<Metric>>(lines);
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+        assert_eq!(result.len(), 2);
+
         let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789".as_bytes();
+        let result = from_reader::<Vec<Metric>>(line);
+        assert!(result.is_ok());
+
+        let result = result.unwrap();
+        assert_eq!(result.len(), 1);
+
+        let line = r#"
+                
+        "#
+        .as_bytes();
         let result = from_reader::<Metric>(line);
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_de_chrono_timestamp() {
+        use chrono::{DateTime, Utc};
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Metric {
+            pub measurement: String,
+
+            pub fields: std::collections::HashMap<String, Value>,
+
+            #[serde(default, with = "crate::ts_chrono::option")]
+            pub timestamp: Option<DateTime<Utc>>,
+        }
+
+        let line = "metric1 field1=321i 1577836800123456789";
+        let result = from_str::<Metric>(line);
+        assert!(result.is_ok());
+
+        let metric = result.unwrap();
+        let expected = DateTime::from_timestamp_nanos(1577836800123456789);
+        assert_eq!(metric.timestamp, Some(expected));
+        assert_eq!(crate::to_string(&metric).unwrap(), line);
+
+        let line = "metric1 field1=321i";
+        let metric = from_str::<Metric>(line).unwrap();
+        assert_eq!(metric.timestamp, None);
+        assert_eq!(crate::to_string(&metric).unwrap(), line);
+
+        let options = Options::new().precision(Precision::Seconds);
+        let line = "metric1 field1=321i 1577836800";
+        let metric = from_str_with_options::<Metric>(line, &options).unwrap();
+        assert_eq!(metric.timestamp, DateTime::from_timestamp(1577836800, 0));
+
+        // Strings are left as they are without the with module
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Text {
+            pub measurement: String,
+
+            pub fields: std::collections::HashMap<String, String>,
+
+            pub timestamp: String,
+        }
+
+        let line = "metric1 field1=\"2020-01-01T00:00:00Z\" 1577836800123456789";
+        let text = from_str::<Text>(line).unwrap();
+        assert_eq!(text.fields["field1"], "2020-01-01T00:00:00Z");
+        assert_eq!(text.timestamp, "1577836800123456789");
+        assert_eq!(crate::to_string(&text).unwrap(), line);
+    }
+
+    #[test]
+    fn test_de_coerce() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Metric {
+            pub fields: HashMap<String, Value>,
+        }
+
+        let options = Options::new()
+            .coerce("status_code", LpType::String)
+            .coerce("count", LpType::Float);
+
+        let line = "metric1 status_code=200i,count=5i,other=5i";
+        let result = from_str_with_options::<Metric>(line, &options);
+        assert!(result.is_ok());
+
+        let fields = result.unwrap().fields;
+        assert_eq!(fields["status_code"], Value::from("200i"));
+        assert_eq!(fields["count"], Value::from(5.0));
+        assert_eq!(fields["other"], Value::from(5i64));
+
+        let options = Options::new().coerce("count", LpType::Boolean);
+        let result = from_str_with_options::<Metric>(line, &options);
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_de_time_timestamp() {
+        use time::OffsetDateTime;
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Metric {
+            pub measurement: String,
+
+            pub fields: std::collections::HashMap<String, Value>,
+
+            #[serde(default, with = "crate::ts_time::option")]
+            pub timestamp: Option<OffsetDateTime>,
+        }
+
+        let line = "metric1 field1=321i 1577836800123456789";
+        let result = from_str::<Metric>(line);
+        assert!(result.is_ok());
+
+        let metric = result.unwrap();
+        let expected = OffsetDateTime::from_unix_timestamp_nanos(1577836800123456789).unwrap();
+        assert_eq!(metric.timestamp, Some(expected));
+        assert_eq!(crate::to_string(&metric).unwrap(), line);
+
+        let line = "metric1 field1=321i";
+        let metric = from_str::<Metric>(line).unwrap();
+        assert_eq!(metric.timestamp, None);
+        assert_eq!(crate::to_string(&metric).unwrap(), line);
+    }
+
+    #[test]
+    fn test_de_detect_type() {
+        for (token, typ) in [
+            ("12.5", LpType::Float),
+            ("12", LpType::Float),
+            ("-12i", LpType::Integer),
+            ("12i", LpType::Integer),
+            ("12u", LpType::UInteger),
+            ("18446744073709551615i", LpType::UInteger),
+            ("t", LpType::Boolean),
+            ("False", LpType::Boolean),
+            ("hello", LpType::String),
+            ("", LpType::String),
+        ] {
+            assert_eq!(LpType::detect(token), typ);
+            assert_eq!(LpType::of(&Value::from_any_str(token)), Some(typ));
+        }
+
+        assert_eq!(LpType::of(&Value::None), None);
+    }
+
+    #[test]
+    fn test_de_permissive() {
+        let lines = r#"
+        metric1,tag1=123,tag3=public field1=321,field2=t 123456789
+        #comment line
+        metric2,tag1=abc,tag3=private field1=123,field2=True 123456789
+        metric3,tag1=321,tag2=hello\ world,tag3=private field1=123,field2=f
+
+        metric4,tag1=321,tag3=private field1="not a number",field2=f
+        "#;
+        let result = from_str_permissive::<Metric>(lines);
+        assert_eq!(result.len(), 4);
+
+        assert!(matches!(result[0], ParsedOrRaw::Parsed(_)));
+        assert_eq!(
+            result[1],
+            ParsedOrRaw::Raw(
+                "metric2,tag1=abc,tag3=private field1=123,field2=True 123456789".to_string()
+            )
+        );
+        assert!(matches!(result[2], ParsedOrRaw::Parsed(_)));
+        assert!(matches!(result[3], ParsedOrRaw::Raw(_)));
+    }
+
+    #[test]
+    fn test_de_from_reader_n() {
+        let lines = "metric1 field1=1i,field2=t 1\nmetric2,tag1=1 \
+                     field1=2i,field2=f\n\n#comment\nmetric3 field1=\"a\nb\",field2=t 3\nmetric4 \
+                     field1=4i,field2=t";
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Metric {
+            pub measurement: String,
+
+            pub fields: HashMap<String, Value>,
+        }
+
+        let mut reader = lines.as_bytes();
+        let page = from_reader_n::<Metric>(&mut reader, 2).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[1].measurement, "metric2");
+
+        let page = from_reader_n::<Metric>(&mut reader, 2).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].measurement, "metric3");
+        assert_eq!(page[0].fields["field1"], Value::from("a\nb"));
+
+        let page = from_reader_n::<Metric>(&mut reader, 2).unwrap();
+        assert!(page.is_empty());
+
+        let result = from_str::<Vec<Metric>>(lines);
+        assert_eq!(result.unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_de_timestamp_precision() {
+        let options = Options::new()
+            .precision(Precision::Seconds)
+            .target_precision(Precision::Nanoseconds);
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 1577836800";
+        let result = from_str_with_options::<Metric>(line, &options);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().timestamp, Some(1577836800000000000));
+
+        let options = Options::new()
+            .precision(Precision::Nanoseconds)
+            .target_precision(Precision::Milliseconds);
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 1577836800123456789";
+        let result = from_str_with_options::<Metric>(line, &options);
         assert!(result.is_ok());
+        assert_eq!(result.unwrap().timestamp, Some(1577836800123));
+
+        let options = Options::new()
+            .precision(Precision::Seconds)
+            .target_precision(Precision::Nanoseconds);
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 9223372036854775807";
+        let result = from_str_with_options::<Metric>(line, &options);
+        assert!(matches!(
+            result.unwrap_err().code,
+            ErrorCode::TimestampOutOfRange
+        ));
+    }
+
+    #[test]
+    fn test_de_timestamp_parsing() {
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t -9223372036854775806";
+        let result = from_str::<Metric>(line);
+        assert_eq!(result.unwrap().timestamp, Some(crate::MIN_TIMESTAMP));
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t -9223372036854775807";
+        let result = from_str::<Metric>(line);
+        assert!(matches!(
+            result.unwrap_err().code,
+            ErrorCode::TimestampOutOfRange
+        ));
+
+        let options = Options::new().precision(Precision::Seconds);
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 9223372037";
+        let result = from_str_with_options::<Metric>(line, &options);
+        assert!(matches!(
+            result.unwrap_err().code,
+            ErrorCode::TimestampOutOfRange
+        ));
+
+        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 9223372036854775808";
+        let result = from_str::<Metric>(line);
+        let error = result.unwrap_err();
+        assert!(matches!(error.code, ErrorCode::TimestampOutOfRange));
+        assert_eq!(error.position.column, 50);
+
+        for line in [
+            "metric1,tag1=123,tag3=private field1=321,field2=t 12a3",
+            "metric1,tag1=123,tag3=private field1=321,field2=t --123",
+            "metric1,tag1=123,tag3=private field1=321,field2=t -",
+        ] {
+            let result = from_str::<Metric>(line);
+            assert!(matches!(
+                result.unwrap_err().code,
+                ErrorCode::UnexpectedChar(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_de_detect_precision() {
+        let input = "
+        metric1,tag1=123,tag3=private field1=321,field2=t 1704067200
+        metric1,tag1=123,tag3=private field1=321,field2=t 1704067200000
+        metric1,tag1=123,tag3=private field1=321,field2=t 1704067200000000
+        metric1,tag1=123,tag3=private field1=321,field2=t 1704067200000000000
+        ";
+
+        let options = Options::new()
+            .detect_precision(true)
+            .target_precision(Precision::Milliseconds);
+        let metrics = from_str_with_options::<Vec<Metric>>(input, &options).unwrap();
+        assert!(metrics.iter().all(|m| m.timestamp == Some(1704067200000)));
+    }
+
+    #[test]
+    fn test_de_bytes() {
+        #[derive(Debug, Deserialize, serde::Serialize)]
+        struct RawTags {
+            host: Vec<u8>,
+        }
+
+        #[derive(Debug, Deserialize, serde::Serialize)]
+        struct RawFields {
+            payload: serde_bytes::ByteBuf,
+        }
+
+        #[derive(Debug, Deserialize, serde::Serialize)]
+        struct Raw {
+            measurement: String,
+            tags: RawTags,
+            fields: RawFields,
+        }
+
+        // Latin-1 encoded "café" and "naïve" which are not valid utf8
+        let input = b"metric1,host=caf\xe9 payload=\"na\xefve \\\"x\\\"\"";
+        let raw = from_slice::<Raw>(input).unwrap();
+        assert_eq!(raw.tags.host, b"caf\xe9");
+        assert_eq!(raw.fields.payload.as_slice(), b"na\xefve \"x\"");
+
+        let output = crate::to_vec(&raw).unwrap();
+        assert_eq!(output, input);
+        assert!(crate::to_string(&raw).is_err());
+
+        let result = from_slice::<Metric>(b"metric1,tag1=caf\xe9 field1=321");
+        assert!(matches!(result.unwrap_err().code, ErrorCode::InvalidUtf8));
+    }
+
+    #[test]
+    fn test_de_with_line_info() {
+        let input = "
+        # Header
+        metric1,tag1=123,tag3=private field1=321,field2=t
+
+        metric2,tag1=123,tag3=private field1=321,field2=t 1704067200000000000
+        ";
+
+        let expected = [(3, 26..75), (5, 85..154)];
+        let metrics = from_str::<Vec<crate::WithLineInfo<Metric>>>(input).unwrap();
+        assert_eq!(metrics.len(), 2);
+        for (metric, (line, span)) in metrics.iter().zip(expected.clone()) {
+            assert_eq!(metric.line, line);
+            assert_eq!(metric.span, span);
+        }
+
+        let metrics = from_reader::<Vec<crate::WithLineInfo<Metric>>>(input.as_bytes()).unwrap();
+        let spans: Vec<_> = metrics.into_iter().map(|m| (m.line, m.span)).collect();
+        assert_eq!(spans, expected);
+    }
+
+    #[test]
+    fn test_de_for_each_line() {
+        let input = "
+        metric1,tag1=123,tag3=private field1=1,field2=t
+        metric2,tag1=123,tag3=private field1=2,field2=t
+        metric3,tag1=123,tag3=private field1=3,field2=t
+        ";
+
+        let mut seen = Vec::new();
+        let result = for_each_line(input, |metric: Metric| {
+            seen.push(metric.fields.field1);
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(result.unwrap(), ControlFlow::Continue(()));
+        assert_eq!(seen, [1, 2, 3]);
+
+        let result = for_each_line_reader(input.as_bytes(), |metric: Metric| {
+            match metric.fields.field1 {
+                2 => ControlFlow::Break(metric.measurement),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(result.unwrap(), ControlFlow::Break("metric2".to_string()));
+
+        let result = for_each_line(
+            "metric1 field1",
+            |_: Metric| ControlFlow::<()>::Continue(()),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_de_timestamp_targets() {
+        #[derive(Debug, Deserialize)]
+        struct Archived<T> {
+            measurement: String,
+            fields: HashMap<String, Value>,
+            timestamp: T,
+        }
+
+        let line = "metric1 field1=321 1704067200000000000";
+        let metric = from_str::<Archived<u64>>(line).unwrap();
+        assert_eq!(metric.measurement, "metric1");
+        assert_eq!(metric.fields.len(), 1);
+        assert_eq!(metric.timestamp, 1704067200000000000);
+
+        let metric = from_str::<Archived<u128>>(line).unwrap();
+        assert_eq!(metric.timestamp, 1704067200000000000);
+
+        let metric = from_str::<Archived<String>>(line).unwrap();
+        assert_eq!(metric.timestamp, "1704067200000000000");
+
+        let line = "metric1 field1=321 -1";
+        let result = from_str::<Archived<u64>>(line);
+        assert!(matches!(
+            result.unwrap_err().code,
+            ErrorCode::TimestampOutOfRange
+        ));
+
+        let metric = from_str::<Archived<String>>(line).unwrap();
+        assert_eq!(metric.timestamp, "-1");
+    }
+
+    #[test]
+    fn test_de_map_values() {
+        let options = Options::new()
+            .map_tag(|key, value| match key {
+                "tag2" => value.replace("%20", " "),
+                _ => value,
+            })
+            .map_field(|key, value| match key {
+                "field1" => value.trim_end_matches('i').to_string(),
+                _ => value.to_lowercase(),
+            });
+
+        let line = "metric1,tag1=123,tag2=hello%20world,tag3=private field1=321i,field2=TRUE";
+        let metric = from_str_with_options::<Metric>(line, &options).unwrap();
+        assert_eq!(metric.tags.tag2.as_deref(), Some("hello world"));
+        assert_eq!(metric.fields.field1, 321);
+        assert!(metric.fields.field2);
+
+        let metric = from_str::<Metric>(line).unwrap();
+        assert_eq!(metric.tags.tag2.as_deref(), Some("hello%20world"));
+    }
+
+    #[test]
+    fn test_de_count_lines() {
+        let input = "# Header\nmetric1 field1=\"multi\nline\"\n\n  \nmetric2 field1=123i\n";
+        let counts = count_lines(input);
+        assert_eq!(
+            counts,
+            LineCounts {
+                data: 2,
+                comments: 1,
+                empty: 2,
+                data_bytes: 46,
+            }
+        );
+
+        let counts = count_lines("  metric1\\\nfield1=1i  \n  # comment\n  ");
+        assert_eq!(counts.data, 1);
+        assert_eq!(counts.comments, 1);
+        assert_eq!(counts.empty, 1);
+        assert_eq!(counts.data_bytes, 18);
+
+        assert_eq!(count_lines(""), LineCounts::default());
+    }
+
+    #[test]
+    fn test_de_float_suffix() {
+        #[derive(Debug, Deserialize)]
+        struct Floats {
+            fields: FloatFields,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct FloatFields {
+            field1: f64,
+            field2: f32,
+        }
+
+        let line = "metric1 field1=42i,field2=7u";
+        let metric = from_str::<Floats>(line).unwrap();
+        assert_eq!(metric.fields.field1, 42.0);
+        assert_eq!(metric.fields.field2, 7.0);
+
+        let line = "metric1 field1=-42i,field2=+1.5";
+        let metric = from_str::<Floats>(line).unwrap();
+        assert_eq!(metric.fields.field1, -42.0);
+        assert_eq!(metric.fields.field2, 1.5);
+
+        let line = "metric1 field1=007.5,field2=0012i";
+        let metric = from_str::<Floats>(line).unwrap();
+        assert_eq!(metric.fields.field1, 7.5);
+        assert_eq!(metric.fields.field2, 12.0);
+
+        let line = "metric1 field1=1.5i,field2=-7u";
+        assert!(from_str::<Floats>(line).is_err());
+
+        // Integers which the float can not represent exactly are rejected
+        let line = "metric1 field1=9007199254740992i,field2=16777216i";
+        let metric = from_str::<Floats>(line).unwrap();
+        assert_eq!(metric.fields.field1, 9007199254740992.0);
+        assert_eq!(metric.fields.field2, 16777216.0);
+
+        assert!(from_str::<Floats>("metric1 field1=1i,field2=16777217i").is_err());
+        assert!(from_str::<Floats>("metric1 field1=9007199254740993i,field2=1i").is_err());
+        assert!(from_str::<Floats>("metric1 field1=18446744073709551615u,field2=1i").is_err());
+
+        let options = Options::new().strict_numbers(true);
+        let line = "metric1 field1=42i,field2=1.5";
+        assert!(from_str_with_options::<Floats>(line, &options).is_err());
+
+        let line = "metric1 field1=42,field2=1.5";
+        let metric = from_str_with_options::<Floats>(line, &options).unwrap();
+        assert_eq!(metric.fields.field1, 42.0);
+
+        // Integers follow the line protocol, which does not allow a plus sign
+        let line = "metric1,tag1=0042,tag3=private field1=+1i,field2=t";
+        assert!(from_str::<Metric>(line).is_err());
+
+        let line = "metric1,tag1=0042,tag3=private field1=-01i,field2=t";
+        let metric = from_str::<Metric>(line).unwrap();
+        assert_eq!(metric.tags.tag1, 42);
+        assert_eq!(metric.fields.field1, -1);
+    }
+
+    #[test]
+    fn test_de_flatten() {
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct Base {
+            measurement: String,
+            timestamp: Option<i64>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct CommonTags {
+            host: String,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct FlatTags {
+            #[serde(flatten)]
+            common: CommonTags,
+
+            #[serde(flatten)]
+            extra: BTreeMap<String, String>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct CommonFields {
+            field1: i32,
+            field2: Option<bool>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct FlatFields {
+            #[serde(flatten)]
+            common: CommonFields,
+
+            #[serde(flatten)]
+            extra: BTreeMap<String, Value>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct Flat {
+            #[serde(flatten)]
+            base: Base,
+            tags: FlatTags,
+            fields: FlatFields,
+        }
+
+        let line = r#"metric1,host=server01,region=eu-west field1=321i,field2=t,field3="hello",field4=1.5 123"#;
+        let metric = from_str::<Flat>(line).unwrap();
+        assert_eq!(metric.base.measurement, "metric1");
+        assert_eq!(metric.base.timestamp, Some(123));
+        assert_eq!(metric.tags.common.host, "server01");
+        assert_eq!(metric.tags.extra["region"], "eu-west");
+        assert_eq!(metric.fields.common.field1, 321);
+        assert_eq!(metric.fields.common.field2, Some(true));
+        assert_eq!(metric.fields.extra["field3"], Value::from("hello"));
+        assert_eq!(metric.fields.extra.len(), 2);
+
+        let line = crate::to_string(&metric).unwrap();
+        assert_eq!(
+            line,
+            r#"metric1,host=server01,region=eu-west field1=321i,field2=true,field3="hello",field4=1.5 123"#
+        );
+
+        let metrics = from_str::<Vec<Flat>>(&format!("{line}\n{line}")).unwrap();
+        assert_eq!(metrics.len(), 2);
+    }
+
+    #[test]
+    fn test_de_tagged_enum() {
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct CpuFields {
+            usage: f64,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct MemFields {
+            used: u64,
+            free: u64,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        struct HostTags {
+            host: String,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, serde::Serialize)]
+        #[serde(tag = "measurement", rename_all = "lowercase")]
+        enum Line {
+            Cpu {
+                tags: HostTags,
+                fields: CpuFields,
+                timestamp: Option<i64>,
+            },
+            Mem {
+                fields: MemFields,
+            },
+        }
+
+        let lines = "cpu,host=server01 usage=0.5 1704067200\nmem used=1024i,free=2048i";
+        let parsed = from_str::<Vec<Line>>(lines).unwrap();
+        assert_eq!(
+            parsed,
+            [
+                Line::Cpu {
+                    tags: HostTags {
+                        host: "server01".to_string()
+                    },
+                    fields: CpuFields { usage: 0.5 },
+                    timestamp: Some(1704067200),
+                },
+                Line::Mem {
+                    fields: MemFields {
+                        used: 1024,
+                        free: 2048
+                    },
+                },
+            ]
+        );
+
+        let serialized = crate::to_string(&parsed).unwrap();
+        assert_eq!(serialized, lines);
+
+        let result = from_str::<Line>("disk used=1i");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_de_stream_deserializer() {
+        let lines = r#"
+        # comment line
+        metric1,tag1=1,tag3=public field1=1,field2=t 123456789
+
+        metric2,tag1=2,tag3=private field1=2,field2=f
+        metric3,tag1=3,tag3=private field1=invalid,field2=f
+        metric4,tag1=4,tag3=private field1=4,field2=f
+        "#;
+
+        let mut iter = Deserializer::from_str(lines).into_iter::<Metric>();
+
+        let metric = iter.next().unwrap().unwrap();
+        assert_eq!(metric.measurement, "metric1");
+        assert_eq!(metric.timestamp, Some(123456789));
+
+        let metric = iter.next().unwrap().unwrap();
+        assert_eq!(metric.measurement, "metric2");
+        assert_eq!(metric.fields.field1, 2);
+
+        // Iteration stops after the first error
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let metrics: Result<Vec<Metric>> =
+            Deserializer::from_slice(b"metric1,tag1=1,tag3=public field1=1,field2=t")
+                .into_iter()
+                .collect();
+        assert_eq!(metrics.unwrap().len(), 1);
+
+        assert!(Deserializer::from_str("# comment\n\n")
+            .into_iter::<Metric>()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_de_borrowed_str() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct BorrowedTags<'a> {
+            host: &'a str,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct BorrowedFields<'a> {
+            message: &'a str,
+
+            level: &'a str,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct BorrowedMetric<'a> {
+            #[serde(borrow)]
+            measurement: &'a str,
+
+            #[serde(borrow)]
+            tags: BorrowedTags<'a>,
+
+            #[serde(borrow)]
+            fields: BorrowedFields<'a>,
+        }
+
+        let input = r#"logs,host=server01 message="disk, full",level=warn 1704067200"#;
+        let metric: BorrowedMetric = from_str(input).unwrap();
+        assert_eq!(
+            metric,
+            BorrowedMetric {
+                measurement: "logs",
+                tags: BorrowedTags { host: "server01" },
+                fields: BorrowedFields {
+                    message: "disk, full",
+                    level: "warn",
+                },
+            }
+        );
+
+        let input = "logs,host=a message=\"x\",level=info\nlogs,host=b message=\"y\",level=warn";
+        let metrics: Vec<BorrowedMetric> = from_slice(input.as_bytes()).unwrap();
+        assert_eq!(metrics[1].tags.host, "b");
+        assert_eq!(metrics[1].fields.level, "warn");
+
+        // Escaped values can not be borrowed
+        let input = r#"logs,host=server\ 01 message="disk",level=warn"#;
+        assert!(from_str::<BorrowedMetric>(input).is_err());
+
+        let input = r#"logs,host=server01 message="say \"hi\"",level=warn"#;
+        assert!(from_str::<BorrowedMetric>(input).is_err());
+    }
+
+    #[test]
+    fn test_de_cow_str() {
+        use std::borrow::Cow;
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Tags<'a> {
+            #[serde(borrow)]
+            host: Cow<'a, str>,
+
+            #[serde(borrow)]
+            path: Option<Cow<'a, str>>,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Fields<'a> {
+            #[serde(borrow)]
+            message: Cow<'a, str>,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Log<'a> {
+            #[serde(borrow)]
+            measurement: Cow<'a, str>,
+
+            #[serde(borrow)]
+            tags: Option<Tags<'a>>,
+
+            #[serde(borrow)]
+            fields: Fields<'a>,
+        }
+
+        let input = r#"logs,host=server01,path=C:\\logs message="disk full""#;
+        let log: Log = from_str(input).unwrap();
+        assert!(matches!(log.measurement, Cow::Borrowed("logs")));
+        let tags = log.tags.unwrap();
+        assert!(matches!(tags.host, Cow::Borrowed("server01")));
+        assert!(matches!(log.fields.message, Cow::Borrowed("disk full")));
+
+        // Values which have to be unescaped are allocated
+        assert!(matches!(tags.path, Some(Cow::Owned(ref v)) if v == r"C:\logs"));
+
+        let input = r#"my\ logs message="say \"hi\"""#;
+        let log: Log = from_str(input).unwrap();
+        assert!(matches!(log.measurement, Cow::Owned(ref v) if v == "my logs"));
+        assert!(matches!(log.fields.message, Cow::Owned(ref v) if v == r#"say "hi""#));
+    }
+
+    #[test]
+    fn test_de_deserializer() {
+        use serde::de::DeserializeSeed;
+
+        /// Collects the measurement of each line into an existing vector
+        struct Measurements<'a>(&'a mut Vec<String>);
+
+        impl<'de> DeserializeSeed<'de> for Measurements<'_> {
+            type Value = ();
+
+            fn deserialize<D>(self, deserializer: D) -> std::result::Result<(), D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let metrics = Vec::<Metric>::deserialize(deserializer)?;
+                self.0.extend(metrics.into_iter().map(|m| m.measurement));
+                Ok(())
+            }
+        }
+
+        let input = "metric1,tag1=1,tag3=public field1=1,field2=t\nmetric2,tag1=2,tag3=private \
+                     field1=2,field2=f";
+
+        let mut measurements = vec!["metric0".to_string()];
+        let mut deserializer = Deserializer::from_str(input);
+        Measurements(&mut measurements)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(measurements, ["metric0", "metric1", "metric2"]);
+
+        let mut deserializer = Deserializer::from_reader(input.as_bytes())
+            .options(Options::new().strip_measurement_prefix("metric"));
+        let metrics = Vec::<Metric>::deserialize(&mut deserializer).unwrap();
+        assert_eq!(metrics[0].measurement, "1");
+        assert_eq!(metrics[1].measurement, "2");
+    }
+
+    #[test]
+    fn test_de_from_bufread() {
+        let lines = r#"
+        # comment line
+        metric1,tag1=1,tag3=public field1=1,field2=t 123456789
+
+        metric2,tag1=2,tag2=hello\ world,tag3=private field1=2,field2=f
+        "#;
+
+        let expected = from_str::<Vec<Metric>>(lines).unwrap();
+
+        // A small buffer makes the values cross the boundaries of the buffer
+        for capacity in [1, 3, 8, 1024] {
+            let reader = io::BufReader::with_capacity(capacity, lines.as_bytes());
+            let metrics: Vec<Metric> = from_bufread(reader).unwrap();
+            assert_eq!(metrics, expected);
+        }
+
+        let metrics: Vec<Metric> = from_bufread(lines.as_bytes()).unwrap();
+        assert_eq!(metrics, expected);
+
+        let result = from_bufread::<Metric>("metric1,tag1=1 field1=1".as_bytes());
+        assert!(result.is_err());
+
+        let deserializer = Deserializer::from_bufread(lines.as_bytes());
+        assert_eq!(deserializer.into_iter::<Metric>().count(), 2);
+
+        // Escapes and quoted values crossing the boundaries of the buffer
+        let lines = "a\\ b,t\\=1=x\\,y s=\"q \\\"u,o=te\\\\\",f=1.5 1\n# c\nb\\,c f=2i\n";
+        let expected = from_str::<Vec<crate::Point>>(lines).unwrap();
+        assert_eq!(expected[0].tags["t=1"], "x,y");
+        for capacity in [1, 2, 5, 1024] {
+            let reader = io::BufReader::with_capacity(capacity, lines.as_bytes());
+            let points: Vec<crate::Point> = from_bufread(reader).unwrap();
+            assert_eq!(points, expected);
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_de_from_reader_async() {
+        let lines = r#"
+        # comment line
+        metric1,tag1=1,tag3=public field1=1,field2=t 123456789
+
+        metric2,tag1=2,tag2=hello\ world,tag3=private field1=2,field2=f
+        metric3,tag1=3,tag3=private field1=invalid,field2=f
+        "#;
+
+        let metrics: Result<Vec<Metric>> = from_reader_async(lines.as_bytes()).await;
+        assert!(metrics.is_err());
+
+        let expected = from_str::<Vec<Metric>>(&lines[..lines.find("metric3").unwrap()]).unwrap();
+
+        let mut lines = lines_async::<_, Metric>(lines.as_bytes());
+        assert_eq!(
+            lines.next_line().await.unwrap().as_ref(),
+            Some(&expected[0])
+        );
+        assert_eq!(
+            lines.next_line().await.unwrap().as_ref(),
+            Some(&expected[1])
+        );
+
+        let error = lines.next_line().await.unwrap_err();
+        assert_eq!(error.position.line, 6);
+
+        assert!(lines.next_line().await.unwrap().is_none());
+
+        // Newlines in quoted field values do not end the line
+        let input = "log msg=\"a\nb\" 1\nlog msg=\"c\"";
+        let reader = tokio::io::BufReader::with_capacity(3, input.as_bytes());
+        let mut lines = lines_async::<_, crate::Point>(reader);
+        let point = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(point.fields["msg"], Value::from("a\nb"));
+        let point = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(point.fields["msg"], Value::from("c"));
+        assert!(lines.next_line().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_de_parser() {
+        let input = "# comment line\nmetric1,tag1=1,tag3=public field1=1,field2=t \
+                     123456789\n\nmetric2,tag1=2,tag3=private \
+                     field1=invalid,field2=f\nmetric3,tag1=3,tag3=private field1=3,field2=f";
+
+        // Every chunk size has to give the same result
+        for size in [1, 2, 7, 64, input.len()] {
+            let mut parser = Parser::<Metric>::new();
+            let mut results = Vec::new();
+            for chunk in input.as_bytes().chunks(size) {
+                results.extend(parser.feed(chunk));
+            }
+
+            assert_eq!(
+                parser.remaining(),
+                &input.as_bytes()[input.rfind('\n').unwrap() + 1..]
+            );
+            results.extend(parser.finish());
+
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].as_ref().unwrap().measurement, "metric1");
+            assert_eq!(results[1].as_ref().unwrap_err().position.line, 4);
+            assert_eq!(results[2].as_ref().unwrap().measurement, "metric3");
+        }
+
+        let mut parser = Parser::<Metric>::new();
+        assert!(
+            parser
+                .feed(b"metric1,tag1=1,tag3=public field1=1,field2=t\n")
+                .len()
+                == 1
+        );
+        assert!(parser.remaining().is_empty());
+        assert!(parser.finish().is_none());
+
+        // Escaped newlines and newlines in quoted field values do not end the line
+        let input = "log msg=\"a\nb\",n=1i 1\ncpu,host=a\\\nb x=1\n# c\nlog msg=\"\n\"\nmem y=2i";
+        let expected = from_str::<Vec<crate::Point>>(input).unwrap();
+        assert_eq!(expected.len(), 4);
+        for size in [1, 2, 5, input.len()] {
+            let mut parser = Parser::<crate::Point>::new();
+            let mut results = Vec::new();
+            for chunk in input.as_bytes().chunks(size) {
+                results.extend(parser.feed(chunk));
+            }
+            results.extend(parser.finish());
+
+            let points = results.into_iter().collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(points, expected);
+        }
+
+        let mut parser = Parser::<crate::Point>::new();
+        let results = parser.feed(b"a x=1\nlog msg=\"a\nb\nc\" 1\nb x=\"\n");
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1].as_ref().unwrap().fields["msg"],
+            Value::from("a\nb\nc")
+        );
+        assert_eq!(parser.remaining(), b"b x=\"\n");
+
+        let results = parser.feed(b"\" 2\nb y=");
+        assert_eq!(results.len(), 1);
+        assert_eq!(parser.finish().unwrap().unwrap_err().position.line, 7);
+    }
+
+    #[test]
+    fn test_de_parser_incremental() {
+        // A line fed one byte at a time is deserialized once its newline arrives
+        let line = "log,host=a msg=\"a \\\"\nb\",n=1i 1\n";
+        let expected: crate::Point = from_str(line).unwrap();
+        let mut parser = Parser::<crate::Point>::new();
+        for (i, byte) in line.bytes().enumerate() {
+            let results = parser.feed(&[byte]);
+            match i == line.len() - 1 {
+                true => assert_eq!(results.into_iter().next().unwrap().unwrap(), expected),
+                false => assert!(results.is_empty()),
+            }
+        }
+        assert!(parser.remaining().is_empty());
+
+        // A partial line longer than the maximum is dropped and skipped until its end
+        let input = "cpu usage=1\ncpu,host=a,region=b usage=2\nmem used=3i\n";
+        let options = Options::new().max_line_length(16);
+        let mut parser = Parser::<crate::Point>::with_options(options);
+        let mut results = Vec::new();
+        for byte in input.bytes() {
+            results.extend(parser.feed(&[byte]));
+            assert!(parser.remaining().len() <= 16);
+        }
+        assert!(parser.finish().is_none());
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().measurement, "cpu");
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(
+            error.code,
+            ErrorCode::LineTooLong { line: 2, max: 16 }
+        ));
+        assert_eq!(error.position.line, 2);
+        assert_eq!(results[2].as_ref().unwrap().measurement, "mem");
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_de_from_str_parallel() {
+        let mut input = String::from("# comment line\n");
+        for i in 0..200 {
+            input.push_str(&format!(
+                "metric{i},tag1={i},tag3=public field1={i},field2=t {i}\n\n"
+            ));
+        }
+
+        let metrics: Vec<Metric> = from_str_parallel(&input).unwrap();
+        assert_eq!(metrics, from_str::<Vec<Metric>>(&input).unwrap());
+
+        input.push_str("metric,tag1=1,tag3=public field1=invalid,field2=t\n");
+        let error = from_str_parallel::<Metric>(&input).unwrap_err();
+        assert_eq!(error.position.line, 402);
+    }
 
-        let lines = r#"
-        metric1,tag1=123,tag3=public field1=321,field2=t 123456789
-        #comment line
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_de_from_str_parallel_options() {
+        let input = [
+            "cpu,tag1=1,tag3=public field1=1,field2=t",
+            "mem,tag1=2,tag3=public field1=2,field2=t",
+            "# comment line",
+            "cpu,tag1=3,tag3=public field1=3,field2=t\n",
+        ]
+        .join("\n");
+        let input = input.as_str();
 
-        metric2,tag1=321,tag2=hello\ world,tag3=private field1=123,field2=True 123456789
+        let options = Options::new().only_measurements(["cpu"]);
+        let metrics: Vec<crate::WithLineInfo<Metric>> =
+            from_str_parallel_with_options(input, &options).unwrap();
+        assert_eq!(
+            metrics,
+            from_str_with_options::<Vec<_>>(input, &options).unwrap()
+        );
 
-        #another comment line
+        let spans: Vec<_> = metrics.into_iter().map(|m| (m.line, m.span)).collect();
+        assert_eq!(spans, [(1, 0..40), (4, 97..137)]);
 
-        "#
-        .as_bytes();
-        let result = from_reader::<Vec<Metric>>(lines);
-        assert!(result.is_ok());
+        let options = Options::new().max_lines(2);
+        let error = from_str_parallel_with_options::<Metric>(input, &options).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::MaxLines(2)));
+        assert_eq!(error.position.line, 4);
 
-        let result = result.unwrap();
-        assert_eq!(result.len(), 2);
+        let input =
+            "cpu,tag1=1,tag3=public field1=1,field2=t\ncpu,tag1=2,tag3=public field1=x,field2=t\n";
+        let error = from_str_parallel::<Metric>(input).unwrap_err();
+        let expected = from_str::<Vec<Metric>>(input).unwrap_err();
+        assert_eq!(error.to_string(), expected.to_string());
+        assert_eq!(
+            error.position.previous_columns + error.position.column,
+            expected.position.previous_columns + expected.position.column
+        );
+    }
 
-        let line = "metric1,tag1=123,tag3=private field1=321,field2=t 123456789".as_bytes();
-        let result = from_reader::<Vec<Metric>>(line);
-        assert!(result.is_ok());
+    #[test]
+    fn test_de_measurement_dispatch() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct HostTags {
+            host: String,
+        }
 
-        let result = result.unwrap();
-        assert_eq!(result.len(), 1);
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct CpuFields {
+            usage: f64,
+        }
 
-        let line = r#"
-                
-        "#
-        .as_bytes();
-        let result = from_reader::<Metric>(line);
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Cpu {
+            measurement: String,
+            tags: HostTags,
+            fields: CpuFields,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct DiskFields {
+            free: u64,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Line {
+            Cpu(Cpu),
+            Disk {
+                tags: HostTags,
+                fields: DiskFields,
+                timestamp: i64,
+            },
+            Mem,
+        }
+
+        let lines = "cpu,host=a usage=0.5\nmem,host=b used=1i 1\ndisk,host=c free=10i 2\n";
+        let parsed = from_str::<Vec<Line>>(lines).unwrap();
+        assert_eq!(
+            parsed,
+            [
+                Line::Cpu(Cpu {
+                    measurement: "cpu".to_string(),
+                    tags: HostTags {
+                        host: "a".to_string()
+                    },
+                    fields: CpuFields { usage: 0.5 },
+                }),
+                Line::Mem,
+                Line::Disk {
+                    tags: HostTags {
+                        host: "c".to_string()
+                    },
+                    fields: DiskFields { free: 10 },
+                    timestamp: 2,
+                },
+            ]
+        );
+
+        let result = from_str::<Line>("net,host=d sent=1i");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_de_measurement_filter() {
+        let lines = [
+            "memory,tag1=0,tag3=public field1=0i,field2=t",
+            "metric,tag1=1,tag3=public field1=1i,field2=t",
+            "mem,tag1=2 used=1i",
+            "metric,tag1=3,tag3=private field1=3i,field2=f 1",
+            "disk free=\"invalid",
+        ]
+        .join("\n");
+        let lines = lines.as_str();
+
+        let options = Options::new().only_measurements(["metric"]);
+        let metrics = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].tags.tag1, 1);
+        assert_eq!(metrics[1].measurement, "metric");
+        assert_eq!(metrics[1].fields.field1, 3);
+        assert_eq!(metrics[1].timestamp, Some(1));
+
+        let options = Options::new()
+            .strip_measurement_prefix("me")
+            .filter_measurement(|measurement| measurement == "mory");
+        let metrics = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].measurement, "mory");
+
+        let options = Options::new().only_measurements(["cpu"]);
+        let metrics = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap();
+        assert!(metrics.is_empty());
+
+        let mut parser =
+            Parser::<Metric>::with_options(Options::new().only_measurements(["metric"]));
+        assert_eq!(parser.feed(lines.as_bytes()).len(), 2);
+    }
+
+    #[test]
+    fn test_de_project_fields() {
+        let line =
+            "metric,tag1=1,tag3=public field0=\"a\\\" b\",field1=1i,field3=1.5,field2=t,field4=f 1";
+
+        let mapped = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let options = Options::new().project_fields(true).map_field({
+            let mapped = mapped.clone();
+            move |key, value| {
+                mapped.lock().unwrap().push(key.to_string());
+                value
+            }
+        });
+
+        let metric = from_str_with_options::<Metric>(line, &options).unwrap();
+        assert_eq!(metric.fields.field1, 1);
+        assert!(metric.fields.field2);
+        assert_eq!(metric.timestamp, Some(1));
+        assert_eq!(*mapped.lock().unwrap(), ["field1", "field2"]);
+
+        // Maps still receive every field
+        #[derive(Deserialize)]
+        struct Untyped {
+            fields: HashMap<String, Value>,
+        }
+
+        let untyped = from_str_with_options::<Untyped>(line, &options).unwrap();
+        assert_eq!(untyped.fields.len(), 5);
+    }
+
+    #[test]
+    fn test_de_deny_unknown_fields() {
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StrictTags {
+            host: String,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StrictFields {
+            usage: f64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Strict {
+            measurement: String,
+            tags: StrictTags,
+            fields: StrictFields,
+        }
+
+        let strict = from_str::<Strict>("cpu,host=a usage=0.5").unwrap();
+        assert_eq!(strict.measurement, "cpu");
+        assert_eq!(strict.tags.host, "a");
+        assert_eq!(strict.fields.usage, 0.5);
+
+        let error = from_str::<Strict>("cpu,host=a,region=eu usage=0.5").unwrap_err();
+        match &error.code {
+            ErrorCode::UnknownKey { key, expected } => {
+                assert_eq!(key, "region");
+                assert_eq!(expected, &["host"]);
+            }
+            _ => panic!("unexpected error: {error}"),
+        }
+        assert_eq!(error.position.column, 11);
+
+        let error = from_str::<Vec<Strict>>("cpu,host=a usage=0.5\ncpu,host=b usage=0.5,idle=0.5")
+            .unwrap_err();
+        assert!(matches!(error.code, ErrorCode::UnknownKey { ref key, .. } if key == "idle"));
+        assert_eq!(error.position.line, 2);
+        assert!(error.to_string().contains("unknown key `idle`"));
+
+        let error = from_str::<Strict>("cpu,host=a usage=0.5 1").unwrap_err();
+        assert!(matches!(error.code, ErrorCode::UnknownKey { ref key, .. } if key == "timestamp"));
+    }
+
+    #[test]
+    fn test_de_flatten_extra() {
+        #[derive(Debug, Deserialize)]
+        struct ExtraTags {
+            host: String,
+
+            #[serde(flatten)]
+            extra: HashMap<String, Value>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct ExtraFields {
+            usage: f64,
+
+            #[serde(flatten)]
+            extra: HashMap<String, Value>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Extra {
+            tags: ExtraTags,
+            fields: ExtraFields,
+            timestamp: Option<i64>,
+        }
+
+        let line = r#"cpu,host=123,region=eu,rack=1 usage=5i,idle=0.25,up=t,name="x" 1"#;
+        let options = Options::new().coerce("name", LpType::String);
+        let extra = from_str_with_options::<Extra>(line, &options).unwrap();
+        assert_eq!(extra.tags.host, "123");
+        assert_eq!(extra.tags.extra.len(), 2);
+        assert_eq!(extra.tags.extra["region"], Value::from("eu"));
+        assert_eq!(extra.tags.extra["rack"], Value::from(1.0));
+        assert_eq!(extra.fields.usage, 5.0);
+        assert_eq!(extra.fields.extra.len(), 3);
+        assert_eq!(extra.fields.extra["idle"], Value::from(0.25));
+        assert_eq!(extra.fields.extra["up"], Value::from(true));
+        assert_eq!(extra.fields.extra["name"], Value::from("x"));
+        assert_eq!(extra.timestamp, Some(1));
+
+        // Lines without any unknown keys leave the catch-all empty
+        let extra = from_str::<Extra>("cpu,host=a usage=0.5").unwrap();
+        assert!(extra.tags.extra.is_empty());
+        assert!(extra.fields.extra.is_empty());
+    }
+
+    #[test]
+    fn test_de_tags_as_strings() {
+        #[derive(Debug, Deserialize)]
+        struct Untyped {
+            tags: HashMap<String, Value>,
+            fields: HashMap<String, Value>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct ExtraTags {
+            tag1: i32,
+
+            #[serde(flatten)]
+            extra: HashMap<String, String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Extra {
+            tags: ExtraTags,
+        }
+
+        let line = "metric,tag1=123,tag2=true,tag3=1.5 field1=123i,field2=t";
+
+        let untyped = from_str::<Untyped>(line).unwrap();
+        assert_eq!(untyped.tags["tag1"], Value::from(123.0));
+        assert_eq!(untyped.tags["tag2"], Value::from(true));
+
+        let options = Options::new().tags_as_strings(true);
+        let untyped = from_str_with_options::<Untyped>(line, &options).unwrap();
+        assert_eq!(untyped.tags["tag1"], Value::from("123"));
+        assert_eq!(untyped.tags["tag2"], Value::from("true"));
+        assert_eq!(untyped.tags["tag3"], Value::from("1.5"));
+        assert_eq!(untyped.fields["field1"], Value::from(123i64));
+        assert_eq!(untyped.fields["field2"], Value::from(true));
+
+        let extra = from_str_with_options::<Extra>(line, &options).unwrap();
+        assert_eq!(extra.tags.tag1, 123);
+        assert_eq!(extra.tags.extra["tag2"], "true");
+        assert_eq!(extra.tags.extra["tag3"], "1.5");
+        assert!(from_str::<Extra>(line).is_err());
+    }
+
+    #[test]
+    fn test_de_duplicates() {
+        #[derive(Debug, Deserialize)]
+        struct Untyped {
+            tags: HashMap<String, Value>,
+            fields: HashMap<String, Value>,
+        }
+
+        let line = "metric,tag1=1,tag3=public,tag1=2 field1=1i,field2=t,field1=2i 1";
+
+        // Without a policy the outcome depends on the target
+        assert!(from_str::<Metric>(line).is_err());
+        let untyped = from_str::<Untyped>(line).unwrap();
+        assert_eq!(untyped.tags["tag1"], Value::from(2.0));
+
+        let options = Options::new().duplicates(Duplicates::KeepLast);
+        let metric = from_str_with_options::<Metric>(line, &options).unwrap();
+        assert_eq!(metric.tags.tag1, 2);
+        assert_eq!(metric.fields.field1, 2);
+        assert!(metric.fields.field2);
+        assert_eq!(metric.timestamp, Some(1));
+
+        let options = Options::new().duplicates(Duplicates::KeepFirst);
+        let metric = from_str_with_options::<Metric>(line, &options).unwrap();
+        assert_eq!(metric.tags.tag1, 1);
+        assert_eq!(metric.fields.field1, 1);
+        let untyped = from_str_with_options::<Untyped>(line, &options).unwrap();
+        assert_eq!(untyped.tags["tag1"], Value::from(1.0));
+        assert_eq!(untyped.fields["field1"], Value::from(1i64));
+        assert_eq!(untyped.fields.len(), 2);
+
+        let options = Options::new().duplicates(Duplicates::Error);
+        let error = from_str_with_options::<Untyped>(line, &options).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::DuplicateKey(ref key) if key == "tag1"));
+        assert_eq!(error.position.column, 26);
+
+        let options = Options::new()
+            .duplicates(Duplicates::KeepLast)
+            .project_fields(true);
+        let metrics =
+            from_str_with_options::<Vec<Metric>>(&format!("{line}\n{line}"), &options).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[1].fields.field1, 2);
+    }
+
+    #[test]
+    fn test_de_scientific_notation() {
+        #[derive(Debug, Deserialize)]
+        struct Untyped {
+            fields: HashMap<String, Value>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Floats {
+            a: f64,
+            b: f64,
+            c: f32,
+            d: f64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Typed {
+            fields: Floats,
+        }
+
+        let line = "metric a=1e5,b=1.5E-3,c=-2.5e+2,d=1.E5";
+        let untyped = from_str::<Untyped>(line).unwrap();
+        assert_eq!(untyped.fields["a"], Value::from(1e5));
+        assert_eq!(untyped.fields["b"], Value::from(1.5e-3));
+        assert_eq!(untyped.fields["c"], Value::from(-2.5e2));
+        assert_eq!(untyped.fields["d"], Value::from(1e5));
+
+        let typed = from_str::<Typed>(line).unwrap();
+        assert_eq!(typed.fields.a, 1e5);
+        assert_eq!(typed.fields.b, 1.5e-3);
+        assert_eq!(typed.fields.c, -2.5e2);
+        assert_eq!(typed.fields.d, 1e5);
+
+        // Only float literals InfluxDB accepts are numbers
+        let untyped = from_str::<Untyped>("metric a=-inf,b=1e,c=1e5.5,d=infinity").unwrap();
+        assert_eq!(untyped.fields["a"], Value::from("-inf"));
+        assert_eq!(untyped.fields["b"], Value::from("1e"));
+        assert_eq!(untyped.fields["c"], Value::from("1e5.5"));
+        assert_eq!(untyped.fields["d"], Value::from("infinity"));
+        assert!(from_str::<Typed>("metric a=NaN,b=1,c=1,d=1").is_err());
+    }
+
+    #[test]
+    fn test_de_lenient_whitespace() {
+        let expected = [(1, 1, None), (2, 2, Some(5)), (3, 3, Some(6))];
+        let check = |metrics: Vec<Metric>| {
+            assert_eq!(metrics.len(), expected.len());
+            for (metric, (tag1, field1, timestamp)) in metrics.iter().zip(expected) {
+                assert_eq!(metric.measurement, "metric");
+                assert_eq!(metric.tags.tag1, tag1);
+                assert_eq!(metric.fields.field1, field1);
+                assert_eq!(metric.timestamp, timestamp);
+            }
+        };
+
+        // Windows line endings are always accepted
+        let lines = "metric,tag1=1,tag3=public field1=1i,field2=t\r\nmetric,tag1=2,tag3=public \
+                     field1=2i,field2=t 5\r\nmetric,tag1=3,tag3=public field1=3i,field2=t 6\r\n";
+        check(from_str::<Vec<Metric>>(lines).unwrap());
+
+        let lines = "metric,tag1=1,tag3=public\tfield1=1i,field2=t\r\nmetric,tag1=2,tag3=public  \
+                     field1=2i,field2=t \t5\nmetric,tag1=3,tag3=public \t field1=3i,field2=t\t6 \
+                     \r\n";
+        assert!(from_str::<Vec<Metric>>(lines).is_err());
+
+        let options = Options::new().lenient_whitespace(true);
+        check(from_str_with_options::<Vec<Metric>>(lines, &options).unwrap());
+        check(
+            Deserializer::from_reader(lines.as_bytes())
+                .options(options.clone())
+                .into_iter()
+                .collect::<Result<_>>()
+                .unwrap(),
+        );
+
+        #[derive(Debug, Deserialize)]
+        struct Message {
+            fields: HashMap<String, Value>,
+        }
+
+        // Tabs within string values are kept
+        let message = from_str_with_options::<Message>("log\tmessage=\"a\tb\"", &options).unwrap();
+        assert_eq!(message.fields["message"], Value::from("a\tb"));
+    }
+
+    #[test]
+    fn test_de_from_slice_lossy() {
+        #[derive(Debug, Deserialize)]
+        struct Log {
+            tags: HashMap<String, String>,
+            fields: HashMap<String, Value>,
+        }
+
+        let lines = b"log,host=a\xF0\x90\x80 message=\"\xFFok\"\nlog,host=b message=\"ok\"";
+        assert!(from_slice::<Vec<Log>>(lines).is_err());
+
+        let logs = from_slice_lossy::<Vec<Log>>(lines).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].tags["host"], "a\u{FFFD}");
+        assert_eq!(logs[0].fields["message"], Value::from("\u{FFFD}ok"));
+        assert_eq!(logs[1].tags["host"], "b");
+
+        let options = Options::new().tags_as_strings(true);
+        let log =
+            from_slice_lossy_with_options::<Log>(b"log,host=1 message=\"ok\"", &options).unwrap();
+        assert_eq!(log.tags["host"], "1");
+    }
+
+    #[test]
+    fn test_de_invalid_utf8() {
+        #[derive(Debug, Deserialize)]
+        struct Log {
+            tags: HashMap<String, String>,
+            fields: HashMap<String, Value>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Borrowed<'a> {
+            measurement: &'a str,
+        }
+
+        let log = from_slice::<Log>(b"log,host=a message=\"ok\"").unwrap();
+        assert_eq!(log.tags["host"], "a");
+        assert_eq!(log.fields["message"], Value::from("ok"));
+        let borrowed = from_slice::<Borrowed>(b"log message=\"ok\"").unwrap();
+        assert_eq!(borrowed.measurement, "log");
+
+        let lines = b"log,host=a message=\"ok\"\nlog,host=ab\xFF message=\"ok\"";
+        let error = from_slice::<Vec<Log>>(lines).unwrap_err();
+        assert!(matches!(error.code, ErrorCode::InvalidUtf8));
+        assert_eq!(error.position.line, 2);
+        assert_eq!(error.position.column, 11);
+        assert!(error.to_string().contains("invalid utf-8"));
+
+        let error = from_slice::<Log>(b"log,ho\xC3st=a message=\"ok\"").unwrap_err();
+        assert!(matches!(error.code, ErrorCode::InvalidUtf8));
+        assert_eq!(error.position.column, 6);
+
+        let error = from_slice::<Borrowed>(b"l\xE2og message=\"ok\"").unwrap_err();
+        assert!(matches!(error.code, ErrorCode::InvalidUtf8));
+        assert_eq!(error.position.column, 1);
+    }
+
+    #[test]
+    fn test_de_input_limits() {
+        let lines = "metric,tag1=1,tag3=public field1=1i,field2=t 5\nmetric,tag1=2,tag3=public \
+                     field1=2i,field2=t 6\nmetric,tag1=3,tag3=public field1=3i,field2=t 7\n";
+        assert_eq!(from_str::<Vec<Metric>>(lines).unwrap().len(), 3);
+
+        // Lines exactly as long as the maximum are accepted, a cut short timestamp is
+        // not mistaken for a shorter one
+        let options = Options::new().max_line_length(46);
+        assert_eq!(
+            from_str_with_options::<Vec<Metric>>(lines, &options)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let options = Options::new().max_line_length(45);
+        let err = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap_err();
+        assert!(matches!(
+            err.code,
+            ErrorCode::LineTooLong { line: 1, max: 45 }
+        ));
+        assert_eq!(err.position.line, 1);
+
+        let err = Deserializer::from_reader(lines.as_bytes())
+            .options(Options::new().max_line_length(20))
+            .into_iter::<Metric>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+        assert!(matches!(
+            err.code,
+            ErrorCode::LineTooLong { line: 1, max: 20 }
+        ));
+
+        let options = Options::new().max_lines(3);
+        assert_eq!(
+            from_str_with_options::<Vec<Metric>>(lines, &options)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let options = Options::new().max_lines(2);
+        let err = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::MaxLines(2)));
+        assert_eq!(err.position.line, 3);
+
+        let metrics = Deserializer::from_str(lines)
+            .options(options)
+            .into_iter::<Metric>()
+            .collect::<Vec<_>>();
+        assert_eq!(metrics.len(), 3);
+        assert!(metrics[..2].iter().all(Result::is_ok));
+        assert!(matches!(&metrics[2], Err(e) if matches!(e.code, ErrorCode::MaxLines(2))));
+
+        let options = Options::new().max_tags(1);
+        let err = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::MaxTags(1)));
+
+        let options = Options::new()
+            .max_fields(1)
+            .duplicates(Duplicates::KeepLast);
+        let err = from_str_with_options::<Vec<Metric>>(lines, &options).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::MaxFields(1)));
+
+        let options = Options::new().max_tags(2).max_fields(2);
+        assert_eq!(
+            from_str_with_options::<Vec<Metric>>(lines, &options)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_de_line_tuple() {
+        type Line = (
+            String,
+            HashMap<String, String>,
+            HashMap<String, Value>,
+            Option<i64>,
+        );
+
+        let line = "metric,tag1=a,tag2=b field1=1i,field2=\"x y\" 1577836800";
+        let (measurement, tags, fields, timestamp) = from_str::<Line>(line).unwrap();
+        assert_eq!(measurement, "metric");
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags["tag1"], "a");
+        assert_eq!(tags["tag2"], "b");
+        assert_eq!(fields["field1"], Value::from(1i64));
+        assert_eq!(fields["field2"], Value::from("x y"));
+        assert_eq!(timestamp, Some(1577836800));
+
+        // Missing tag sets and timestamps are empty
+        let lines = "metric field1=1i\nmetric,tag1=a field1=2i 5\n";
+        let metrics = from_str::<Vec<Line>>(lines).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert!(metrics[0].1.is_empty());
+        assert_eq!(metrics[0].3, None);
+        assert_eq!(metrics[1].1["tag1"], "a");
+        assert_eq!(metrics[1].2["field1"], Value::from(2i64));
+        assert_eq!(metrics[1].3, Some(5));
+
+        // The rest of the line is skipped by shorter tuples
+        let metrics = Deserializer::from_str(lines)
+            .into_iter::<(String, HashMap<String, String>)>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[1].0, "metric");
+
+        // Elements can be deserialized into structs as well
+        let (_, tags, fields, _) = from_str::<(String, Tags, Fields, Option<i64>)>(
+            "metric,tag1=1,tag3=public field1=1i,field2=t",
+        )
+        .unwrap();
+        assert_eq!(tags.tag1, 1);
+        assert_eq!(fields.field1, 1);
+    }
+
+    #[test]
+    fn test_de_grouped() {
+        let lines = "cpu,tag1=1,tag3=public field1=1i,field2=t\nmem,tag1=2,tag3=public \
+                     field1=2i,field2=t\n# comment\ncpu,tag1=3,tag3=public field1=3i,field2=t\n";
+
+        let groups = from_str_grouped::<Metric>(lines).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["cpu"].len(), 2);
+        assert_eq!(groups["cpu"][0].tags.tag1, 1);
+        assert_eq!(groups["cpu"][1].tags.tag1, 3);
+        assert_eq!(groups["cpu"][1].measurement, "cpu");
+        assert_eq!(groups["mem"][0].fields.field1, 2);
+
+        #[derive(Debug, Deserialize)]
+        struct Point {
+            fields: Fields,
+        }
+
+        // The target needs no measurement member, which is grouped without its prefix
+        let options = Options::new().strip_measurement_prefix("c");
+        let groups = from_str_grouped_with_options::<Point>(lines, &options).unwrap();
+        assert_eq!(groups["pu"].len(), 2);
+        assert_eq!(groups["pu"][1].fields.field1, 3);
+        assert_eq!(groups["mem"][0].fields.field1, 2);
+
+        let options = Options::new().only_measurements(["mem"]);
+        let groups = from_str_grouped_with_options::<Point>(lines, &options).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups["mem"].len(), 1);
+
+        assert!(from_str_grouped::<Metric>("cpu field1=1i\nmem").is_err());
+    }
+
+    #[test]
+    fn test_de_pair_lists() {
+        #[derive(Debug, PartialEq, serde::Serialize, Deserialize)]
+        struct Point {
+            measurement: String,
+            #[serde(default)]
+            tags: Vec<(String, String)>,
+            fields: Vec<(String, Value)>,
+            timestamp: Option<i64>,
+        }
+
+        // Pairs keep the order and duplicates of the keys
+        let line = "metric,b=1,a=2 y=1i,x=\"s\",y=2.5 5";
+        let point = from_str::<Point>(line).unwrap();
+        assert_eq!(
+            point.tags,
+            [
+                ("b".to_string(), "1".to_string()),
+                ("a".to_string(), "2".to_string())
+            ]
+        );
+        assert_eq!(
+            point.fields,
+            [
+                ("y".to_string(), Value::from(1i64)),
+                ("x".to_string(), Value::from("s")),
+                ("y".to_string(), Value::from(2.5)),
+            ]
+        );
+        assert_eq!(point.timestamp, Some(5));
+
+        let point = from_str::<Point>("metric y=1i").unwrap();
+        assert!(point.tags.is_empty());
+        assert_eq!(point.fields.len(), 1);
+
+        let point = Point {
+            measurement: "metric".to_string(),
+            tags: vec![
+                ("b".to_string(), "1".to_string()),
+                ("a".to_string(), "2".to_string()),
+            ],
+            fields: vec![
+                ("y".to_string(), Value::from(1i64)),
+                ("x".to_string(), Value::from("s")),
+            ],
+            timestamp: None,
+        };
+        assert_eq!(
+            crate::to_string(&point).unwrap(),
+            "metric,b=1,a=2 y=1i,x=\"s\""
+        );
+    }
+
+    #[test]
+    fn test_de_interned() {
+        #[derive(Debug, Deserialize)]
+        struct Tags {
+            host: crate::Interned,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Metric {
+            tags: Tags,
+            fields: HashMap<String, Value>,
+        }
+
+        let lines = "cpu,host=a usage=1i\ncpu,host=b usage=2i\ncpu,host=a usage=3i\n";
+        let check = |metrics: Vec<Metric>| {
+            assert_eq!(metrics.len(), 3);
+            assert_eq!(metrics[0].fields.len(), 1);
+            assert_eq!(&*metrics[0].tags.host, "a");
+            assert_eq!(&*metrics[1].tags.host, "b");
+
+            // Equal values share the same allocation
+            let (first, last) = (&metrics[0].tags.host, &metrics[2].tags.host);
+            assert!(std::ptr::eq(first.as_str(), last.as_str()));
+            assert!(!std::ptr::eq(first.as_str(), metrics[1].tags.host.as_str()));
+        };
+
+        check(from_str(lines).unwrap());
+        check(from_reader(lines.as_bytes()).unwrap());
+
+        // Values no longer in use are released from the pool
+        let metrics: Vec<Metric> = from_str(lines).unwrap();
+        assert_eq!(crate::Interned::release_unused(), 2);
+        drop(metrics);
+        assert_eq!(crate::Interned::release_unused(), 0);
+    }
+
+    #[test]
+    fn test_de_raw() {
+        use crate::{RawLine, RawValue};
+
+        let lines = "cpu\\ load,host=a usage=0.5  1\r\n# comment\nmem,host=a used=1024i\n";
+        let raw = from_str::<Vec<RawLine>>(lines).unwrap();
+        assert_eq!(raw.len(), 2);
+        assert_eq!(raw[0].as_str(), "cpu\\ load,host=a usage=0.5  1");
+        assert_eq!(raw[0].measurement(), "cpu load");
+        assert_eq!(raw[1].as_str(), "mem,host=a used=1024i");
+        assert_eq!(
+            crate::to_string(&raw).unwrap(),
+            lines
+                .replace("\r", "")
+                .replace("# comment\n", "")
+                .trim_end()
+        );
+
+        type Line = (
+            String,
+            HashMap<String, String>,
+            HashMap<String, Value>,
+            Option<i64>,
+        );
+        let (measurement, tags, fields, _) = raw[1].deserialize::<Line>().unwrap();
+        assert_eq!(measurement, "mem");
+        assert_eq!(tags["host"], "a");
+        assert_eq!(fields["used"], Value::from(1024i64));
+
+        let raw = Deserializer::from_reader(lines.as_bytes())
+            .into_iter::<RawLine<'static>>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(raw[0].as_str(), "cpu\\ load,host=a usage=0.5  1");
+
+        // Lines whose measurement has already been read are restored
+        let options = Options::new().only_measurements(["cpu load"]);
+        let raw = from_str_with_options::<Vec<RawLine>>(lines, &options).unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0].as_str(), "cpu\\ load,host=a usage=0.5  1");
+
+        #[derive(Debug, serde::Serialize, Deserialize)]
+        struct Fields<'a> {
+            #[serde(borrow)]
+            message: RawValue<'a>,
+            count: RawValue<'a>,
+        }
+
+        #[derive(Debug, serde::Serialize, Deserialize)]
+        struct Log<'a> {
+            measurement: &'a str,
+            #[serde(borrow)]
+            fields: Fields<'a>,
+        }
+
+        let line = r#"log message="a \"quoted\", string",count=3i"#;
+        let log = from_str::<Log>(line).unwrap();
+        assert_eq!(log.fields.message.as_str(), r#""a \"quoted\", string""#);
+        assert_eq!(log.fields.count.as_str(), "3i");
+        assert_eq!(crate::to_string(&log).unwrap(), line);
+    }
+
+    #[test]
+    fn test_de_deny_trailing() {
+        let lines = "metric,tag1=1,tag3=public field1=1i,field2=t 5\n# comment\n\n";
+        let options = Options::new().deny_trailing(true);
+        assert!(from_str_with_options::<Metric>(lines, &options).is_ok());
+        assert!(from_str_with_options::<Vec<Metric>>(lines, &options).is_ok());
+
+        let lines = "metric,tag1=1,tag3=public field1=1i,field2=t 5\nmetric,tag1=2,tag3=public \
+                     field1=2i,field2=t\n";
+        assert!(from_str::<Metric>(lines).is_ok());
+
+        let err = from_str_with_options::<Metric>(lines, &options).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::TrailingData));
+        assert_eq!(err.position.line, 2);
+        assert!(from_reader_with_options::<Metric>(lines.as_bytes(), &options).is_err());
+        assert!(from_bufread_with_options::<Metric>(lines.as_bytes(), &options).is_err());
+        assert!(from_str_with_options::<Vec<Metric>>(lines, &options).is_ok());
+
+        // Data left on the line itself is trailing data as well
+        let line = "metric,tag1=1,tag3=public field1=1i,field2=t 5 garbage";
+        let err = from_str_with_options::<Metric>(line, &options).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::TrailingData));
+
+        let mut deserializer = Deserializer::from_str(lines);
+        assert_eq!(Metric::deserialize(&mut deserializer).unwrap().tags.tag1, 1);
+        assert!(deserializer.end().is_err());
+    }
+
+    #[test]
+    fn test_de_value_as_str() {
+        let point: crate::Point = from_str("m host=\"server01\",count=1i").unwrap();
+        let fields = point.fields;
+        assert_eq!(fields["host"].as_str(), Some("server01"));
+        assert_eq!(fields["count"].as_str(), None);
+        assert_eq!(Value::None.as_str(), None);
+    }
+
+    #[test]
+    fn test_de_value_from_str() {
+        for (token, value) in [
+            ("-12i", Value::from(-12i64)),
+            ("12i", Value::from(12i64)),
+            ("12u", Value::from(12u64)),
+            ("3.5", Value::from(3.5)),
+            ("-1e3", Value::from(-1000.0)),
+            ("t", Value::from(true)),
+            ("FALSE", Value::from(false)),
+            ("\"\"", Value::from("")),
+            (r#""a \"b\" c\\ d\e""#, Value::from(r#"a "b" c\ d\e"#)),
+        ] {
+            assert_eq!(token.parse::<Value>().unwrap(), value);
+        }
+        assert_eq!(format!("{:#}", "12i".parse::<Value>().unwrap()), "12i");
+
+        for token in ["", "hello", "12x", "\"a\"b\"", r#""a\""#, "\""] {
+            let error = token.parse::<Value>().unwrap_err();
+            assert!(matches!(error.code, ErrorCode::InvalidValue(v) if v == token));
+        }
+    }
+
+    #[test]
+    fn test_de_raw_multiline_string() {
+        use crate::RawLine;
+
+        let lines = "log,host=a\\\"b msg=\"a\nb \\\"c\nd\" 1\ncpu usage=0.5\n";
+        let expected = ["log,host=a\\\"b msg=\"a\nb \\\"c\nd\" 1", "cpu usage=0.5"];
+
+        let raw = from_str::<Vec<RawLine>>(lines).unwrap();
+        assert_eq!(
+            raw.iter().map(RawLine::as_str).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(raw[0].measurement(), "log");
+
+        let raw = Deserializer::from_reader(lines.as_bytes())
+            .into_iter::<RawLine<'static>>()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            raw.iter().map(RawLine::as_str).collect::<Vec<_>>(),
+            expected
+        );
+
+        let options = Options::new().only_measurements(["log"]);
+        let raw = from_str_with_options::<Vec<RawLine>>(lines, &options).unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0].as_str(), expected[0]);
+    }
+
+    #[test]
+    fn test_de_io_error() {
+        /// Reader failing once the first `len` bytes of the input are read
+        struct Failing<'a>(&'a [u8], usize);
+
+        impl io::Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.1 == 0 {
+                    return Err(io::Error::other("connection reset"));
+                }
+
+                let len = buf.len().min(self.1).min(self.0.len());
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                self.1 -= len;
+                Ok(len)
+            }
+        }
+
+        let lines = "metric1,tag1=1,tag3=public field1=1,field2=t\nmetric2,tag1=2,tag3=public \
+                     field1=2,field2=f\n";
+        let is_io = |result: Result<Vec<Metric>>| {
+            matches!(
+                result,
+                Err(Error {
+                    code: ErrorCode::Io(_),
+                    ..
+                })
+            )
+        };
+
+        // Both within a line and at the end of a line
+        for len in [10, 45] {
+            assert!(is_io(from_reader(Failing(lines.as_bytes(), len))));
+
+            let reader = io::BufReader::with_capacity(4, Failing(lines.as_bytes(), len));
+            assert!(is_io(from_bufread(reader)));
+        }
+
+        let reader = io::BufReader::new(Failing(lines.as_bytes(), usize::MAX));
+        assert_eq!(from_bufread::<Vec<Metric>>(reader).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_de_generic_reader() {
+        /// Count the lines of any deserializer
+        fn count<'de, R: super::Read<'de>>(deserializer: Deserializer<R>) -> usize {
+            deserializer.into_iter::<Metric>().count()
+        }
+
+        struct Source<'a> {
+            deserializer: Deserializer<SliceReader<'a>>,
+        }
+
+        let lines = "metric1,tag1=1,tag3=public field1=1,field2=t\nmetric2,tag1=2,tag3=private \
+                     field1=2,field2=f";
+        let source = Source {
+            deserializer: Deserializer::from_str(lines),
+        };
+        assert_eq!(count(source.deserializer), 2);
+
+        let deserializer: Deserializer<IoReader<&[u8]>> =
+            Deserializer::from_reader(lines.as_bytes());
+        assert_eq!(count(deserializer), 2);
+
+        let deserializer: Deserializer<BufReadReader<&[u8]>> =
+            Deserializer::from_bufread(lines.as_bytes());
+        assert_eq!(count(deserializer), 2);
+    }
+
+    #[test]
+    fn test_de_read_set_positions() {
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct Strict {
+            pub field1: i32,
+
+            pub field2: bool,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct StrictMetric {
+            pub measurement: String,
+
+            pub fields: Strict,
+        }
+
+        // Errors of values and keys of a set read in full are reported at the value
+        // or key, not at the end of the set
+        let line = "metric1,tag1=1,tag3=public field1=abc,field2=t,field3=1i";
+        let expected = from_str::<Metric>(line).unwrap_err().to_string();
+        for duplicates in [Duplicates::KeepFirst, Duplicates::KeepLast] {
+            let options = Options::new().duplicates(duplicates);
+            let error = from_str_with_options::<Metric>(line, &options).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+
+        let line = "metric1 field1=1i,field3=1i,field2=t";
+        let expected = from_str::<StrictMetric>(line).unwrap_err();
+        assert!(matches!(expected.code, ErrorCode::UnknownKey { .. }));
+        let options = Options::new().duplicates(Duplicates::KeepLast);
+        let error = from_str_with_options::<StrictMetric>(line, &options).unwrap_err();
+        assert_eq!(error.to_string(), expected.to_string());
+
+        // The position of a replaced value is the one of the value kept
+        let line = "metric1,tag1=1,tag3=public field1=1i,field1=abc,field2=t";
+        let error = from_str_with_options::<Metric>(line, &options).unwrap_err();
+        let expected = from_str::<Metric>("metric1,tag1=1,tag3=public field1=abc,field2=t")
+            .unwrap_err()
+            .position;
+        assert_eq!(error.position.column, expected.column + "field1=1i,".len());
+    }
 }
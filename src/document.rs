@@ -0,0 +1,516 @@
+//! Line protocol files which keep their comments and layout
+
+use std::{fmt, ops::Range, str::FromStr};
+
+use crate::{
+    builder,
+    error::{Error, Result},
+    reader, Point, Value,
+};
+
+/// A whole line protocol file, e.g., the output of `influx export`
+///
+/// Unlike deserializing into a `Vec<Point>`, comment lines, empty lines, and
+/// the order of all lines are kept. Lines which are not changed are written
+/// again exactly as they were read, making it possible to edit a file
+/// programmatically without losing its annotations
+///
+/// # Example
+///
+/// ```rust
+/// let input = "# exported 2024-01-01\ncpu,host=a usage=0.5\n\nmem,host=a used=1i\n";
+///
+/// let mut document: Document = input.parse().unwrap();
+/// for line in document.lines_mut() {
+///     if line.point().measurement == "cpu" {
+///         line.point_mut()
+///             .tags
+///             .insert("region".to_string(), "eu".to_string());
+///     }
+/// }
+///
+/// println!("{document}");
+/// // Output:
+/// // # exported 2024-01-01
+/// // cpu,host=a,region=eu usage=0.5
+/// //
+/// // mem,host=a used=1i
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    /// Every line of the document, in order
+    pub entries: Vec<Entry>,
+
+    trailing_newline: bool,
+}
+
+/// A single line of a [Document]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// A line of data
+    Line(Line),
+
+    /// A comment line, including its `#` and any leading whitespace
+    Comment(String),
+
+    /// An empty line, or a line only containing whitespace
+    Blank(String),
+}
+
+/// A line of data in a [Document], together with its original text
+///
+/// Editing the point with [Line::point_mut] writes the whole line again, while
+/// [Line::set_tag] and [Line::set_field] only change the text of that tag or
+/// field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    point: Point,
+
+    text: Option<String>,
+}
+
+impl Line {
+    /// Create a new line, written as [Point] would be written
+    pub fn new(point: Point) -> Self {
+        Line { point, text: None }
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.point
+    }
+
+    /// Mutable access to the point of the line
+    ///
+    /// The line is considered edited afterwards, and is written from its
+    /// point instead of its original text
+    pub fn point_mut(&mut self) -> &mut Point {
+        self.text = None;
+        &mut self.point
+    }
+
+    /// Set the tag `key` to `value`
+    ///
+    /// The original text of the line is kept apart from the value of the tag,
+    /// a new tag is added after the existing tags. The order and escaping of
+    /// the other tags and fields are left as they were written
+    ///
+    /// # Errors
+    ///
+    /// Fails, leaving the line unchanged, if the key or value is empty or
+    /// contains a newline
+    pub fn set_tag(&mut self, key: impl Into<String>, value: impl Into<String>) -> Result<()> {
+        let (key, value) = (key.into(), value.into());
+        builder::check_tag(&key, &value)?;
+
+        if let Some(text) = &mut self.text {
+            let mut escaped = Vec::new();
+            builder::escape(value.as_bytes(), b"=, ", false, &mut escaped);
+            edit_pair(text, &key, &escaped, false);
+        }
+
+        self.point.tags.insert(key, value);
+        Ok(())
+    }
+
+    /// Set the field `key` to `value`
+    ///
+    /// The original text of the line is kept apart from the value of the
+    /// field, a new field is added after the existing fields. The order and
+    /// escaping of the other tags and fields are left as they were written
+    ///
+    /// # Errors
+    ///
+    /// Fails, leaving the line unchanged, if the key is empty or contains a
+    /// newline, or if the value is [Value::None] or a NaN or infinite float
+    pub fn set_field(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Result<()> {
+        let (key, value) = (key.into(), value.into());
+        builder::check_field(&key, &value)?;
+
+        if let Some(text) = &mut self.text {
+            let mut written = Vec::new();
+            builder::write_field_value(value.clone(), &mut written);
+            edit_pair(text, &key, &written, true);
+        }
+
+        self.point.fields.insert(key, value);
+        Ok(())
+    }
+
+    pub fn into_point(self) -> Point {
+        self.point
+    }
+
+    /// The text of the line as read from the input, None if the line has
+    /// been edited or was not read from an input
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+/// Byte ranges of the keys and values of a line's tags or fields, together
+/// with the end of the set
+struct Pairs {
+    pairs: Vec<(Range<usize>, Range<usize>)>,
+
+    end: usize,
+}
+
+/// Replace the value of `key` in the tag set, or the field set if `field` is
+/// set, of `text` with `value`, which is already escaped
+///
+/// The pair is added to the end of the set if the key does not exist. If the
+/// key is repeated the last occurrence is replaced, as it is the one in effect
+fn edit_pair(text: &mut String, key: &str, value: &[u8], field: bool) {
+    let (tags, fields) = scan_line(text.as_bytes());
+    let set = if field { fields } else { tags };
+    let value = String::from_utf8_lossy(value);
+
+    let existing = set
+        .pairs
+        .iter()
+        .rev()
+        .find(|(k, _)| unescape_key(&text[k.clone()]) == key);
+
+    match existing {
+        Some((_, range)) => text.replace_range(range.clone(), &value),
+        None => {
+            let mut pair = Vec::from(b",");
+            builder::escape(key.as_bytes(), b"=, ", false, &mut pair);
+            pair.push(b'=');
+            pair.extend_from_slice(value.as_bytes());
+            text.insert_str(set.end, &String::from_utf8_lossy(&pair));
+        }
+    }
+}
+
+/// Find the tags and fields of a line which has already been parsed
+/// successfully
+fn scan_line(line: &[u8]) -> (Pairs, Pairs) {
+    let start = line.len() - line.trim_ascii_start().len();
+    let mut index = scan_until(line, start, b", ");
+
+    let mut tags = Vec::new();
+    while line.get(index) == Some(&b',') {
+        let key = index + 1..scan_until(line, index + 1, b"=");
+        let value = key.end + 1..scan_until(line, key.end + 1, b", ");
+        index = value.end;
+        tags.push((key, value));
+    }
+    let tags_end = index;
+
+    let mut fields = Vec::new();
+    while index < line.len() && line[index] == b' ' {
+        index += 1;
+    }
+    loop {
+        let key = index..scan_until(line, index, b"=");
+        let value = key.end + 1..scan_field_value(line, key.end + 1);
+        index = value.end;
+        fields.push((key, value));
+
+        match line.get(index) {
+            Some(b',') => index += 1,
+            _ => break,
+        }
+    }
+
+    let tags = Pairs {
+        pairs: tags,
+        end: tags_end,
+    };
+    let fields = Pairs {
+        pairs: fields,
+        end: index,
+    };
+    (tags, fields)
+}
+
+/// Index of the first unescaped character of `chars` from `start`, or the end
+/// of the line
+fn scan_until(line: &[u8], start: usize, chars: &[u8]) -> usize {
+    let mut index = start;
+    while let Some(&c) = line.get(index) {
+        match c {
+            b'\\' => index += 2,
+            c if chars.contains(&c) => return index,
+            _ => index += 1,
+        }
+    }
+
+    line.len()
+}
+
+/// Index of the end of the field value starting at `start`
+fn scan_field_value(line: &[u8], start: usize) -> usize {
+    if line.get(start) != Some(&b'"') {
+        return scan_until(line, start, b", ");
+    }
+
+    let mut index = start + 1;
+    while let Some(&c) = line.get(index) {
+        match c {
+            b'\\' => index += 2,
+            b'"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+
+    line.len()
+}
+
+/// Remove the escape characters from a tag or field key
+fn unescape_key(key: &str) -> String {
+    let mut unescaped = String::with_capacity(key.len());
+    let mut chars = key.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next)) if matches!(next, ',' | '=' | ' ') => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+impl From<Point> for Line {
+    fn from(value: Point) -> Self {
+        Line::new(value)
+    }
+}
+
+/// Writes the original text of the line if it has not been edited, otherwise
+/// the point of the line
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.text {
+            Some(text) => f.write_str(text),
+            None => self.point.fmt(f),
+        }
+    }
+}
+
+impl Document {
+    /// Create an empty document
+    pub fn new() -> Self {
+        Document {
+            entries: Vec::new(),
+            trailing_newline: true,
+        }
+    }
+
+    /// Append a line of data to the end of the document
+    pub fn push(&mut self, point: Point) {
+        self.entries.push(Entry::Line(Line::new(point)));
+    }
+
+    /// Iterator over the lines of data in the document
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Line(line) => Some(line),
+            _ => None,
+        })
+    }
+
+    /// Mutable iterator over the lines of data in the document
+    pub fn lines_mut(&mut self) -> impl Iterator<Item = &mut Line> {
+        self.entries.iter_mut().filter_map(|entry| match entry {
+            Entry::Line(line) => Some(line),
+            _ => None,
+        })
+    }
+
+    /// Iterator over the points of the lines of data in the document
+    pub fn points(&self) -> impl Iterator<Item = &Point> {
+        self.lines().map(Line::point)
+    }
+
+    /// Push the lines of `text`, which lies between two lines of data
+    ///
+    /// If `continues` the first line is the remainder of the previous line of
+    /// data, e.g., trailing whitespace
+    fn push_text(&mut self, text: &str, continues: bool) {
+        let mut lines = text.split('\n');
+        if continues {
+            let first = lines.next().unwrap_or_default();
+            if let Some(Entry::Line(Line {
+                text: Some(previous),
+                ..
+            })) = self.entries.last_mut()
+            {
+                previous.push_str(first);
+            }
+        }
+
+        for line in lines {
+            let entry = match line.trim_start().starts_with('#') {
+                true => Entry::Comment(line.to_string()),
+                false => Entry::Blank(line.to_string()),
+            };
+            self.entries.push(entry);
+        }
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    /// Parse every line of `s`, fails if any line of data is invalid
+    fn from_str(s: &str) -> Result<Self> {
+        let mut document = Document::new();
+        if s.is_empty() {
+            return Ok(document);
+        }
+
+        let mut offset = 0;
+        for span in reader::Lines::new(s.as_bytes()) {
+            let point: Point = s[span.range.clone()]
+                .parse()
+                .map_err(|e: Error| e.offset_line(span.line))?;
+
+            // Whitespace at the start of the line belongs to the line itself
+            let gap = &s[offset..span.range.start];
+            let leading = match gap.rfind('\n') {
+                Some(index) => {
+                    document.push_text(&gap[..index], offset > 0);
+                    &gap[index + 1..]
+                }
+                None => gap,
+            };
+
+            let text = format!("{leading}{}", &s[span.range.clone()]);
+            document.entries.push(Entry::Line(Line {
+                point,
+                text: Some(text),
+            }));
+            offset = span.range.end;
+        }
+
+        let rest = &s[offset..];
+        document.trailing_newline = rest.ends_with('\n');
+        let rest = rest.strip_suffix('\n').unwrap_or(rest);
+        document.push_text(rest, offset > 0);
+
+        Ok(document)
+    }
+}
+
+/// Writes every line of the document, separated by newlines
+///
+/// Lines written from their [Point] are written as is, even if the point is
+/// not valid line protocol, e.g., a point without fields. Use
+/// [Point::to_line] to check such a point first
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+
+            match entry {
+                Entry::Line(line) => line.fmt(f)?,
+                Entry::Comment(text) | Entry::Blank(text) => f.write_str(text)?,
+            }
+        }
+
+        if self.trailing_newline && !self.entries.is_empty() {
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_document_round_trip() {
+        let input = "# exported\n\n  cpu,host=a usage=0.5 \r\n# note\n  \nmem,host=a \
+                     text=\"multi\nline\"\ndisk free=1i";
+        let mut document: Document = input.parse().unwrap();
+        assert_eq!(document.to_string(), input);
+        assert_eq!(document.lines().count(), 3);
+        assert!(matches!(&document.entries[0], Entry::Comment(text) if text == "# exported"));
+        assert!(matches!(&document.entries[4], Entry::Blank(text) if text == "  "));
+
+        for line in document.lines_mut() {
+            if line.point().measurement == "disk" {
+                line.point_mut().timestamp = Some(1);
+            }
+        }
+        assert_eq!(
+            document.to_string(),
+            input.replace("disk free=1i", "disk free=1i 1")
+        );
+
+        document.push("load,host=a value=1i".parse().unwrap());
+        assert!(document
+            .to_string()
+            .ends_with("disk free=1i 1\nload,host=a value=1i"));
+
+        let document: Document = "\ncpu usage=1i\n".parse().unwrap();
+        assert_eq!(document.entries.len(), 2);
+        assert_eq!(document.to_string(), "\ncpu usage=1i\n");
+
+        let error = "# comment\ncpu usage=1i\ncpu,host usage=1i\n"
+            .parse::<Document>()
+            .unwrap_err();
+        assert_eq!(error.position.line, 3);
+    }
+
+    #[test]
+    fn test_document_edit_line() {
+        let input = "# note\ncpu,zone=b,host=a\\ 1 usage=0.5,msg=\"a, b=c\",count=1i 10\n";
+        let mut document: Document = input.parse().unwrap();
+        let line = document.lines_mut().next().unwrap();
+
+        line.set_tag("host", "x,y").unwrap();
+        line.set_field("msg", "new").unwrap();
+        line.set_field("usage", 0.75).unwrap();
+        assert_eq!(
+            line.text(),
+            Some("cpu,zone=b,host=x\\,y usage=0.75,msg=\"new\",count=1i 10")
+        );
+
+        line.set_tag("region", "eu").unwrap();
+        line.set_field("up", true).unwrap();
+        assert_eq!(
+            line.text(),
+            Some("cpu,zone=b,host=x\\,y,region=eu usage=0.75,msg=\"new\",count=1i,up=true 10")
+        );
+        assert_eq!(line.point().tags["region"], "eu");
+        assert_eq!(line.point().fields["up"], Value::from(true));
+
+        let reparsed: Point = line.text().unwrap().parse().unwrap();
+        assert_eq!(&reparsed, line.point());
+
+        // Tags and fields which can not be written leave the line unchanged
+        let text = line.text().unwrap().to_string();
+        assert!(line.set_tag("", "a").is_err());
+        assert!(line.set_tag("host", "").is_err());
+        assert!(line.set_tag("host", "a\nb").is_err());
+        assert!(line.set_field("", 1).is_err());
+        assert!(line.set_field("a\nb", 1).is_err());
+        assert!(line.set_field("usage", f64::NAN).is_err());
+        assert!(line.set_field("usage", f64::INFINITY).is_err());
+        assert!(line.set_field("usage", Value::None).is_err());
+        assert_eq!(line.text(), Some(text.as_str()));
+        assert_eq!(line.point(), &reparsed);
+
+        // Newlines in string field values are written within their quotes
+        line.set_field("msg", "a\nb").unwrap();
+        let reparsed: Point = line.text().unwrap().parse().unwrap();
+        assert_eq!(&reparsed, line.point());
+        assert!(document.to_string().starts_with("# note\ncpu,zone=b"));
+    }
+}
@@ -2,14 +2,16 @@ use std::{
     error::Error as StdError,
     fmt::{self, Debug, Display},
     io,
+    str::Utf8Error,
 };
 
 use serde::{de, ser};
 
-use crate::reader::datatypes::Position;
+pub use crate::reader::datatypes::Position;
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
+#[non_exhaustive]
 pub enum ErrorCode {
     /// A custom error message
     Message(String),
@@ -24,6 +26,9 @@ pub enum ErrorCode {
     /// Met an unexpacted character while parsing line
     UnexpectedChar(String),
 
+    /// Element contains bytes which are not valid UTF-8
+    InvalidUtf8,
+
     /// Tried to deserialize from an unsupported type
     InvalidType {
         got: String,
@@ -42,9 +47,49 @@ pub enum ErrorCode {
     /// Tried to serialize an infinite float to a string
     InfiniteFloat,
 
+    /// Tried to serialize an integer which does not fit a line protocol
+    /// integer or unsigned integer
+    IntegerOutOfRange(String),
+
+    /// Timestamp does not fit within the supported range
+    TimestampOutOfRange,
+
+    /// Line serialized or read is longer than the configured maximum
+    LineTooLong {
+        /// Number of the line, starting at 1
+        line: usize,
+
+        /// Maximum length of a line in bytes
+        max: usize,
+    },
+
+    /// Input contains more lines than the configured maximum
+    MaxLines(usize),
+
+    /// Tag set contains more tags than the configured maximum
+    MaxTags(usize),
+
+    /// Field set contains more fields than the configured maximum
+    MaxFields(usize),
+
+    /// Key occurs more than once in the same tag or field set
+    DuplicateKey(String),
+
+    /// Input continues after the deserialized value
+    TrailingData,
+
     /// Unsupported key type
     InvalidKey,
 
+    /// Key is not a member of a struct with `#[serde(deny_unknown_fields)]`
+    UnknownKey {
+        /// Tag or field key read from the input
+        key: String,
+
+        /// Keys the struct does have members for
+        expected: Vec<String>,
+    },
+
     /// Set field creates an invalid structure
     InvalidFieldType(String),
 
@@ -57,6 +102,10 @@ pub enum ErrorCode {
     /// Feature is not supported by this crate although it might be in the
     /// future!
     UnsupportedFeature(String),
+
+    /// Line does not match any routing rule and there is no fallback target,
+    /// see [route](crate::route)
+    NoRoute,
 }
 
 /// Custom Error for serde_influxlp
@@ -125,8 +174,72 @@ impl Display for Error {
                     self.position.column, self.position.line
                 )
             }
+            ErrorCode::InvalidUtf8 => {
+                format!(
+                    "invalid utf-8 at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
             ErrorCode::InfiniteFloat => "invalid float: floats must be finite".to_string(),
+            ErrorCode::IntegerOutOfRange(v) => {
+                format!("integer out of range: `{v}` does not fit a 64 bit integer")
+            }
+            ErrorCode::TimestampOutOfRange => {
+                format!(
+                    "timestamp out of range at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
+            ErrorCode::LineTooLong { line, max } => {
+                format!("line too long: line {line} exceeds the maximum of {max} bytes")
+            }
+            ErrorCode::MaxLines(max) => {
+                format!(
+                    "too many lines: exceeds the maximum of {max} lines at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
+            ErrorCode::MaxTags(max) => {
+                format!(
+                    "too many tags: exceeds the maximum of {max} tags at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
+            ErrorCode::MaxFields(max) => {
+                format!(
+                    "too many fields: exceeds the maximum of {max} fields at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
+            // Keys read from an input never start at the first column, a duplicate key
+            // found while serializing or merging points has no position
+            ErrorCode::DuplicateKey(v) if self.position.column == 0 => {
+                format!("duplicate key `{v}`")
+            }
+            ErrorCode::DuplicateKey(v) => {
+                format!(
+                    "duplicate key `{v}` at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
+            ErrorCode::TrailingData => {
+                format!(
+                    "trailing data at column {}, line {}",
+                    self.position.column, self.position.line
+                )
+            }
             ErrorCode::InvalidKey => "invalid key: keys must be of type string".to_string(),
+            ErrorCode::UnknownKey { key, expected } => {
+                let expected = match expected.is_empty() {
+                    true => "there are no keys".to_string(),
+                    false => format!("expected one of `{}`", expected.join("`, `")),
+                };
+
+                format!(
+                    "unknown key `{key}` at column {}, line {}, {expected}",
+                    self.position.column, self.position.line
+                )
+            }
             ErrorCode::InvalidFieldType(v) => format!(
                 "invalid field type `{v}`, expected any of: float, int, uint, string, or bool"
             ),
@@ -137,6 +250,7 @@ impl Display for Error {
             ErrorCode::UnsupportedFeature(v) => {
                 format!("attempted to use a unsupported feature: {v}")
             }
+            ErrorCode::NoRoute => format!("no route for line {}", self.position.line),
         };
 
         write!(f, "an error occured: {err}")
@@ -158,6 +272,10 @@ impl de::Error for Error {
             position: Position::new(),
         }
     }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Error {
+        Error::unknown_key(field, expected, Position::new())
+    }
 }
 
 impl ser::Error for Error {
@@ -205,6 +323,16 @@ impl Error {
         }
     }
 
+    /// Report the error at line `line` instead of the first line, for errors
+    /// of a single line taken from a larger input
+    pub(crate) fn offset_line(mut self, line: usize) -> Self {
+        self.position.line += line - 1;
+        if let ErrorCode::LineTooLong { line: l, .. } = &mut self.code {
+            *l += line - 1;
+        }
+        self
+    }
+
     pub(crate) fn invalid_value(value: impl ToString, mut position: Position) -> Self {
         // We've actually parsed to the end of this value so we adjust position to show
         // it correctly in the error mesage
@@ -242,6 +370,16 @@ impl Error {
         }
     }
 
+    pub(crate) fn invalid_utf8(error: Utf8Error, mut position: Position) -> Self {
+        // Point at the first invalid byte of the element
+        position.column += error.valid_up_to();
+
+        Error {
+            code: ErrorCode::InvalidUtf8,
+            position,
+        }
+    }
+
     pub(crate) fn infinite_float() -> Self {
         Error {
             code: ErrorCode::InfiniteFloat,
@@ -249,6 +387,62 @@ impl Error {
         }
     }
 
+    pub(crate) fn integer_out_of_range(value: impl ToString) -> Self {
+        Error {
+            code: ErrorCode::IntegerOutOfRange(value.to_string()),
+            position: Position::new(),
+        }
+    }
+
+    pub(crate) fn timestamp_out_of_range(position: Position) -> Self {
+        Error {
+            code: ErrorCode::TimestampOutOfRange,
+            position,
+        }
+    }
+
+    pub(crate) fn line_too_long(line: usize, max: usize) -> Self {
+        Error {
+            code: ErrorCode::LineTooLong { line, max },
+            position: Position::new(),
+        }
+    }
+
+    pub(crate) fn max_lines(max: usize, position: Position) -> Self {
+        Error {
+            code: ErrorCode::MaxLines(max),
+            position,
+        }
+    }
+
+    pub(crate) fn max_tags(max: usize, position: Position) -> Self {
+        Error {
+            code: ErrorCode::MaxTags(max),
+            position,
+        }
+    }
+
+    pub(crate) fn max_fields(max: usize, position: Position) -> Self {
+        Error {
+            code: ErrorCode::MaxFields(max),
+            position,
+        }
+    }
+
+    pub(crate) fn duplicate_key(key: impl ToString, position: Position) -> Self {
+        Error {
+            code: ErrorCode::DuplicateKey(key.to_string()),
+            position,
+        }
+    }
+
+    pub(crate) fn trailing_data(position: Position) -> Self {
+        Error {
+            code: ErrorCode::TrailingData,
+            position,
+        }
+    }
+
     pub(crate) fn invalid_key() -> Self {
         Error {
             code: ErrorCode::InvalidKey,
@@ -256,6 +450,16 @@ impl Error {
         }
     }
 
+    pub(crate) fn unknown_key(key: impl ToString, expected: &[&str], position: Position) -> Self {
+        Error {
+            code: ErrorCode::UnknownKey {
+                key: key.to_string(),
+                expected: expected.iter().map(ToString::to_string).collect(),
+            },
+            position,
+        }
+    }
+
     pub(crate) fn invalid_field_type(typ: impl ToString) -> Self {
         Error {
             code: ErrorCode::InvalidFieldType(typ.to_string()),
@@ -283,4 +487,14 @@ impl Error {
             position: Position::new(),
         }
     }
+
+    pub(crate) fn no_route(line: usize) -> Self {
+        let mut position = Position::new();
+        position.line = line;
+
+        Error {
+            code: ErrorCode::NoRoute,
+            position,
+        }
+    }
 }
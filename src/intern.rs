@@ -0,0 +1,162 @@
+use std::{borrow::Borrow, cell::RefCell, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+thread_local! {
+    /// Strings interned by the current thread
+    static POOL: RefCell<HashSet<Arc<str>>> = RefCell::new(HashSet::new());
+}
+
+/// A string shared by every equal [Interned] string of the same thread
+///
+/// Useful for tag values, e.g., host names, which are repeated across
+/// millions of lines while only having a handful of distinct values. Each
+/// distinct value is only allocated once, when deserializing from a string or
+/// slice the value is looked up without allocating at all
+///
+/// # Memory
+///
+/// The pool of each thread grows without bound. Every distinct string
+/// interned by a thread is kept in its pool for the lifetime of the thread,
+/// even after every [Interned] of it has been dropped, until
+/// [Interned::release_unused] is called on that thread. Only intern values
+/// with few distinct values, interning unbounded values such as request ids in
+/// a long running thread leaks memory. Threads of a thread pool, e.g., with
+/// `from_str_parallel`, each have their own pool
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug, Deserialize)]
+/// pub struct Tags {
+///     pub host: Interned,
+/// }
+///
+/// let input = "cpu,host=server01 usage=0.5\ncpu,host=server01 usage=0.7";
+/// let metrics: Vec<Metric> = serde_influxlp::from_str(input)?;
+/// // Both lines share the same allocation of "server01"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned(Arc<str>);
+
+impl Interned {
+    /// Intern `s`, sharing the string with earlier interned strings equal to it
+    pub fn new(s: &str) -> Self {
+        POOL.with_borrow_mut(|pool| match pool.get(s) {
+            Some(interned) => Interned(interned.clone()),
+            None => {
+                let interned: Arc<str> = Arc::from(s);
+                pool.insert(interned.clone());
+                Interned(interned)
+            }
+        })
+    }
+
+    /// The interned string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The shared allocation of the interned string
+    ///
+    /// The string stays in the pool as long as it is in use, including by the
+    /// returned [Arc]
+    pub fn into_arc(self) -> Arc<str> {
+        self.0
+    }
+
+    /// Drop the strings of the current thread's pool which are no longer used
+    /// outside of it
+    ///
+    /// Returns the number of strings left in the pool
+    pub fn release_unused() -> usize {
+        POOL.with_borrow_mut(|pool| {
+            pool.retain(|interned| Arc::strong_count(interned) > 1);
+            pool.len()
+        })
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Interned {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Interned {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Interned {
+    fn from(value: &str) -> Self {
+        Interned::new(value)
+    }
+}
+
+impl From<String> for Interned {
+    fn from(value: String) -> Self {
+        Interned::new(&value)
+    }
+}
+
+impl From<Interned> for Arc<str> {
+    fn from(value: Interned) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Interned {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct InternedVisitor;
+
+        impl Visitor<'_> for InternedVisitor {
+            type Value = Interned;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            // Borrowed and owned strings are forwarded here as well, the value is only
+            // allocated if it has not been interned before
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Interned::new(v))
+            }
+        }
+
+        deserializer.deserialize_str(InternedVisitor)
+    }
+}
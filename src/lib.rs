@@ -14,8 +14,12 @@
 //! Unfortunately due to the required format of the line protocol (as seen
 //! above) the struct, although created by the user, has a required format that
 //! must be followed closely. Some serde features are also currently
-//! unsupported, e.g., tagging. Below are different examples of how to setup and
-//! customize your struct.
+//! unsupported, e.g., adjacently tagged enums. Enums internally tagged by the
+//! measurement, `#[serde(tag = "measurement")]`, are supported. Externally
+//! tagged enums are deserialized from a line by selecting the variant named
+//! after the measurement, letting batches of different measurements be
+//! deserialized into a `Vec` of such an enum. Below are different examples of
+//! how to setup and customize your struct.
 //!
 //! This crate does not support any type which is not [supported](https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/#data-types-and-format) by InfluxDB v2
 //! Line Protocol. These types are:
@@ -260,22 +264,166 @@
 //! Tip: You can deserialize a line protocol string to a struct, then add,
 //! remove, or edit its values before serializing again to change the line
 //! protocol.
+//!
+//! ### Collecting unknown tags and fields
+//!
+//! Tags and fields the struct has no member for are ignored. They can instead
+//! be collected with a `#[serde(flatten)]` map, next to the members for the
+//! known keys. The values of collected tags are inferred like those of fields,
+//! so the map should hold [Value]s, unless they are kept as strings with
+//! [de::Options::tags_as_strings].
+//!
+//! ```rust
+//! #[derive(Debug, Deserialize)]
+//! pub struct Tags {
+//!     pub host: String,
+//!
+//!     #[serde(flatten)]
+//!     pub extra: HashMap<String, Value>,
+//! }
+//! ```
+//!
+//! ### Lines as tuples
+//!
+//! Quick scripts can deserialize a line into a tuple of its measurement, tag
+//! set, field set, and timestamp instead of defining a struct. A line without
+//! tags has an empty tag set and the timestamp is `None` when omitted.
+//!
+//! ```rust
+//! type Line = (
+//!     String,
+//!     HashMap<String, String>,
+//!     HashMap<String, Value>,
+//!     Option<i64>,
+//! );
+//!
+//! let (measurement, tags, fields, timestamp) = serde_influxlp::from_str::<Line>(&string)?;
+//! ```
+//!
+//! ### Borrowing from the input
+//!
+//! When deserializing from a string or slice, the measurement, tag values and
+//! string field values can be borrowed as `&str` as long as they contain no
+//! escape characters. Use `Cow<str>` with `#[serde(borrow)]` to borrow when
+//! possible and only allocate values which have to be unescaped. Serde only
+//! borrows `Cow` members marked directly, e.g., the values of a map of `Cow`s
+//! are always allocated.
+//!
+//! ```rust
+//! #[derive(Debug, Deserialize)]
+//! pub struct Fields<'a> {
+//!     #[serde(borrow)]
+//!     pub message: Cow<'a, str>,
+//! }
+//!
+//! #[derive(Debug, Deserialize)]
+//! pub struct Log<'a> {
+//!     pub measurement: &'a str,
+//!
+//!     #[serde(borrow)]
+//!     pub fields: Fields<'a>,
+//! }
+//! ```
+//!
+//! ## Modules
+//!
+//! The most commonly used items are re-exported at the crate root. The rest
+//! are found in their modules:
+//!
+//! - [de]: Deserialization, its [de::Deserializer] and [de::Options]
+//! - [ser]: Serialization and its [ser::Options]
+//! - [value]: Untyped values, [value::Value], [value::Number], and [value::Map]
+//! - [point](mod@point): Untyped lines, [point::Point] and [point::Batch]
+//! - [document]: Whole files keeping their comments and empty lines,
+//!   [document::Document]
+//! - [route]: Routing lines to buckets by their measurement and tags
+//!
+//! ## Optional features
+//!
+//! - `arbitrary`: Implements `arbitrary::Arbitrary` for [value::Value],
+//!   [value::Number], and [Point], generating points which can be written
+//! - `bytes`: Serialization into a `bytes::BytesMut`, see `to_bytes_mut`
+//! - `chrono`: Allows the timestamp element to be a `chrono::DateTime<Utc>`,
+//!   see `ts_chrono`
+//! - `futures`: A `futures::Sink` serializing each item, see `LineProtocolSink`
+//! - `influxdb2`: Writing with the `influxdb2` client without building its
+//!   `DataPoint`s, see `WritePoint`
+//! - `json`: Conversions between [value::Value] and `serde_json::Value`
+//! - `preserve_order`: Keeps the keys of a [value::Map], and thereby the tags
+//!   and fields of a [Point], in the order they were inserted in or read from
+//!   the input instead of sorting them
+//! - `rayon`: Deserialization of large inputs across threads, see
+//!   `from_str_parallel`
+//! - `schemars`: Implements `schemars::JsonSchema` for [value::Value] and
+//!   [value::Number]
+//! - `time`: Allows the timestamp element to be a `time::OffsetDateTime`, see
+//!   `ts_time`
+//! - `tokio`: Serialization into a `tokio::io::AsyncWrite` and deserialization
+//!   from a `tokio::io::AsyncRead`, see `to_writer_async`, `from_reader_async`,
+//!   and `lines_async`
 
 pub(crate) mod builder;
 pub(crate) mod datatypes;
-pub(crate) mod de;
+pub mod de;
+pub mod document;
 pub(crate) mod error;
+pub(crate) mod intern;
+pub(crate) mod line_info;
+pub mod point;
+pub(crate) mod raw;
 pub(crate) mod reader;
-pub(crate) mod ser;
-pub(crate) mod value;
+pub mod route;
+pub mod ser;
+pub(crate) mod timestamp;
+pub mod value;
 
 pub use crate::{
-    de::{from_reader, from_slice, from_str},
-    error::{Error, ErrorCode},
-    ser::{to_string, to_vec, to_writer},
-    value::{
-        datatypes::{Number, Value},
-        de::from_value,
-        ser::to_value,
+    datatypes::{Duplicates, Precision, MAX_TIMESTAMP, MIN_TIMESTAMP},
+    de::{
+        for_each_line, for_each_line_reader, for_each_line_slice, from_bufread,
+        from_bufread_with_options, from_reader, from_reader_n, from_reader_with_options,
+        from_slice, from_slice_lossy, from_slice_lossy_with_options, from_slice_permissive,
+        from_slice_with_options, from_str, from_str_grouped, from_str_grouped_with_options,
+        from_str_permissive, from_str_with_options, take_lines, LineCounts, ParsedOrRaw, TakeLines,
     },
+    document::Document,
+    error::{Error, ErrorCode, Position},
+    intern::Interned,
+    line_info::WithLineInfo,
+    point::{Batch, Point},
+    raw::{RawLine, RawValue},
+    ser::{
+        to_batches, to_batches_with_options, to_fmt_writer, to_fmt_writer_with_options, to_lines,
+        to_lines_with_options, to_string, to_string_into, to_string_into_with_options,
+        to_string_with_options, to_vec, to_vec_with_options, to_writer, to_writer_iter,
+        to_writer_iter_with_options, to_writer_with_options,
+    },
+    timestamp::{ts_micros, ts_millis, ts_seconds, Timestamp},
+    value::{from_value, to_value, to_values, LpType, Number, Value},
+};
+
+#[cfg(feature = "tokio")]
+pub use crate::de::{
+    from_reader_async, from_reader_async_with_options, lines_async, lines_async_with_options,
+    AsyncLines,
+};
+#[cfg(feature = "rayon")]
+pub use crate::de::{
+    from_slice_parallel, from_slice_parallel_with_options, from_str_parallel,
+    from_str_parallel_with_options,
+};
+#[cfg(feature = "futures")]
+pub use crate::ser::LineProtocolSink;
+#[cfg(feature = "influxdb2")]
+pub use crate::ser::WritePoint;
+#[cfg(feature = "bytes")]
+pub use crate::ser::{to_bytes_mut, to_bytes_mut_with_options};
+#[cfg(feature = "tokio")]
+pub use crate::ser::{
+    to_writer_async, to_writer_async_batched, to_writer_async_batched_with_options,
+    to_writer_async_with_options,
 };
+#[cfg(feature = "chrono")]
+pub use crate::timestamp::ts_chrono;
+#[cfg(feature = "time")]
+pub use crate::timestamp::ts_time;
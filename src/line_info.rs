@@ -0,0 +1,93 @@
+use std::{fmt, marker::PhantomData, ops::Range};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// Name used by the deserializer to recognize [WithLineInfo]
+pub(crate) const NAME: &str = "$serde_influxlp::WithLineInfo";
+
+/// Fields of [WithLineInfo] in the order they are provided by the deserializer
+pub(crate) const FIELDS: &[&str] = &["line", "start", "value", "end"];
+
+/// A deserialized line together with its location in the input
+///
+/// Useful for error reporting further down the pipeline, e.g., rejecting
+/// specific lines back to the producer
+///
+/// # Example
+///
+/// ```rust
+/// let input = "
+/// # Header
+/// metric1 field1=123i
+/// metric2 field1=321i
+/// ";
+///
+/// let metrics: Vec<WithLineInfo<Metric>> = serde_influxlp::from_str(input).unwrap();
+/// for metric in metrics {
+///     println!("{} {:?}", metric.line, metric.span);
+/// }
+/// // Output: 3 10..29
+/// //         4 30..49
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithLineInfo<T> {
+    /// Line number the line starts on, starting at 1
+    pub line: usize,
+
+    /// Byte range of the line in the input
+    pub span: Range<usize>,
+
+    /// The deserialized line
+    pub value: T,
+}
+
+impl<'de, T> Deserialize<'de> for WithLineInfo<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct WithLineInfoVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for WithLineInfoVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = WithLineInfo<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a line with its location")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let (mut line, mut start, mut end, mut value) = (None, None, None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "line" => line = Some(map.next_value()?),
+                        "start" => start = Some(map.next_value()?),
+                        "end" => end = Some(map.next_value()?),
+                        "value" => value = Some(map.next_value()?),
+                        _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+                    }
+                }
+
+                Ok(WithLineInfo {
+                    line: line.ok_or_else(|| de::Error::missing_field("line"))?,
+                    span: start.ok_or_else(|| de::Error::missing_field("start"))?
+                        ..end.ok_or_else(|| de::Error::missing_field("end"))?,
+                    value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct(NAME, FIELDS, WithLineInfoVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,1102 @@
+//! Untyped line protocol data points
+
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt,
+    iter::Peekable,
+    str::FromStr,
+};
+
+use serde::{
+    de::{self, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::{
+    builder,
+    datatypes::Duplicates,
+    de::Options,
+    error::{Error, Result},
+    reader::{self, datatypes::Position},
+    value::{Map, Number, Value},
+};
+
+/// Name used by the deserializer to recognize the field values of a point,
+/// which keep the type they are written with
+pub(crate) const FIELD_VALUE_NAME: &str = "$serde_influxlp::FieldValue";
+
+/// A batch of points which are written together
+pub type Batch = Vec<Point>;
+
+/// An untyped InfluxDB v2 line protocol data point
+///
+/// Useful when the shape of the data is not known beforehand, or when points
+/// are processed generically, e.g., by normalization layers, before being
+/// written. Any line can be parsed into a point with [str::parse], inspected
+/// or changed, and written again with [Point::to_line]. Field values
+/// keep the type they are written with, e.g., `"2"` stays a string
+///
+/// # Example
+///
+/// ```rust
+/// let mut point = Point::new("memory");
+/// point
+///     .tags
+///     .insert("host".to_string(), "server01".to_string());
+/// point
+///     .fields
+///     .insert("used".to_string(), Value::from(2097152u64));
+///
+/// point.scale_field("used", 1.0 / 1048576.0);
+///
+/// let line = serde_influxlp::to_string(&point).unwrap();
+/// // Output: memory,host=server01 used=2
+///
+/// let mut point: Point = "cpu,host=server01 usage=0.5 1577836800".parse().unwrap();
+/// point.tags.insert("region".to_string(), "eu".to_string());
+/// println!("{}", point.to_line().unwrap());
+/// // Output: cpu,host=server01,region=eu usage=0.5 1577836800
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Point {
+    pub measurement: String,
+
+    pub tags: Map<String>,
+
+    pub fields: Map,
+
+    pub timestamp: Option<i64>,
+}
+
+impl Point {
+    /// Create a new point with the given measurement and no tags, fields, or
+    /// timestamp
+    pub fn new(measurement: impl Into<String>) -> Self {
+        Point {
+            measurement: measurement.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Scales the field value of `key` by `factor`
+    ///
+    /// Returns the new value if the field exists and is a number, otherwise
+    /// the field is left untouched and None is returned. See [Number::scale]
+    /// for the rules on how the type of the number is preserved
+    pub fn scale_field(&mut self, key: &str, factor: impl Into<Number>) -> Option<&Value> {
+        let field = self.fields.get_mut(key)?;
+        *field = field.scale(factor)?;
+
+        Some(field)
+    }
+
+    /// Write the point as a line, without a trailing newline
+    ///
+    /// Fails for points which can not be written, e.g., points without fields
+    /// or with a non-finite float field value, unlike
+    /// [Display](fmt::Display) which writes any point as is
+    pub fn to_line(&self) -> Result<String> {
+        crate::to_string(self)
+    }
+
+    /// The series the point belongs to, its measurement and tags as written
+    /// in line protocol with the tags sorted by key
+    ///
+    /// Points of the same series share the same key, regardless of the order
+    /// their tags were read or inserted in
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let point: Point = "cpu,region=eu,host=a usage=0.5".parse().unwrap();
+    /// assert_eq!(point.series_key(), "cpu,host=a,region=eu");
+    /// ```
+    pub fn series_key(&self) -> String {
+        let mut key = Vec::new();
+        builder::escape(self.measurement.as_bytes(), b", ", false, &mut key);
+
+        let mut tags: Vec<_> = self.tags.iter().collect();
+        tags.sort_unstable();
+        for (k, v) in tags {
+            key.push(b',');
+            builder::escape(k.as_bytes(), b"=, ", false, &mut key);
+            key.push(b'=');
+            builder::escape(v.as_bytes(), b"=, ", false, &mut key);
+        }
+
+        // Only valid utf8 has been escaped
+        String::from_utf8(key).unwrap()
+    }
+
+    /// A 64 bit hash of [Point::series_key]
+    ///
+    /// The hash is stable across processes, platforms, and versions of this
+    /// crate, using 64 bit FNV-1a, and can thereby be stored or used to shard
+    /// points between hosts
+    pub fn series_hash(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+
+        self.series_key()
+            .bytes()
+            .fold(OFFSET, |hash, b| (hash ^ b as u64).wrapping_mul(PRIME))
+    }
+}
+
+/// Build a [Point] from a measurement and, optionally, tags, fields, and a
+/// timestamp
+///
+/// Keys are either identifiers or string literals. Tag values are converted
+/// with [ToString], field values with [Value::from]
+///
+/// # Example
+///
+/// ```rust
+/// let point = serde_influxlp::point!(
+///     "cpu",
+///     tags: { "data center": "eu-1", host: "server01" },
+///     fields: { cores: 8u64, usage: 0.5 },
+///     ts: 1577836800,
+/// );
+/// // Output: cpu,data\ center=eu-1,host=server01 cores=8i,usage=0.5 1577836800
+/// ```
+#[macro_export]
+macro_rules! point {
+    (@key $key:ident) => {
+        ::std::string::ToString::to_string(::std::stringify!($key))
+    };
+    (@key $key:literal) => {
+        ::std::string::ToString::to_string(&$key)
+    };
+    (
+        $measurement:expr
+        $(, tags: { $($tag:tt : $tag_value:expr),* $(,)? })?
+        $(, fields: { $($field:tt : $field_value:expr),* $(,)? })?
+        $(, ts: $timestamp:expr)?
+        $(,)?
+    ) => {{
+        #[allow(unused_mut)]
+        let mut point = $crate::Point::new($measurement);
+        $($(
+            point.tags.insert(
+                $crate::point!(@key $tag),
+                ::std::string::ToString::to_string(&$tag_value),
+            );
+        )*)?
+        $($(
+            point
+                .fields
+                .insert($crate::point!(@key $field), $crate::Value::from($field_value));
+        )*)?
+        $(point.timestamp = ::std::option::Option::Some($timestamp);)?
+        point
+    }};
+}
+
+/// Merge the points which share their series and timestamp into one point,
+/// mirroring how InfluxDB stores them
+///
+/// The fields of the points are combined, `duplicates` decides which value is
+/// kept when several points have a different value for the same field, where
+/// [Duplicates::KeepLast] is the behavior of InfluxDB. The merged points keep
+/// the position of their first point in the batch
+///
+/// # Example
+///
+/// ```rust
+/// let batch: Batch = serde_influxlp::from_str("cpu,host=a usage=0.5 1\ncpu,host=a idle=0.3 1")?;
+/// let merged = point::merge(batch, Duplicates::KeepLast)?;
+/// // merged[0]: cpu,host=a idle=0.3,usage=0.5 1
+/// ```
+pub fn merge(batch: impl IntoIterator<Item = Point>, duplicates: Duplicates) -> Result<Batch> {
+    let merged = merge_keyed(batch, duplicates)?;
+    Ok(merged.into_iter().map(|(_, point)| point).collect())
+}
+
+/// Series and timestamp of a point, which identify it in InfluxDB
+type PointKey = (String, Option<i64>);
+
+/// Same as [merge], but keeps the key of each merged point
+fn merge_keyed(
+    batch: impl IntoIterator<Item = Point>,
+    duplicates: Duplicates,
+) -> Result<Vec<(PointKey, Point)>> {
+    let mut merged: Vec<(PointKey, Point)> = Vec::new();
+    let mut series: HashMap<PointKey, usize> = HashMap::new();
+    for point in batch {
+        let key = (point.series_key(), point.timestamp);
+        let index = match series.entry(key) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                merged.push((entry.key().clone(), point));
+                entry.insert(merged.len() - 1);
+                continue;
+            }
+        };
+
+        let fields = &mut merged[index].1.fields;
+        for (key, value) in point.fields {
+            match fields.get(&key) {
+                Some(existing) if *existing == value => (),
+                Some(_) => match duplicates {
+                    Duplicates::Error => return Err(Error::duplicate_key(key, Position::new())),
+                    Duplicates::KeepFirst => (),
+                    Duplicates::KeepLast => {
+                        fields.insert(key, value);
+                    }
+                },
+                None => {
+                    fields.insert(key, value);
+                }
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Which points are considered duplicates of each other, see [dedup_lines]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dedup {
+    /// Points with the same series, timestamp, and fields
+    #[default]
+    Exact,
+
+    /// Points with the same series and timestamp, regardless of their fields
+    Series,
+}
+
+/// Remove duplicate points from `batch`, e.g., of a batch which was sent
+/// more than once
+///
+/// Of each set of duplicates the last point is kept, at its position in the
+/// batch
+///
+/// # Example
+///
+/// ```rust
+/// let mut batch: Batch = serde_influxlp::from_str("cpu usage=0.5 1\ncpu usage=0.7 1")?;
+/// point::dedup_lines(&mut batch, Dedup::Series);
+/// // batch: [cpu usage=0.7 1]
+/// ```
+pub fn dedup_lines(batch: &mut Batch, by: Dedup) {
+    // Indices of the points which are kept, by their series and timestamp
+    let mut kept: HashMap<(String, Option<i64>), Vec<usize>> = HashMap::new();
+    let mut keep = vec![false; batch.len()];
+    for (index, point) in batch.iter().enumerate().rev() {
+        let series = kept
+            .entry((point.series_key(), point.timestamp))
+            .or_default();
+        let is_duplicate = match by {
+            Dedup::Exact => series.iter().any(|&i| batch[i].fields == point.fields),
+            Dedup::Series => !series.is_empty(),
+        };
+
+        if !is_duplicate {
+            series.push(index);
+            keep[index] = true;
+        }
+    }
+
+    let mut keep = keep.into_iter();
+    batch.retain(|_| keep.next().unwrap_or_default());
+}
+
+/// Remove duplicate lines from `input` without deserializing the lines
+/// which are kept
+///
+/// With [Dedup::Exact] lines are only duplicates if their text is the same,
+/// and the lines are not parsed, so invalid lines are kept without failing.
+/// With [Dedup::Series] each line is parsed to find its series and
+/// timestamp, failing on the first invalid line. Of each set of duplicates
+/// the last line is kept. The lines are copied as is, comments and empty
+/// lines are dropped, and the lines are separated by newlines
+pub fn dedup_raw_lines(input: &str, by: Dedup) -> Result<String> {
+    let lines: Vec<(&str, usize)> = reader::Lines::new(input.as_bytes())
+        .map(|span| (&input[span.range], span.line))
+        .collect();
+
+    let mut seen: HashSet<(Cow<'_, str>, Option<i64>)> = HashSet::new();
+    let mut keep = Vec::with_capacity(lines.len());
+    for &(line, number) in lines.iter().rev() {
+        let key = match by {
+            Dedup::Exact => (Cow::Borrowed(line), None),
+            Dedup::Series => {
+                let point: Point = line.parse().map_err(|e: Error| e.offset_line(number))?;
+                (Cow::Owned(point.series_key()), point.timestamp)
+            }
+        };
+
+        keep.push(seen.insert(key));
+    }
+
+    let kept: Vec<&str> = lines
+        .iter()
+        .zip(keep.into_iter().rev())
+        .filter(|(_, keep)| *keep)
+        .map(|((line, _), _)| *line)
+        .collect();
+    Ok(kept.join("\n"))
+}
+
+/// The order points are sorted in, see [sort_lines]
+///
+/// Points without a timestamp are sorted before points with one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortBy {
+    /// By their timestamp
+    #[default]
+    Timestamp,
+
+    /// By their series, see [Point::series_key]
+    Series,
+
+    /// By their series, and the points of the same series by their timestamp
+    SeriesThenTimestamp,
+}
+
+impl SortBy {
+    /// The key `point` is sorted by
+    fn key(&self, point: &Point) -> (Option<String>, Option<i64>) {
+        match self {
+            SortBy::Timestamp => (None, point.timestamp),
+            SortBy::Series => (Some(point.series_key()), None),
+            SortBy::SeriesThenTimestamp => (Some(point.series_key()), point.timestamp),
+        }
+    }
+}
+
+/// Sort the points of `batch`, InfluxDB ingests sorted batches faster
+///
+/// The sort is stable, points which are equal keep their order
+///
+/// # Example
+///
+/// ```rust
+/// let mut batch: Batch =
+///     serde_influxlp::from_str("cpu,host=b usage=0.5 1\ncpu,host=a usage=0.7 2")?;
+/// point::sort_lines(&mut batch, SortBy::SeriesThenTimestamp);
+/// // batch: [cpu,host=a usage=0.7 2, cpu,host=b usage=0.5 1]
+/// ```
+pub fn sort_lines(batch: &mut Batch, by: SortBy) {
+    batch.sort_by_cached_key(|point| by.key(point));
+}
+
+/// Sort the lines of `input`, see [sort_lines]
+///
+/// Each line is parsed to find its series and timestamp. The lines are copied
+/// as is, comments and empty lines are dropped, and the lines are separated
+/// by newlines
+pub fn sort_raw_lines(input: &str, by: SortBy) -> Result<String> {
+    let mut lines = reader::Lines::new(input.as_bytes())
+        .map(|span| {
+            let line = &input[span.range];
+            let point: Point = line.parse().map_err(|e: Error| e.offset_line(span.line))?;
+
+            Ok((by.key(&point), line))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    lines.sort_by(|a, b| a.0.cmp(&b.0));
+    let lines: Vec<&str> = lines.into_iter().map(|(_, line)| line).collect();
+    Ok(lines.join("\n"))
+}
+
+/// Group `points` by their measurement
+///
+/// The points of each measurement are kept in their order. See
+/// [measurement_runs] for grouping a stream of points without collecting them
+/// first
+///
+/// # Example
+///
+/// ```rust
+/// let batch: Batch = serde_influxlp::from_str("cpu usage=0.5\nmem used=1i\ncpu usage=0.7")?;
+/// let groups = point::group_by_measurement(batch);
+/// // groups["cpu"].len() == 2
+/// // groups["mem"].len() == 1
+/// ```
+pub fn group_by_measurement(points: impl IntoIterator<Item = Point>) -> HashMap<String, Batch> {
+    let mut groups: HashMap<String, Batch> = HashMap::new();
+    for point in points {
+        match groups.get_mut(&point.measurement) {
+            Some(group) => group.push(point),
+            None => {
+                groups.insert(point.measurement.clone(), vec![point]);
+            }
+        }
+    }
+
+    groups
+}
+
+/// Split a stream of points into runs of consecutive points sharing their
+/// measurement
+///
+/// Only the current run is kept in memory, making it possible to split a feed
+/// of any length. A measurement occurs in more than one run if its points are
+/// not consecutive, see [sort_lines] to put them next to each other
+///
+/// # Example
+///
+/// ```rust
+/// let points = serde_influxlp::take_lines::<_, Point>(reader, usize::MAX).map_while(Result::ok);
+/// for (measurement, batch) in point::measurement_runs(points) {
+///     handlers[&measurement].handle(batch);
+/// }
+/// ```
+pub fn measurement_runs<I>(points: I) -> MeasurementRuns<I::IntoIter>
+where
+    I: IntoIterator<Item = Point>,
+{
+    MeasurementRuns {
+        points: points.into_iter().peekable(),
+    }
+}
+
+/// Iterator over runs of points sharing their measurement, see
+/// [measurement_runs]
+#[derive(Debug)]
+pub struct MeasurementRuns<I>
+where
+    I: Iterator<Item = Point>,
+{
+    points: Peekable<I>,
+}
+
+impl<I> Iterator for MeasurementRuns<I>
+where
+    I: Iterator<Item = Point>,
+{
+    type Item = (String, Batch);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.points.next()?;
+        let measurement = first.measurement.clone();
+
+        let mut batch = vec![first];
+        while let Some(point) = self.points.next_if(|p| p.measurement == measurement) {
+            batch.push(point);
+        }
+
+        Some((measurement, batch))
+    }
+}
+
+/// Differences between two batches, see [diff]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchDiff {
+    /// Points only found in the new batch
+    pub added: Batch,
+
+    /// Points only found in the old batch
+    pub removed: Batch,
+
+    /// Points found in both batches with different fields, as the old and the
+    /// new point
+    pub changed: Vec<(Point, Point)>,
+}
+
+impl BatchDiff {
+    /// Checks if the batches contain the same points
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the points of two batches, e.g., to validate the output of a
+/// migration or replay
+///
+/// Points are matched by their series and timestamp. The points of a batch
+/// which share them are first combined with [merge] and
+/// [Duplicates::KeepLast], as InfluxDB would store them, so the compared
+/// points are the merged ones. Added and changed points are listed in the
+/// order of the new batch, removed points in the order of the old batch, each
+/// at the position of its first point
+///
+/// # Example
+///
+/// ```rust
+/// let old: Batch = serde_influxlp::from_str("cpu usage=0.5 1\ncpu usage=0.7 2")?;
+/// let new: Batch = serde_influxlp::from_str("cpu usage=0.5 1\ncpu usage=0.9 2\ncpu usage=0.1 3")?;
+///
+/// let diff = point::diff(&old, &new);
+/// // diff.added: [cpu usage=0.1 3]
+/// // diff.changed: [(cpu usage=0.7 2, cpu usage=0.9 2)]
+/// ```
+pub fn diff(old: &[Point], new: &[Point]) -> BatchDiff {
+    // Keeping the last value never fails
+    let old = merge_keyed(old.iter().cloned(), Duplicates::KeepLast).unwrap();
+    let new = merge_keyed(new.iter().cloned(), Duplicates::KeepLast).unwrap();
+
+    let old_points: HashMap<&PointKey, &Point> = old.iter().map(|(k, p)| (k, p)).collect();
+    let new_keys: HashSet<&PointKey> = new.iter().map(|(k, _)| k).collect();
+
+    let mut diff = BatchDiff::default();
+    for (key, point) in &new {
+        match old_points.get(key) {
+            Some(old) if old.fields != point.fields => {
+                diff.changed.push(((*old).clone(), point.clone()))
+            }
+            Some(_) => (),
+            None => diff.added.push(point.clone()),
+        }
+    }
+
+    diff.removed = old
+        .into_iter()
+        .filter(|(key, _)| !new_keys.contains(key))
+        .map(|(_, point)| point)
+        .collect();
+
+    diff
+}
+
+/// Generates points which can be written, with up to 8 tags and between 1
+/// and 8 fields
+///
+/// Measurements, keys, and tag values are short strings of letters, digits,
+/// and the characters which have to be escaped. Field values are never
+/// [Value::None] or non-finite floats, and timestamps are within the range
+/// supported by InfluxDB
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Point {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut point = Point::new(arbitrary_name(u)?);
+        for _ in 0..u.int_in_range(0..=8)? {
+            point.tags.insert(arbitrary_name(u)?, arbitrary_name(u)?);
+        }
+
+        for _ in 0..u.int_in_range(1..=8)? {
+            let value = match u.arbitrary()? {
+                Value::None => Value::Boolean(u.arbitrary()?),
+                Value::Number(Number::Float(n)) if !n.is_finite() => Value::from(0.0),
+                value => value,
+            };
+            point.fields.insert(arbitrary_name(u)?, value);
+        }
+
+        if u.arbitrary()? {
+            point.timestamp = Some(u.int_in_range(crate::MIN_TIMESTAMP..=crate::MAX_TIMESTAMP)?);
+        }
+
+        Ok(point)
+    }
+}
+
+/// A measurement, key, or tag value of 1 to 16 characters, starting with a
+/// letter or digit
+#[cfg(feature = "arbitrary")]
+fn arbitrary_name(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<String> {
+    const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-., =";
+    const FIRST: usize = 62;
+
+    let mut name = String::from(*u.choose(&CHARS[..FIRST])? as char);
+    for _ in 0..u.int_in_range(0..=15)? {
+        name.push(*u.choose(CHARS)? as char);
+    }
+
+    Ok(name)
+}
+
+impl Serialize for Point {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Point", 4)?;
+        state.serialize_field("measurement", &self.measurement)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("fields", &self.fields)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PointVisitor;
+
+        impl<'de> Visitor<'de> for PointVisitor {
+            type Value = Point;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a line")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut point = Point::default();
+                let (mut measurement, mut fields) = (None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "measurement" => measurement = Some(map.next_value()?),
+                        "tags" => point.tags = map.next_value()?,
+                        "fields" => fields = Some(map.next_value::<FieldSet>()?.0),
+                        "timestamp" => point.timestamp = map.next_value()?,
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                point.measurement =
+                    measurement.ok_or_else(|| de::Error::missing_field("measurement"))?;
+                point.fields = fields.ok_or_else(|| de::Error::missing_field("fields"))?;
+                Ok(point)
+            }
+        }
+
+        const FIELDS: &[&str] = &["measurement", "tags", "fields", "timestamp"];
+        deserializer.deserialize_struct("Point", FIELDS, PointVisitor)
+    }
+}
+
+/// The field set of a point, see [FieldValue]
+struct FieldSet(Map);
+
+impl<'de> Deserialize<'de> for FieldSet {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldSetVisitor;
+
+        impl<'de> Visitor<'de> for FieldSetVisitor {
+            type Value = FieldSet;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a field set")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut fields = Map::new();
+                while let Some((key, value)) = map.next_entry::<String, FieldValue>()? {
+                    fields.insert(key, value.0);
+                }
+
+                Ok(FieldSet(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldSetVisitor)
+    }
+}
+
+/// A field value of a point
+///
+/// Line protocol field values keep the type they are written with, e.g.,
+/// `"2"` stays a string and `5i` a signed integer, so the point is written
+/// again unchanged. Other formats provide the value as is
+struct FieldValue(Value);
+
+impl<'de> Deserialize<'de> for FieldValue {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldValueVisitor;
+
+        impl<'de> Visitor<'de> for FieldValueVisitor {
+            type Value = FieldValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a field value")
+            }
+
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> std::result::Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Value::deserialize(deserializer).map(FieldValue)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(FIELD_VALUE_NAME, FieldValueVisitor)
+    }
+}
+
+impl FromStr for Point {
+    type Err = Error;
+
+    /// Parse a single line into a point, any other lines are an error
+    fn from_str(s: &str) -> Result<Self> {
+        crate::from_str_with_options(s, &Options::new().deny_trailing(true))
+    }
+}
+
+/// Writes the point as a line, without a trailing newline
+///
+/// The point is written as is, without being checked, points which can not be
+/// written, e.g., points without fields, are written as well. Use
+/// [Point::to_line] to only write valid lines
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut line = Vec::new();
+        builder::escape(self.measurement.as_bytes(), b", ", false, &mut line);
+        for (key, value) in &self.tags {
+            line.push(b',');
+            builder::escape(key.as_bytes(), b"=, ", false, &mut line);
+            line.push(b'=');
+            builder::escape(value.as_bytes(), b"=, ", false, &mut line);
+        }
+
+        let fields = self.fields.iter().filter(|(_, value)| !value.is_none());
+        for (index, (key, value)) in fields.enumerate() {
+            line.push(if index == 0 { b' ' } else { b',' });
+            builder::escape(key.as_bytes(), b"=, ", false, &mut line);
+            line.push(b'=');
+            builder::write_field_value(value.clone(), &mut line);
+        }
+
+        if let Some(timestamp) = self.timestamp {
+            line.push(b' ');
+            line.extend_from_slice(itoa::Buffer::new().format(timestamp).as_bytes());
+        }
+
+        // Only valid utf8 has been written
+        f.write_str(&String::from_utf8_lossy(&line))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{de::from_str, ser::to_string, ErrorCode};
+
+    use super::*;
+
+    #[test]
+    fn test_point_series_key() {
+        let a: Point = "cpu\\ load,region=eu,host=a\\,1 usage=0.5 1"
+            .parse()
+            .unwrap();
+        let b: Point = "cpu\\ load,host=a\\,1,region=eu usage=0.7 2"
+            .parse()
+            .unwrap();
+        assert_eq!(a.series_key(), "cpu\\ load,host=a\\,1,region=eu");
+        assert_eq!(a.series_key(), b.series_key());
+        assert_eq!(a.series_hash(), b.series_hash());
+
+        let c: Point = "cpu\\ load,host=b usage=0.5".parse().unwrap();
+        assert_ne!(a.series_hash(), c.series_hash());
+
+        // FNV-1a is stable, so the hash of a key never changes
+        assert_eq!(Point::new("").series_hash(), 0xcbf29ce484222325);
+        assert_eq!(Point::new("a").series_hash(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_point_merge() {
+        let input = "cpu,host=a idle=0.3 1\nmem,host=a used=1i 1\ncpu,host=a idle=0.3,usage=0.5 \
+                     1\ncpu,host=a usage=0.7 2\ncpu,host=a usage=0.9 1";
+        let batch: Batch = from_str(input).unwrap();
+
+        let merged = crate::point::merge(batch.clone(), Duplicates::KeepLast).unwrap();
+        let lines: Vec<String> = merged.iter().map(Point::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "cpu,host=a idle=0.3,usage=0.9 1",
+                "mem,host=a used=1i 1",
+                "cpu,host=a usage=0.7 2"
+            ]
+        );
+
+        let merged = crate::point::merge(batch.clone(), Duplicates::KeepFirst).unwrap();
+        assert_eq!(merged[0].to_string(), "cpu,host=a idle=0.3,usage=0.5 1");
+
+        let error = crate::point::merge(batch, Duplicates::Error).unwrap_err();
+        assert_eq!(error.to_string(), "an error occured: duplicate key `usage`");
+        assert!(matches!(error.code, ErrorCode::DuplicateKey(key) if key == "usage"));
+    }
+
+    #[test]
+    fn test_point_dedup() {
+        use crate::point::{dedup_lines, dedup_raw_lines, Dedup};
+
+        let input = "cpu,host=a usage=0.5 1\nmem used=1i 1\n# resent\ncpu,host=a usage=0.5 \
+                     1\ncpu,host=a usage=0.7 1\ncpu,host=b usage=0.5 1";
+        let batch: Batch = from_str(input).unwrap();
+
+        let mut exact = batch.clone();
+        dedup_lines(&mut exact, Dedup::Exact);
+        let lines: Vec<String> = exact.iter().map(Point::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "mem used=1i 1",
+                "cpu,host=a usage=0.5 1",
+                "cpu,host=a usage=0.7 1",
+                "cpu,host=b usage=0.5 1"
+            ]
+        );
+
+        let mut series = batch;
+        dedup_lines(&mut series, Dedup::Series);
+        let lines: Vec<String> = series.iter().map(Point::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "mem used=1i 1",
+                "cpu,host=a usage=0.7 1",
+                "cpu,host=b usage=0.5 1"
+            ]
+        );
+
+        assert_eq!(
+            dedup_raw_lines(input, Dedup::Exact).unwrap(),
+            "mem used=1i 1\ncpu,host=a usage=0.5 1\ncpu,host=a usage=0.7 1\ncpu,host=b usage=0.5 1"
+        );
+        assert_eq!(
+            dedup_raw_lines(input, Dedup::Series).unwrap(),
+            "mem used=1i 1\ncpu,host=a usage=0.7 1\ncpu,host=b usage=0.5 1"
+        );
+
+        let error = dedup_raw_lines("a f=1i\nb,t f=1i", Dedup::Series).unwrap_err();
+        assert_eq!(error.position.line, 2);
+    }
+
+    #[test]
+    fn test_point_sort() {
+        use crate::point::{sort_lines, sort_raw_lines, SortBy};
+
+        let input = "cpu,host=b usage=0.1 3\ncpu,host=a usage=0.2 2\n# c\ncpu,host=b usage=0.3 \
+                     1\ncpu,host=a usage=0.4";
+        let sorted = |by| {
+            let mut batch: Batch = from_str(input).unwrap();
+            sort_lines(&mut batch, by);
+            batch.iter().map(Point::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(SortBy::Timestamp),
+            [
+                "cpu,host=a usage=0.4",
+                "cpu,host=b usage=0.3 1",
+                "cpu,host=a usage=0.2 2",
+                "cpu,host=b usage=0.1 3"
+            ]
+        );
+        assert_eq!(
+            sorted(SortBy::Series),
+            [
+                "cpu,host=a usage=0.2 2",
+                "cpu,host=a usage=0.4",
+                "cpu,host=b usage=0.1 3",
+                "cpu,host=b usage=0.3 1"
+            ]
+        );
+        assert_eq!(
+            sorted(SortBy::SeriesThenTimestamp),
+            [
+                "cpu,host=a usage=0.4",
+                "cpu,host=a usage=0.2 2",
+                "cpu,host=b usage=0.3 1",
+                "cpu,host=b usage=0.1 3"
+            ]
+        );
+
+        assert_eq!(
+            sort_raw_lines(input, SortBy::SeriesThenTimestamp).unwrap(),
+            sorted(SortBy::SeriesThenTimestamp).join("\n")
+        );
+    }
+
+    #[test]
+    fn test_point_group_by_measurement() {
+        use crate::point::{group_by_measurement, measurement_runs};
+
+        let input = "cpu usage=0.5\nmem used=1i\ncpu usage=0.7\ncpu usage=0.9";
+        let batch: Batch = from_str(input).unwrap();
+
+        let groups = group_by_measurement(batch.clone());
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["cpu"].len(), 3);
+        assert_eq!(groups["cpu"][1].fields["usage"], Value::from(0.7));
+        assert_eq!(groups["mem"].len(), 1);
+
+        let runs: Vec<(String, usize)> = measurement_runs(batch)
+            .map(|(measurement, batch)| (measurement, batch.len()))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                ("cpu".to_string(), 1),
+                ("mem".to_string(), 1),
+                ("cpu".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_point_diff() {
+        let old: Batch = from_str("cpu usage=0.5 1\ncpu usage=0.7 2\nmem used=1i 1").unwrap();
+        let new: Batch =
+            from_str("cpu usage=0.1 3\ncpu usage=0.5 1\ncpu usage=0.8 2\ncpu usage=0.9 2").unwrap();
+
+        let diff = crate::point::diff(&old, &new);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added, [new[0].clone()]);
+        assert_eq!(diff.removed, [old[2].clone()]);
+        assert_eq!(diff.changed, [(old[1].clone(), new[3].clone())]);
+
+        assert!(crate::point::diff(&old, &old).is_empty());
+
+        // Points sharing their series and timestamp are merged before comparing
+        let old: Batch = from_str("disk free=1i,used=2i 5").unwrap();
+        let new: Batch = from_str("disk free=1i 5\ndisk used=2i 5").unwrap();
+        assert!(crate::point::diff(&old, &new).is_empty());
+
+        let new: Batch = from_str("disk free=1i 5\ndisk used=3i 5").unwrap();
+        let changed = crate::point::diff(&old, &new).changed;
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].1.fields["free"], Value::from(1));
+        assert_eq!(changed[0].1.fields["used"], Value::from(3));
+    }
+
+    #[test]
+    fn test_point_scale_field() {
+        let mut point = Point::new("memory");
+        point
+            .tags
+            .insert("host".to_string(), "server01".to_string());
+        point.fields.insert("total".to_string(), Value::from(4i64));
+        point
+            .fields
+            .insert("used".to_string(), Value::from(2097152u64));
+        point.timestamp = Some(1577836800);
+
+        assert_eq!(
+            point.scale_field("used", 1.0 / 1048576.0),
+            Some(&Value::from(2.0))
+        );
+        assert_eq!(
+            point.scale_field("total", 1024),
+            Some(&Value::from(4096i64))
+        );
+        assert_eq!(point.scale_field("missing", 2), None);
+
+        let line = to_string(&point).unwrap();
+        assert_eq!(line, "memory,host=server01 total=4096i,used=2 1577836800");
+    }
+
+    #[test]
+    fn test_point_round_trip() {
+        let line = "cpu\\ load,host=server01,region=eu count=3i,msg=\"a \
+                    \\\"b\\\"\",up=true,usage=0.5 1577836800";
+        let mut point: Point = line.parse().unwrap();
+        assert_eq!(point.measurement, "cpu load");
+        assert_eq!(point.tags["host"], "server01");
+        assert_eq!(point.fields["usage"], Value::from(0.5));
+        assert_eq!(point.fields["up"], Value::from(true));
+        assert_eq!(point.fields["msg"], Value::from("a \"b\""));
+        assert_eq!(point.timestamp, Some(1577836800));
+
+        point.tags.retain(|key, _| key != "region");
+        point.fields.insert("count".to_string(), Value::from(4i64));
+        assert_eq!(
+            point.to_string(),
+            "cpu\\ load,host=server01 count=4i,msg=\"a \\\"b\\\"\",up=true,usage=0.5 1577836800"
+        );
+
+        let point: Point = "memory used=1i".parse().unwrap();
+        assert!(point.tags.is_empty());
+        assert_eq!(point.timestamp, None);
+        assert_eq!(point.to_string(), "memory used=1i");
+
+        let points: Vec<Point> = from_str("a f=1i\nb,t=1 f=2i 5").unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].tags["t"], "1");
+
+        assert!("a f=1i\nb f=2i".parse::<Point>().is_err());
+        assert!("memory".parse::<Point>().is_err());
+    }
+
+    #[test]
+    fn test_point_field_types() {
+        for line in [
+            "m x=\"2\"",
+            "m x=\"T\"",
+            "m x=\"-1.5e3\",y=\"12i\"",
+            "m,t=1 a=\"2\",b=\"T\",c=5i,d=1.5,e=true,f=\"x=\\\"y\\\"\" 1",
+        ] {
+            assert_eq!(line.parse::<Point>().unwrap().to_string(), line);
+        }
+
+        let point: Point = "m a=\"2\",b=5i,c=5u,d=5".parse().unwrap();
+        assert_eq!(point.fields["a"], Value::from("2"));
+        assert_eq!(point.fields["b"], Value::from(5i64));
+        assert_eq!(point.fields["c"], Value::from(5u64));
+        assert_eq!(point.fields["d"], Value::from(5.0));
+
+        let line = "m a=18446744073709551615i";
+        let point: Point = line.parse().unwrap();
+        assert_eq!(point.fields["a"], Value::from(u64::MAX));
+        assert_eq!(point.to_string(), line);
+
+        // Fields read in full to handle duplicate keys keep their type as well
+        let options = crate::de::Options::new().duplicates(crate::Duplicates::KeepLast);
+        let point: Point = crate::from_str_with_options("m a=1i,a=\"T\",b=5i", &options).unwrap();
+        assert_eq!(point.fields["a"], Value::from("T"));
+        assert_eq!(point.fields["b"], Value::from(5i64));
+
+        assert!("m x=5x".parse::<Point>().is_err());
+    }
+
+    #[test]
+    fn test_point_display() {
+        assert_eq!(Point::default().to_string(), "");
+        assert!(Point::default().to_line().is_err());
+
+        let mut point = Point::new("cpu");
+        assert_eq!(point.to_string(), "cpu");
+        assert!(point.to_line().is_err());
+
+        point
+            .fields
+            .insert("usage".to_string(), Value::from(f64::NAN));
+        assert_eq!(point.to_string(), "cpu usage=NaN");
+        assert!(point.to_line().is_err());
+
+        let line = "cpu\\ load,host=a\\=b usage=0.5 1";
+        let point: Point = line.parse().unwrap();
+        assert_eq!(point.to_string(), line);
+        assert_eq!(point.to_line().unwrap(), line);
+
+        let mut document: crate::Document = "cpu usage=1i\n".parse().unwrap();
+        document.push(Point::new("mem"));
+        assert_eq!(document.to_string(), "cpu usage=1i\nmem\n");
+    }
+
+    #[test]
+    fn test_point_macro() {
+        let point = crate::point!(
+            "cpu",
+            tags: { "data center": "eu-1", host: "server01" },
+            fields: { cores: 8u64, up: true, usage: 0.5 },
+            ts: 1577836800,
+        );
+        assert_eq!(point.tags["data center"], "eu-1");
+        assert_eq!(
+            point.to_string(),
+            "cpu,data\\ center=eu-1,host=server01 cores=8i,up=true,usage=0.5 1577836800"
+        );
+
+        let point = crate::point!("memory", fields: { used: 1i64 });
+        assert_eq!(point.to_string(), "memory used=1i");
+        assert_eq!(crate::point!("memory"), Point::new("memory"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    error::Result,
+    reader::{self, Reader},
+};
+
+/// Name used by the deserializer and serializer to recognize [RawLine]
+pub(crate) const LINE_NAME: &str = "$serde_influxlp::RawLine";
+
+/// Name used by the deserializer and serializer to recognize [RawValue]
+pub(crate) const VALUE_NAME: &str = "$serde_influxlp::RawValue";
+
+/// An unparsed line, exactly as written in the input
+///
+/// The line can be inspected, deserialized later, or serialized again
+/// untouched. Useful for routing or proxying lines, where only the
+/// measurement is of interest. Lines are borrowed from the input when
+/// deserializing from a string or slice, without its line ending
+///
+/// # Example
+///
+/// ```rust
+/// let input = "cpu,host=a usage=0.5\nmem,host=a used=1024i";
+///
+/// let lines: Vec<RawLine> = serde_influxlp::from_str(input).unwrap();
+/// for line in lines {
+///     if line.measurement() == "cpu" {
+///         let metric: Metric = line.deserialize().unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawLine<'a>(Cow<'a, str>);
+
+impl<'a> RawLine<'a> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The unescaped measurement of the line
+    pub fn measurement(&self) -> String {
+        let measurement = reader::SliceReader::new(self.0.as_bytes()).parse_measurement();
+        String::from_utf8_lossy(&measurement).into_owned()
+    }
+
+    /// Deserialize the line into a struct `T`
+    pub fn deserialize<'b, T>(&'b self) -> Result<T>
+    where
+        T: Deserialize<'b>,
+    {
+        crate::from_str(&self.0)
+    }
+
+    /// Convert into a line which owns its text
+    pub fn into_owned(self) -> RawLine<'static> {
+        RawLine(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for RawLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for RawLine<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(LINE_NAME, &*self.0)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawLine<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = deserializer.deserialize_newtype_struct(LINE_NAME, RawVisitor(PhantomData))?;
+        Ok(RawLine(raw))
+    }
+}
+
+/// A single unparsed value, exactly as written in the input
+///
+/// Field values keep their type suffix and string values their quotes and
+/// escape characters, e.g., `123i` or `"a \"quoted\" string"`. Serialized
+/// again untouched. Values the deserializer has already read, e.g., when
+/// handling duplicate keys with
+/// [de::Options::duplicates](crate::de::Options::duplicates), are provided
+/// unescaped instead
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug, Deserialize)]
+/// pub struct Fields<'a> {
+///     #[serde(borrow)]
+///     pub payload: RawValue<'a>,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawValue<'a>(Cow<'a, str>);
+
+impl<'a> RawValue<'a> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert into a value which owns its text
+    pub fn into_owned(self) -> RawValue<'static> {
+        RawValue(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl fmt::Display for RawValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for RawValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(VALUE_NAME, &*self.0)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawValue<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = deserializer.deserialize_newtype_struct(VALUE_NAME, RawVisitor(PhantomData))?;
+        Ok(RawValue(raw))
+    }
+}
+
+/// Visits the text of a [RawLine] or [RawValue], borrowed when possible
+struct RawVisitor<'a>(PhantomData<&'a ()>);
+
+impl<'de: 'a, 'a> Visitor<'de> for RawVisitor<'a> {
+    type Value = Cow<'a, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("raw line protocol")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v))
+    }
+}
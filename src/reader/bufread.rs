@@ -0,0 +1,306 @@
+use std::io;
+
+use crate::{datatypes::Element, error::Result, Error};
+
+use super::{
+    datatypes::{
+        LineLength, Position, BACKSLASH, COMMA, DOUBLEQUOTE, EQUALSIGN, NEWLINE, TAB, WHITESPACE,
+    },
+    Reader,
+};
+
+/// Reader over an [io::BufRead], scanning the values and lines directly in its
+/// buffer instead of reading one byte at a time like
+/// [IoReader](super::IoReader), see
+/// [Deserializer::from_bufread](crate::de::Deserializer::from_bufread)
+pub struct BufReadReader<R>
+where
+    R: io::BufRead,
+{
+    reader: R,
+
+    /// Previously parrsed element
+    prev: Element,
+
+    /// Next expected element to parse
+    next: Element,
+
+    include_tags: bool,
+
+    /// Whether tabs and repeated spaces separate elements as well
+    lenient: bool,
+
+    line_length: LineLength,
+
+    position: Position,
+
+    /// Number of newlines skipped so far
+    newlines: usize,
+
+    /// Error reading from the reader which is yet to be reported
+    error: Option<io::Error>,
+
+    /// Whether reading from the reader has failed, nothing is read afterwards
+    failed: bool,
+}
+
+impl<R> BufReadReader<R>
+where
+    R: io::BufRead,
+{
+    pub(crate) fn new(reader: R) -> Self {
+        let mut reader = Self {
+            reader,
+            prev: Element::Measurement,
+            next: Element::Measurement,
+            include_tags: false,
+            lenient: false,
+            line_length: LineLength::default(),
+            position: Position::new(),
+            newlines: 0,
+            error: None,
+            failed: false,
+        };
+        let _ = reader.skip_until_valid_line();
+
+        reader
+    }
+
+    /// Fill the buffer of the reader, returns false at the end of the input or
+    /// once reading has failed
+    ///
+    /// Once filled the buffer is borrowed from the reader with
+    /// [io::BufRead::fill_buf] again, which does not read
+    fn fill_buf(&mut self) -> bool {
+        while !self.failed {
+            match self.reader.fill_buf() {
+                Ok(buf) => return !buf.is_empty(),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    self.failed = true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Read up to the first byte for which `is_end` returns true, given
+    /// whether the byte is within double quotes, a buffer at a time
+    ///
+    /// If `escapes` a backslash escapes the byte following it and is left out
+    /// of `out`, double quotes are only tracked if `quoted`
+    fn scan(
+        &mut self,
+        escapes: bool,
+        quoted: bool,
+        mut out: Option<&mut Vec<u8>>,
+        is_end: impl Fn(u8, bool) -> bool,
+    ) {
+        let mut is_escaped = false;
+        let mut in_quote = false;
+        loop {
+            if !self.fill_buf() {
+                return;
+            }
+
+            // Only the other fields are updated while the buffer is borrowed
+            let buf = self.reader.fill_buf().unwrap_or_default();
+
+            let mut len = 0;
+            let mut is_done = false;
+            for &c in buf {
+                if (!is_escaped && is_end(c, in_quote)) || !self.line_length.allows(c) {
+                    is_done = true;
+                    break;
+                }
+
+                len += 1;
+                self.line_length.advance(c);
+                if c == NEWLINE {
+                    self.newlines += 1;
+                }
+
+                if escapes && c == BACKSLASH && !is_escaped {
+                    is_escaped = true;
+                    continue;
+                }
+
+                if quoted && !is_escaped && c == DOUBLEQUOTE {
+                    in_quote = !in_quote;
+                }
+
+                is_escaped = false;
+                if let Some(out) = &mut out {
+                    out.push(c);
+                }
+            }
+
+            self.reader.consume(len);
+            self.position.column += len;
+            if is_done {
+                return;
+            }
+        }
+    }
+}
+
+impl<'de, R> Reader<'de> for BufReadReader<R>
+where
+    R: io::BufRead,
+{
+    fn get_position(&self) -> Position {
+        self.position.clone()
+    }
+
+    fn include_tags(&mut self) {
+        self.include_tags = true;
+    }
+
+    fn tags_included(&self) -> bool {
+        self.include_tags
+    }
+
+    fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    fn line_length(&mut self) -> &mut LineLength {
+        &mut self.line_length
+    }
+
+    fn peek_char(&mut self) -> Result<u8> {
+        if !self.fill_buf() {
+            return Err(Error::unexpected_eof());
+        }
+
+        match self.reader.fill_buf() {
+            Ok(&[c, ..]) if self.line_length.allows(c) => Ok(c),
+            _ => Err(Error::unexpected_eof()),
+        }
+    }
+
+    fn skip_char(&mut self) {
+        // Consuming past the end of the buffer is not allowed by every reader
+        match self.peek_char() {
+            Ok(c) => {
+                self.line_length.advance(c);
+                if c == NEWLINE {
+                    self.newlines += 1;
+                }
+
+                self.reader.consume(1);
+            }
+            Err(_) => return,
+        }
+
+        self.position.column += 1;
+    }
+
+    fn take_io_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn skip_line(&mut self) {
+        self.scan(false, false, None, |c, _| c == NEWLINE);
+        self.skip_char();
+        self.set_next_line();
+    }
+
+    fn parse_measurement(&mut self) -> Vec<u8> {
+        let lenient = self.lenient;
+        let mut result = Vec::new();
+        self.scan(true, false, Some(&mut result), |c, _| {
+            c == COMMA || is_separator(c, lenient)
+        });
+
+        result
+    }
+
+    fn parse_tag_key(&mut self) -> Vec<u8> {
+        let lenient = self.lenient;
+        let mut result = Vec::new();
+        self.scan(true, false, Some(&mut result), |c, _| {
+            c == COMMA || c == EQUALSIGN || is_separator(c, lenient)
+        });
+
+        result
+    }
+
+    fn parse_field_value(&mut self) -> Vec<u8> {
+        let mut result = Vec::new();
+        self.scan(true, true, Some(&mut result), is_field_value_end);
+
+        if result.len() >= 2 && result.starts_with(b"\"") && result.ends_with(b"\"") {
+            result = result[1..result.len() - 1].to_vec();
+        }
+
+        result
+    }
+
+    fn skip_field_value(&mut self) -> Result<()> {
+        self.scan(true, true, None, is_field_value_end);
+        self.determine_next_element()
+    }
+
+    fn discard_next_element(&mut self) {
+        self.scan(true, true, None, |c, in_quote| {
+            !in_quote && c.is_ascii_whitespace()
+        });
+    }
+
+    fn get_offset(&self) -> usize {
+        self.position.previous_columns + self.position.column
+    }
+
+    fn get_line(&self) -> usize {
+        self.newlines + 1
+    }
+
+    fn get_prev_element(&self) -> &Element {
+        &self.prev
+    }
+
+    fn set_prev_element(&mut self, prev: Element) {
+        self.prev = prev;
+    }
+
+    fn get_next_element(&self) -> &Element {
+        &self.next
+    }
+
+    fn set_next_element(&mut self, next: Element) {
+        self.next = next;
+    }
+
+    fn has_next_line(&mut self) -> bool {
+        if self.skip_until_valid_line().is_err() {
+            return false;
+        }
+
+        self.peek_char().is_ok()
+    }
+
+    fn set_next_line(&mut self) {
+        self.position.next_line();
+
+        self.prev = Element::Measurement;
+        self.next = Element::Measurement;
+        self.include_tags = false;
+    }
+}
+
+/// Check if `c` separates the elements of a line, see [Reader::is_separator]
+fn is_separator(c: u8, lenient: bool) -> bool {
+    c == WHITESPACE || (lenient && c == TAB)
+}
+
+/// Check if `c` ends a field value, given whether it is within double quotes
+fn is_field_value_end(c: u8, in_quote: bool) -> bool {
+    !in_quote && (c == COMMA || c == EQUALSIGN || c.is_ascii_whitespace())
+}
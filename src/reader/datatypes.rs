@@ -1,10 +1,64 @@
 pub(crate) const BACKSLASH: u8 = b'\\';
 pub(crate) const NEWLINE: u8 = b'\n';
 pub(crate) const WHITESPACE: u8 = b' ';
+pub(crate) const TAB: u8 = b'\t';
 pub(crate) const DOUBLEQUOTE: u8 = b'"';
 pub(crate) const COMMA: u8 = b',';
 pub(crate) const EQUALSIGN: u8 = b'=';
 
+// Public as it is part of the sealed reader trait, the module itself is private
+/// Length of the current line, checked against an optional maximum as the
+/// line is read
+#[derive(Debug, Default)]
+pub struct LineLength {
+    max: Option<usize>,
+
+    /// Number of characters read in the current line, without line endings
+    len: usize,
+
+    /// Whether the current line is longer than the maximum
+    exceeded: bool,
+}
+
+impl LineLength {
+    pub(crate) fn set_max(&mut self, max: Option<usize>) {
+        self.max = max;
+    }
+
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Check if `c` can be read without the line becoming longer than the
+    /// maximum, the rest of a line which is too long is not read
+    pub(crate) fn allows(&mut self, c: u8) -> bool {
+        let allowed = match self.max {
+            Some(max) => self.len < max || c == NEWLINE || c == b'\r',
+            None => true,
+        };
+        self.exceeded |= !allowed;
+
+        allowed
+    }
+
+    /// Count `c` as read, a newline starts the next line
+    pub(crate) fn advance(&mut self, c: u8) {
+        match c {
+            NEWLINE => self.len = 0,
+            b'\r' => {}
+            _ => {
+                self.len += 1;
+                self.exceeded |= self.max.is_some_and(|max| self.len > max);
+            }
+        }
+    }
+}
+
+/// Location in the input, e.g., of an [Error](crate::Error)
 #[derive(Debug, Clone)]
 pub struct Position {
     /// Total number of columns in previous lines
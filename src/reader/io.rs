@@ -2,8 +2,13 @@ use std::io;
 
 use crate::{datatypes::Element, error::Result, Error};
 
-use super::{datatypes::Position, Reader};
+use super::{
+    datatypes::{LineLength, Position, NEWLINE},
+    Reader,
+};
 
+/// Reader over an [io::Read], reading one byte at a time, see
+/// [Deserializer::from_reader](crate::de::Deserializer::from_reader)
 pub struct IoReader<R>
 where
     R: io::Read,
@@ -21,21 +26,41 @@ where
 
     include_tags: bool,
 
+    /// Whether tabs and repeated spaces separate elements as well
+    lenient: bool,
+
+    line_length: LineLength,
+
     position: Position,
+
+    /// Number of newlines skipped so far
+    newlines: usize,
+
+    /// Error reading from the reader which is yet to be reported
+    error: Option<io::Error>,
+
+    /// Whether reading from the reader has failed, nothing is read afterwards
+    failed: bool,
 }
 
 impl<R> IoReader<R>
 where
     R: io::Read,
 {
-    pub fn new(reader: R) -> Self {
+    #[allow(clippy::unbuffered_bytes)]
+    pub(crate) fn new(reader: R) -> Self {
         let mut reader = Self {
             iter: reader.bytes(),
             tmp: None,
             prev: Element::Measurement,
             next: Element::Measurement,
             include_tags: false,
+            lenient: false,
+            line_length: LineLength::default(),
             position: Position::new(),
+            newlines: 0,
+            error: None,
+            failed: false,
         };
         let _ = reader.skip_until_valid_line();
 
@@ -59,24 +84,64 @@ where
         self.include_tags
     }
 
+    fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    fn line_length(&mut self) -> &mut LineLength {
+        &mut self.line_length
+    }
+
     fn peek_char(&mut self) -> Result<u8> {
-        if let Some(c) = self.tmp {
-            return Ok(c);
+        let c = match self.tmp {
+            Some(c) => c,
+            None if self.failed => return Err(Error::unexpected_eof()),
+            None => match self.iter.next() {
+                Some(Ok(c)) => {
+                    self.position.column += 1;
+                    self.tmp = Some(c);
+                    c
+                }
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    self.failed = true;
+                    return Err(Error::unexpected_eof());
+                }
+                None => return Err(Error::unexpected_eof()),
+            },
+        };
+
+        match self.line_length.allows(c) {
+            true => Ok(c),
+            false => Err(Error::unexpected_eof()),
         }
+    }
 
-        match self.iter.next() {
-            Some(c) => {
-                let c = c.map_err(|_| Error::unexpected_eof())?;
-                self.position.column += 1;
-                self.tmp = Some(c);
-                Ok(c)
+    fn skip_char(&mut self) {
+        if let Some(c) = self.tmp.take() {
+            self.line_length.advance(c);
+            if c == NEWLINE {
+                self.newlines += 1;
             }
-            None => Err(Error::unexpected_eof()),
         }
     }
 
-    fn skip_char(&mut self) {
-        self.tmp = None;
+    fn take_io_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn get_offset(&self) -> usize {
+        // The column is already advanced for a peeked character
+        let offset = self.position.previous_columns + self.position.column;
+        offset - usize::from(self.tmp.is_some())
+    }
+
+    fn get_line(&self) -> usize {
+        self.newlines + 1
     }
 
     fn get_prev_element(&self) -> &Element {
@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+use super::datatypes::{BACKSLASH, DOUBLEQUOTE, NEWLINE, WHITESPACE};
+
+/// Location of a single line in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LineSpan {
+    /// Line number the line starts on, starting at 1
+    pub line: usize,
+
+    /// Byte range of the line in the input, excluding surrounding whitespace
+    pub range: Range<usize>,
+
+    /// Whether the line is ended by a newline, as opposed to the end of the
+    /// input
+    pub terminated: bool,
+}
+
+/// Iterator over the lines containing data in a line protocol input
+///
+/// Empty lines and comment lines are skipped. Newlines which are escaped, or
+/// are part of a quoted field value, do not end the line
+pub(crate) struct Lines<'a> {
+    input: &'a [u8],
+
+    /// Index of the next byte to read
+    offset: usize,
+
+    /// Line number of the next byte to read
+    line: usize,
+
+    /// Number of comment lines skipped so far
+    pub comments: usize,
+
+    /// Number of empty, or whitespace only, lines skipped so far
+    pub empty: usize,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Lines {
+            input,
+            offset: 0,
+            line: 1,
+            comments: 0,
+            empty: 0,
+        }
+    }
+
+    /// Skip until the next non ascii whitespace
+    ///
+    /// Must be called at the start of a line so the lines skipped can be
+    /// counted as empty
+    fn skip_whitespace(&mut self) {
+        let mut line_start = self.offset;
+        while let Some(&c) = self.input.get(self.offset) {
+            if !c.is_ascii_whitespace() {
+                return;
+            }
+
+            self.offset += 1;
+            if c == NEWLINE {
+                self.line += 1;
+                self.empty += 1;
+                line_start = self.offset;
+            }
+        }
+
+        // Trailing whitespace which is not followed by a newline
+        if self.offset > line_start {
+            self.empty += 1;
+        }
+    }
+
+    /// Skip until the end of the current line, including the newline
+    fn skip_line(&mut self) {
+        while let Some(&c) = self.input.get(self.offset) {
+            self.offset += 1;
+
+            if c == NEWLINE {
+                self.line += 1;
+                break;
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = LineSpan;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.skip_whitespace();
+
+            match self.input.get(self.offset) {
+                Some(b'#') => {
+                    self.comments += 1;
+                    self.skip_line();
+                }
+                Some(_) => break,
+                None => return None,
+            }
+        }
+
+        let start = self.offset;
+        let line = self.line;
+
+        let input = &self.input[start..];
+        self.offset += LineEnd::default().find(input).unwrap_or(input.len());
+        self.line += self.input[start..self.offset]
+            .iter()
+            .filter(|&&c| c == NEWLINE)
+            .count();
+
+        let mut end = self.offset;
+        while end > start && self.input[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+
+        // Skip the newline ending the line so the next line starts at its beginning
+        let terminated = self.input.get(self.offset) == Some(&NEWLINE);
+        if terminated {
+            self.offset += 1;
+            self.line += 1;
+        }
+
+        Some(LineSpan {
+            line,
+            range: start..end,
+            terminated,
+        })
+    }
+}
+
+/// Search for the newline ending a line, which can be resumed when the input
+/// arrives in chunks
+///
+/// Leading whitespace is skipped and comment lines end at the first newline.
+/// Newlines which are escaped, or are part of a quoted field value, do not end
+/// the line
+#[derive(Debug, Default, Clone)]
+pub(crate) struct LineEnd {
+    /// Whether anything but whitespace has been found on the line
+    started: bool,
+
+    is_comment: bool,
+
+    /// Quotes only have a special meaning in the field set, which starts after
+    /// the first unescaped whitespace
+    in_fields: bool,
+
+    in_quote: bool,
+
+    is_escaped: bool,
+}
+
+impl LineEnd {
+    /// Index of the newline ending the line in `input`, which continues the
+    /// input searched so far
+    ///
+    /// The search starts over once the newline is found, the input following
+    /// it is searched as the start of the next line
+    pub fn find(&mut self, input: &[u8]) -> Option<usize> {
+        for (i, &c) in input.iter().enumerate() {
+            if !self.started {
+                if c == NEWLINE {
+                    return Some(i);
+                }
+
+                if c.is_ascii_whitespace() {
+                    continue;
+                }
+
+                self.started = true;
+                self.is_comment = c == b'#';
+            }
+
+            let ends_line = match self.is_comment {
+                true => c == NEWLINE,
+                false => !self.is_escaped && !self.in_quote && c == NEWLINE,
+            };
+            if ends_line {
+                *self = LineEnd::default();
+                return Some(i);
+            }
+
+            if self.is_escaped {
+                self.is_escaped = false;
+                continue;
+            }
+
+            match c {
+                BACKSLASH => self.is_escaped = true,
+                DOUBLEQUOTE if self.in_fields => self.in_quote = !self.in_quote,
+                WHITESPACE if !self.in_quote => self.in_fields = true,
+                _ => (),
+            }
+        }
+
+        None
+    }
+}
@@ -1,8 +1,13 @@
+pub(super) mod bufread;
 pub(super) mod datatypes;
 pub(super) mod io;
+pub(super) mod lines;
 pub(super) mod slice;
 pub(super) mod traits;
 
-pub(crate) use io::IoReader;
-pub(crate) use slice::SliceReader;
-pub(crate) use traits::Reader;
+pub use bufread::BufReadReader;
+pub use io::IoReader;
+pub(crate) use lines::{LineEnd, LineSpan, Lines};
+pub use slice::SliceReader;
+pub(crate) use traits::into_string;
+pub use traits::Reader;
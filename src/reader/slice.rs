@@ -1,7 +1,12 @@
 use crate::{datatypes::Element, error::Result, Error};
 
-use super::{datatypes::Position, Reader};
+use super::{
+    datatypes::{LineLength, Position, BACKSLASH, COMMA, DOUBLEQUOTE, EQUALSIGN, NEWLINE},
+    Reader,
+};
 
+/// Reader over a string or slice of bytes, see
+/// [Deserializer::from_slice](crate::de::Deserializer::from_slice)
 pub struct SliceReader<'a> {
     input: &'a [u8],
 
@@ -13,22 +18,48 @@ pub struct SliceReader<'a> {
 
     include_tags: bool,
 
+    /// Whether tabs and repeated spaces separate elements as well
+    lenient: bool,
+
+    line_length: LineLength,
+
     position: Position,
+
+    /// Number of newlines skipped so far
+    newlines: usize,
 }
 
 impl<'a> SliceReader<'a> {
-    pub fn new(s: &'a [u8]) -> Self {
+    pub(crate) fn new(s: &'a [u8]) -> Self {
         let mut reader = Self {
             input: s,
             prev: Element::Measurement,
             next: Element::Measurement,
             include_tags: false,
+            lenient: false,
+            line_length: LineLength::default(),
             position: Position::new(),
+            newlines: 0,
         };
         let _ = reader.skip_until_valid_line();
 
         reader
     }
+
+    /// Create a reader starting at byte `offset` of `s`, at the start of its
+    /// `line`th line
+    ///
+    /// Positions are reported relative to the whole of `s`
+    pub(crate) fn at_line(s: &'a [u8], offset: usize, line: usize) -> Self {
+        let mut reader = Self::new(&[]);
+        reader.input = s;
+        reader.position.previous_columns = offset;
+        reader.position.line = line;
+        reader.newlines = line - 1;
+        let _ = reader.skip_until_valid_line();
+
+        reader
+    }
 }
 
 impl<'de> Reader<'de> for SliceReader<'de> {
@@ -44,21 +75,51 @@ impl<'de> Reader<'de> for SliceReader<'de> {
         self.include_tags
     }
 
+    fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    fn line_length(&mut self) -> &mut LineLength {
+        &mut self.line_length
+    }
+
     fn peek_char(&mut self) -> Result<u8> {
-        let idx = self.position.column + self.position.previous_columns;
+        let idx = self.get_offset();
         match idx < self.input.len() {
             true => {
                 let c = self.input[idx];
-                Ok(c)
+                match self.line_length.allows(c) {
+                    true => Ok(c),
+                    false => Err(Error::unexpected_eof()),
+                }
             }
             false => Err(Error::unexpected_eof()),
         }
     }
 
     fn skip_char(&mut self) {
+        if let Some(&c) = self.input.get(self.get_offset()) {
+            self.line_length.advance(c);
+            if c == NEWLINE {
+                self.newlines += 1;
+            }
+        }
+
         self.position.column += 1;
     }
 
+    fn get_offset(&self) -> usize {
+        self.position.previous_columns + self.position.column
+    }
+
+    fn get_line(&self) -> usize {
+        self.newlines + 1
+    }
+
     fn get_prev_element(&self) -> &Element {
         &self.prev
     }
@@ -75,6 +136,51 @@ impl<'de> Reader<'de> for SliceReader<'de> {
         self.next = next;
     }
 
+    fn get_next_value_borrowed(&mut self) -> Result<Option<&'de [u8]>> {
+        let input = self.input.get(self.get_offset()..).unwrap_or_default();
+        let len = match self.get_next_element() {
+            Element::Measurement => {
+                unescaped_len(input, false, |c| c == COMMA || self.is_separator(c))
+            }
+            Element::Tags => unescaped_len(input, false, |c| {
+                c == COMMA || c == EQUALSIGN || self.is_separator(c)
+            }),
+            Element::Fields => unescaped_len(input, true, |c| {
+                c == COMMA || c == EQUALSIGN || c.is_ascii_whitespace()
+            }),
+            Element::Timestamp => None,
+        };
+
+        let Some(len) = len else {
+            return Ok(None);
+        };
+
+        let mut value = &input[..len];
+        (0..len).for_each(|_| self.skip_char());
+
+        if self.get_next_element().is_fields()
+            && value.len() >= 2
+            && value.starts_with(b"\"")
+            && value.ends_with(b"\"")
+        {
+            value = &value[1..value.len() - 1];
+        }
+
+        self.determine_next_element()?;
+        Ok(Some(value))
+    }
+
+    fn get_raw_borrowed(&mut self, line: bool) -> Result<Option<&'de [u8]>> {
+        let start = self.get_offset();
+        self.scan_raw(line, None);
+        let end = self.get_offset();
+
+        if !line {
+            self.determine_next_element()?;
+        }
+        Ok(Some(&self.input[start..end]))
+    }
+
     fn has_next_line(&mut self) -> bool {
         if self.skip_until_valid_line().is_err() {
             return false;
@@ -91,3 +197,27 @@ impl<'de> Reader<'de> for SliceReader<'de> {
         self.include_tags = false;
     }
 }
+
+/// Length of the value at the start of `input`, None if it contains any escape
+/// characters
+///
+/// The value ends at the first character matching `is_end`, outside of double
+/// quotes if the value can be `quoted`
+fn unescaped_len(input: &[u8], quoted: bool, is_end: impl Fn(u8) -> bool) -> Option<usize> {
+    let mut in_quote = false;
+    for (i, &c) in input.iter().enumerate() {
+        if c == BACKSLASH {
+            return None;
+        }
+
+        if !in_quote && is_end(c) {
+            return Some(i);
+        }
+
+        if quoted && c == DOUBLEQUOTE {
+            in_quote = !in_quote;
+        }
+    }
+
+    Some(input.len())
+}
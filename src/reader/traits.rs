@@ -1,8 +1,12 @@
 use crate::{datatypes::Element, error::Result, Error};
 
-use super::datatypes::{Position, BACKSLASH, COMMA, DOUBLEQUOTE, EQUALSIGN, NEWLINE, WHITESPACE};
+use super::datatypes::{
+    LineLength, Position, BACKSLASH, COMMA, DOUBLEQUOTE, EQUALSIGN, NEWLINE, TAB, WHITESPACE,
+};
 
-pub(crate) trait Reader<'de> {
+/// Reads the elements of each line from the input, see
+/// [de::Read](crate::de::Read)
+pub trait Reader<'de> {
     /// Skip the current line
     #[doc(hidden)]
     fn skip_line(&mut self) {
@@ -51,12 +55,12 @@ pub(crate) trait Reader<'de> {
 
     /// Parse measurement from input
     #[doc(hidden)]
-    fn parse_measurement(&mut self) -> String {
+    fn parse_measurement(&mut self) -> Vec<u8> {
         let mut result = Vec::new();
 
         let mut is_escaped = false;
         while let Ok(c) = self.peek_char() {
-            if !is_escaped && (c == COMMA || c == WHITESPACE) {
+            if !is_escaped && (c == COMMA || self.is_separator(c)) {
                 break;
             }
 
@@ -71,18 +75,17 @@ pub(crate) trait Reader<'de> {
             result.push(c);
         }
 
-        // Bytes should never be invalid
-        String::from_utf8(result).unwrap()
+        result
     }
 
     /// Parse tag key from input
     #[doc(hidden)]
-    fn parse_tag_key(&mut self) -> String {
+    fn parse_tag_key(&mut self) -> Vec<u8> {
         let mut result = Vec::new();
 
         let mut is_escaped = false;
         while let Ok(c) = self.peek_char() {
-            if !is_escaped && (c == COMMA || c == EQUALSIGN || c == WHITESPACE) {
+            if !is_escaped && (c == COMMA || c == EQUALSIGN || self.is_separator(c)) {
                 break;
             }
 
@@ -97,15 +100,14 @@ pub(crate) trait Reader<'de> {
             result.push(c);
         }
 
-        // Bytes should never be invalid
-        String::from_utf8(result).unwrap()
+        result
     }
 
     /// Parse tag value from input
     ///
     /// Calls [Self::parse_tag_key] as these two have the same escape characters
     #[doc(hidden)]
-    fn parse_tag_value(&mut self) -> String {
+    fn parse_tag_value(&mut self) -> Vec<u8> {
         self.parse_tag_key()
     }
 
@@ -113,13 +115,13 @@ pub(crate) trait Reader<'de> {
     ///
     /// Calls [Self::parse_tag_key] as these two have the same escape characters
     #[doc(hidden)]
-    fn parse_field_key(&mut self) -> String {
+    fn parse_field_key(&mut self) -> Vec<u8> {
         self.parse_tag_key()
     }
 
     /// Parse field value from input
     #[doc(hidden)]
-    fn parse_field_value(&mut self) -> String {
+    fn parse_field_value(&mut self) -> Vec<u8> {
         let mut result = Vec::new();
 
         let mut is_escaped = false;
@@ -146,30 +148,101 @@ pub(crate) trait Reader<'de> {
             result.push(c);
         }
 
-        if result.starts_with(b"\"") && result.ends_with(b"\"") {
+        if result.len() >= 2 && result.starts_with(b"\"") && result.ends_with(b"\"") {
             result = result[1..result.len() - 1].to_vec();
         }
 
-        // Bytes should never be invalid
-        String::from_utf8(result).unwrap()
+        result
+    }
+
+    /// Skip the next field value without parsing it
+    #[doc(hidden)]
+    fn skip_field_value(&mut self) -> Result<()> {
+        let mut is_escaped = false;
+        let mut in_quote = false;
+        while let Ok(c) = self.peek_char() {
+            if (!is_escaped && !in_quote)
+                && (c == COMMA || c == EQUALSIGN || c.is_ascii_whitespace())
+            {
+                break;
+            }
+
+            self.skip_char();
+            if c == BACKSLASH && !is_escaped {
+                is_escaped = true;
+                continue;
+            }
+
+            if !is_escaped && c == DOUBLEQUOTE {
+                in_quote = !in_quote;
+            };
+
+            is_escaped = false;
+        }
+
+        self.determine_next_element()
     }
 
     /// Parse timestamp from input
     #[doc(hidden)]
-    fn parse_timestamp(&mut self) -> String {
+    fn parse_timestamp(&mut self) -> Vec<u8> {
         let mut result = Vec::new();
 
         while let Ok(c) = self.peek_char() {
-            self.skip_char();
             if c.is_ascii_whitespace() {
                 break;
             }
 
+            self.skip_char();
             result.push(c);
         }
 
-        // Bytes should never be invalid
-        String::from_utf8(result).unwrap()
+        result
+    }
+
+    /// Parse timestamp from input directly into an i64
+    ///
+    /// Only digits and a single leading `-` are accepted
+    #[doc(hidden)]
+    fn parse_timestamp_i64(&mut self) -> Result<i64> {
+        let position = self.get_position();
+
+        let negative = matches!(self.peek_char(), Ok(b'-'));
+        if negative {
+            self.skip_char();
+        }
+
+        let mut timestamp: i64 = 0;
+        let mut digits = 0;
+        while let Ok(c) = self.peek_char() {
+            if c.is_ascii_whitespace() {
+                break;
+            }
+
+            self.skip_char();
+            if !c.is_ascii_digit() {
+                return Err(Error::unexpected_char(c as char, self.get_position()));
+            }
+
+            // Negative timestamps are accumulated as negative numbers so i64::MIN can be
+            // parsed
+            let digit = (c - b'0') as i64;
+            timestamp = timestamp
+                .checked_mul(10)
+                .and_then(|t| match negative {
+                    true => t.checked_sub(digit),
+                    false => t.checked_add(digit),
+                })
+                .ok_or_else(|| Error::timestamp_out_of_range(position.clone()))?;
+
+            digits += 1;
+        }
+
+        if digits == 0 {
+            return Err(Error::unexpected_char('-', self.get_position()));
+        }
+
+        Ok(timestamp)
     }
 
     /// Get the current position of the reader
@@ -183,6 +256,37 @@ pub(crate) trait Reader<'de> {
     #[doc(hidden)]
     fn tags_included(&self) -> bool;
 
+    /// Tell the reader to accept tabs and repeated spaces between elements
+    #[doc(hidden)]
+    fn set_lenient(&mut self, lenient: bool);
+
+    #[doc(hidden)]
+    fn is_lenient(&self) -> bool;
+
+    /// Length of the current line and its maximum
+    #[doc(hidden)]
+    fn line_length(&mut self) -> &mut LineLength;
+
+    /// Check if `c` separates the elements of a line, a tab does as well when
+    /// lenient
+    #[doc(hidden)]
+    fn is_separator(&self, c: u8) -> bool {
+        c == WHITESPACE || (self.is_lenient() && c == TAB)
+    }
+
+    /// Skip the separator between two elements, along with any spaces or tabs
+    /// following it when lenient
+    #[doc(hidden)]
+    fn skip_separator(&mut self) {
+        self.skip_char();
+
+        if self.is_lenient() {
+            while let Ok(WHITESPACE | TAB) = self.peek_char() {
+                self.skip_char();
+            }
+        }
+    }
+
     /// Look at the next character in the current input without consuming
     #[doc(hidden)]
     fn peek_char(&mut self) -> Result<u8>;
@@ -191,6 +295,23 @@ pub(crate) trait Reader<'de> {
     #[doc(hidden)]
     fn skip_char(&mut self);
 
+    /// Take the error reading from the input, if reading has failed
+    ///
+    /// Reading stops at the first error, which the reader reports as the end
+    /// of the input until the error is taken
+    #[doc(hidden)]
+    fn take_io_error(&mut self) -> Option<std::io::Error> {
+        None
+    }
+
+    /// Get the byte offset of the next character in the input
+    #[doc(hidden)]
+    fn get_offset(&self) -> usize;
+
+    /// Get the line number, starting at 1, of the next character in the input
+    #[doc(hidden)]
+    fn get_line(&self) -> usize;
+
     /// Discard the next element
     ///
     /// Used if tags is not specified in the result type `T`
@@ -207,6 +328,7 @@ pub(crate) trait Reader<'de> {
             self.skip_char();
             if c == BACKSLASH && !is_escaped {
                 is_escaped = true;
+                continue;
             }
 
             if !is_escaped && c == DOUBLEQUOTE {
@@ -214,7 +336,6 @@ pub(crate) trait Reader<'de> {
             }
 
             is_escaped = false;
-            self.skip_char();
         }
     }
 
@@ -255,13 +376,15 @@ pub(crate) trait Reader<'de> {
                     if !self.tags_included() {
                         self.discard_next_element();
 
+                        // Skip the whitespace separating the tag set and field set
+                        self.skip_separator();
                         Element::Fields
                     } else {
                         Element::Tags
                     }
                 }
-                WHITESPACE => {
-                    self.skip_char();
+                c if self.is_separator(c) => {
+                    self.skip_separator();
                     Element::Fields
                 }
                 c => return Err(Error::unexpected_char(c as char, self.get_position())),
@@ -282,7 +405,7 @@ pub(crate) trait Reader<'de> {
                     self.skip_char();
                     Element::Tags
                 }
-                WHITESPACE => Element::Fields,
+                c if self.is_separator(c) => Element::Fields,
                 c => return Err(Error::unexpected_char(c as char, self.get_position())),
             },
 
@@ -346,19 +469,21 @@ pub(crate) trait Reader<'de> {
 
             // Tag set is done whenever a whitespace is reached
             Element::Tags => match self.peek_char()? {
-                WHITESPACE => {
-                    self.skip_char();
+                c if self.is_separator(c) => {
+                    self.skip_separator();
                     false
                 }
                 _ => true,
             },
 
             // Field set is done whenever a whitespace, newline is reached, or if there are no more
-            // characters remaining
+            // characters remaining. A newline is left for the next line to skip, as consuming it
+            // would make the next check read into the next line
             Element::Fields => match self.peek_char() {
+                Ok(NEWLINE) => false,
                 Ok(c) => {
                     if c.is_ascii_whitespace() {
-                        self.skip_char();
+                        self.skip_separator();
                         false
                     } else {
                         true
@@ -368,11 +493,12 @@ pub(crate) trait Reader<'de> {
             },
 
             // Timestamp is done whenever a whitespace, newline is reached, or if there are no more
-            // characters remaining. This might be an unneccessary check
+            // characters remaining. As with the field set a newline is left for the next line
             Element::Timestamp => match self.peek_char() {
+                Ok(NEWLINE) => false,
                 Ok(c) => {
                     if c.is_ascii_whitespace() {
-                        self.skip_char();
+                        self.skip_separator();
                         false
                     } else {
                         true
@@ -388,6 +514,7 @@ pub(crate) trait Reader<'de> {
     /// Fetch the next key in the current element to deserialize
     #[doc(hidden)]
     fn get_next_key(&mut self) -> Result<String> {
+        let position = self.get_position();
         let key = match self.get_next_element() {
             // The measurement key is not parsed and will always be "measurement"
             Element::Measurement => "measurement".to_string(),
@@ -400,7 +527,7 @@ pub(crate) trait Reader<'de> {
                 } else {
                     let key = self.parse_tag_key();
                     self.determine_next_element()?;
-                    key
+                    into_string(key, position)?
                 };
 
                 self.set_prev_element(Element::Tags);
@@ -416,7 +543,7 @@ pub(crate) trait Reader<'de> {
                 } else {
                     let key = self.parse_field_key();
                     self.determine_next_element()?;
-                    key
+                    into_string(key, position)?
                 };
 
                 self.set_prev_element(Element::Fields);
@@ -434,6 +561,16 @@ pub(crate) trait Reader<'de> {
     /// Fetch the next element in the current element to deserialize
     #[doc(hidden)]
     fn get_next_value(&mut self) -> Result<String> {
+        let position = self.get_position();
+        let value = self.get_next_value_bytes()?;
+
+        into_string(value, position)
+    }
+
+    /// Fetch the next element in the current element to deserialize as the raw
+    /// unescaped bytes, which are not required to be valid utf8
+    #[doc(hidden)]
+    fn get_next_value_bytes(&mut self) -> Result<Vec<u8>> {
         let value = match self.get_next_element() {
             Element::Measurement => self.parse_measurement(),
             Element::Tags => self.parse_tag_value(),
@@ -445,10 +582,105 @@ pub(crate) trait Reader<'de> {
         Ok(value)
     }
 
+    /// Fetch the next value borrowed from the input
+    ///
+    /// Returns None, without consuming anything, if the reader does not
+    /// borrow its input or the value has to be unescaped
+    #[doc(hidden)]
+    fn get_next_value_borrowed(&mut self) -> Result<Option<&'de [u8]>> {
+        Ok(None)
+    }
+
+    /// Skip the rest of the line, or the next value if not `line`, exactly as
+    /// written in the input, pushing each character onto `raw` if given
+    ///
+    /// The newline ending the line is left for the next line to skip
+    #[doc(hidden)]
+    fn scan_raw(&mut self, line: bool, mut raw: Option<&mut Vec<u8>>) {
+        let mut is_escaped = false;
+        let mut in_quote = false;
+
+        // Quotes only have a special meaning in the field set, which a line enters at
+        // its first unescaped separator
+        let mut in_fields = self.get_next_element().is_fields();
+        while let Ok(c) = self.peek_char() {
+            let is_end = match self.get_next_element() {
+                _ if line => !in_quote && c == NEWLINE,
+                Element::Measurement => c == COMMA || self.is_separator(c),
+                Element::Tags => c == COMMA || c == EQUALSIGN || self.is_separator(c),
+                Element::Fields => {
+                    !in_quote && (c == COMMA || c == EQUALSIGN || c.is_ascii_whitespace())
+                }
+                Element::Timestamp => c.is_ascii_whitespace(),
+            };
+            if !is_escaped && is_end {
+                break;
+            }
+
+            self.skip_char();
+            if let Some(raw) = &mut raw {
+                raw.push(c);
+            }
+
+            if c == BACKSLASH && !is_escaped {
+                is_escaped = true;
+                continue;
+            }
+
+            if !is_escaped && c == DOUBLEQUOTE && (in_fields || !line) {
+                in_quote = !in_quote;
+            }
+
+            if !is_escaped && !in_quote && self.is_separator(c) {
+                in_fields = true;
+            }
+
+            is_escaped = false;
+        }
+    }
+
+    /// Fetch the rest of the line, or the next value if not `line`, exactly as
+    /// written in the input
+    #[doc(hidden)]
+    fn get_raw(&mut self, line: bool) -> Result<Vec<u8>> {
+        let mut raw = Vec::new();
+        self.scan_raw(line, Some(&mut raw));
+
+        if !line {
+            self.determine_next_element()?;
+        }
+        Ok(raw)
+    }
+
+    /// Fetch the rest of the line, or the next value if not `line`, borrowed
+    /// from the input exactly as written
+    ///
+    /// Returns None, without consuming anything, if the reader does not
+    /// borrow its input
+    #[doc(hidden)]
+    fn get_raw_borrowed(&mut self, _line: bool) -> Result<Option<&'de [u8]>> {
+        Ok(None)
+    }
+
+    /// Fetch the timestamp of the current line
+    #[doc(hidden)]
+    fn get_next_timestamp(&mut self) -> Result<i64> {
+        let timestamp = self.parse_timestamp_i64()?;
+
+        self.determine_next_element()?;
+        Ok(timestamp)
+    }
+
     /// Discard the next value
     #[doc(hidden)]
     fn discard_next_value(&mut self) -> Result<()> {
-        self.get_next_value()?;
+        self.get_next_value_bytes()?;
         Ok(())
     }
 }
+
+/// Convert the bytes of an element into a string, only strings with valid utf8
+/// are accepted
+pub(crate) fn into_string(bytes: Vec<u8>, position: Position) -> Result<String> {
+    String::from_utf8(bytes).map_err(|e| Error::invalid_utf8(e.utf8_error(), position))
+}
@@ -0,0 +1,315 @@
+//! Routing of lines to buckets based on their measurement and tags
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use serde::{
+    de::{self, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    de::from_slice,
+    error::{Error, Result},
+    reader,
+    value::Map,
+    Point,
+};
+
+/// Destination of a line, an organization and a bucket within it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Target {
+    /// Name of the organization
+    pub org: String,
+
+    /// Name of the bucket within the organization
+    pub bucket: String,
+}
+
+impl Target {
+    /// Create a new target for `bucket` in `org`
+    pub fn new(org: impl Into<String>, bucket: impl Into<String>) -> Self {
+        Target {
+            org: org.into(),
+            bucket: bucket.into(),
+        }
+    }
+}
+
+/// A single routing rule, matching lines by their measurement and tag values
+///
+/// A rule without any conditions matches every line
+///
+/// # Example
+///
+/// ```rust
+/// let rule = Rule::new(Target::new("acme", "metrics"))
+///     .measurement("cpu")
+///     .tag("tenant", "acme");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    measurement: Option<String>,
+
+    tags: Vec<(String, String)>,
+
+    target: Target,
+}
+
+impl Rule {
+    /// Create a new rule routing the lines it matches to `target`
+    pub fn new(target: Target) -> Self {
+        Rule {
+            measurement: None,
+            tags: Vec::new(),
+            target,
+        }
+    }
+
+    /// Only match lines with the given measurement
+    pub fn measurement(mut self, measurement: impl Into<String>) -> Self {
+        self.measurement = Some(measurement.into());
+        self
+    }
+
+    /// Only match lines where the tag `key` has the given value, can be
+    /// repeated to require multiple tags
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// The target lines matching this rule are routed to
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Checks if a line with the given measurement and tags matches the rule
+    fn matches(&self, measurement: &str, tags: &Map<String>) -> bool {
+        self.measurement.as_deref().is_none_or(|m| m == measurement)
+            && self
+                .tags
+                .iter()
+                .all(|(key, value)| tags.get(key) == Some(value))
+    }
+}
+
+/// An ordered set of routing rules
+///
+/// Each line is routed to the target of the first rule it matches, or the
+/// fallback target when it matches none of them
+///
+/// # Example
+///
+/// ```rust
+/// let rules = Rules::new()
+///     .rule(Rule::new(Target::new("acme", "metrics")).tag("tenant", "acme"))
+///     .rule(Rule::new(Target::new("globex", "metrics")).tag("tenant", "globex"))
+///     .fallback(Target::new("ops", "unrouted"));
+///
+/// let input = "cpu,tenant=acme usage=0.5\ncpu,tenant=initech usage=0.7";
+/// let encoded = serde_influxlp::route::route_and_encode(&rules, input).unwrap();
+/// // encoded[&Target::new("acme", "metrics")] == "cpu,tenant=acme usage=0.5"
+/// // encoded[&Target::new("ops", "unrouted")] == "cpu,tenant=initech usage=0.7"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rules {
+    rules: Vec<Rule>,
+
+    fallback: Option<Target>,
+}
+
+impl Rules {
+    /// Create an empty set of rules without a fallback target
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, rules are evaluated in the order they are added
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Set the target of lines which do not match any rule
+    pub fn fallback(mut self, target: Target) -> Self {
+        self.fallback = Some(target);
+        self
+    }
+
+    /// The target of a line with the given measurement and tags, if any
+    pub fn target(&self, measurement: &str, tags: &Map<String>) -> Option<&Target> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(measurement, tags))
+            .map(Rule::target)
+            .or(self.fallback.as_ref())
+    }
+
+    /// The target of `point`, if any
+    pub fn target_of(&self, point: &Point) -> Option<&Target> {
+        self.target(&point.measurement, &point.tags)
+    }
+}
+
+/// The parts of a line needed to route it, the fields are skipped
+struct RouteKey {
+    measurement: String,
+
+    tags: Map<String>,
+}
+
+impl<'de> Deserialize<'de> for RouteKey {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RouteKeyVisitor;
+
+        impl<'de> Visitor<'de> for RouteKeyVisitor {
+            type Value = RouteKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a line")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let (mut measurement, mut tags) = (None, Map::new());
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "measurement" => measurement = Some(map.next_value()?),
+                        "tags" => tags = map.next_value()?,
+                        "fields" => {
+                            map.next_value::<BTreeMap<String, IgnoredAny>>()?;
+                        }
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(RouteKey {
+                    measurement: measurement
+                        .ok_or_else(|| de::Error::missing_field("measurement"))?,
+                    tags,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["measurement", "tags", "fields", "timestamp"];
+        deserializer.deserialize_struct("RouteKey", FIELDS, RouteKeyVisitor)
+    }
+}
+
+/// Route each line of `input`, returning the span of each line in the input
+/// together with its target, or None when no rule matches the line
+fn route_lines<'a>(
+    rules: &'a Rules,
+    input: &[u8],
+) -> Result<Vec<(reader::LineSpan, Option<&'a Target>)>> {
+    reader::Lines::new(input)
+        .map(|span| {
+            let key: RouteKey =
+                from_slice(&input[span.range.clone()]).map_err(|e| e.offset_line(span.line))?;
+            Ok((span, rules.target(&key.measurement, &key.tags)))
+        })
+        .collect()
+}
+
+/// Determine the target of each line of `input`
+///
+/// The targets are returned in the order of the lines, None for lines which
+/// do not match any rule when there is no fallback target. Only the
+/// measurement and tags of each line are deserialized
+///
+/// # Example
+///
+/// ```rust
+/// let targets = serde_influxlp::route::route(&rules, input).unwrap();
+/// for target in targets {
+///     println!("{target:?}");
+/// }
+/// ```
+pub fn route<'a>(rules: &'a Rules, input: &str) -> Result<Vec<Option<&'a Target>>> {
+    let lines = route_lines(rules, input.as_bytes())?;
+    Ok(lines.into_iter().map(|(_, target)| target).collect())
+}
+
+/// Partition the lines of `input` by their target
+///
+/// The lines are copied as is, comments and empty lines are dropped, and the
+/// lines of each target are separated by newlines. Fails with
+/// [ErrorCode::NoRoute](crate::ErrorCode::NoRoute) if a line does not match
+/// any rule and there is no fallback target, see [Rules::fallback]
+pub fn route_and_encode(rules: &Rules, input: &str) -> Result<HashMap<Target, String>> {
+    let mut encoded: HashMap<Target, String> = HashMap::new();
+    for (span, target) in route_lines(rules, input.as_bytes())? {
+        let Some(target) = target else {
+            return Err(Error::no_route(span.line));
+        };
+
+        let lines = encoded.entry(target.clone()).or_default();
+        if !lines.is_empty() {
+            lines.push('\n');
+        }
+        lines.push_str(&input[span.range]);
+    }
+
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_route_and_encode() {
+        let acme = Target::new("acme", "metrics");
+        let globex = Target::new("globex", "metrics");
+        let rules = Rules::new()
+            .rule(
+                Rule::new(acme.clone())
+                    .measurement("cpu")
+                    .tag("tenant", "acme"),
+            )
+            .rule(Rule::new(globex.clone()).tag("tenant", "globex"));
+
+        let input = r#"
+        # comment
+        cpu,tenant=acme,host=a usage=0.5,text="a b" 1704067200
+        mem,tenant=globex used=1i
+        cpu,tenant=initech usage=0.7
+        cpu,tenant=acme usage=0.6
+        "#;
+
+        let targets = route(&rules, input).unwrap();
+        assert_eq!(targets, [Some(&acme), Some(&globex), None, Some(&acme)]);
+
+        let error = route_and_encode(&rules, input).unwrap_err();
+        assert!(matches!(error.code, crate::ErrorCode::NoRoute));
+        assert_eq!(error.position.line, 5);
+
+        let error = route(&rules, "cpu usage=1\ncpu,tenant=a").unwrap_err();
+        assert_eq!(error.position.line, 2);
+
+        let ops = Target::new("ops", "unrouted");
+        let rules = rules.fallback(ops.clone());
+        let encoded = route_and_encode(&rules, input).unwrap();
+        assert_eq!(encoded.len(), 3);
+        assert_eq!(
+            encoded[&acme],
+            "cpu,tenant=acme,host=a usage=0.5,text=\"a b\" 1704067200\ncpu,tenant=acme usage=0.6"
+        );
+        assert_eq!(encoded[&globex], "mem,tenant=globex used=1i");
+        assert_eq!(encoded[&ops], "cpu,tenant=initech usage=0.7");
+
+        let mut point = Point::new("cpu");
+        point.tags.insert("tenant".to_string(), "acme".to_string());
+        assert_eq!(rules.target_of(&point), Some(&acme));
+    }
+}
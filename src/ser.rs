@@ -1,37 +1,506 @@
-use std::{io, str::FromStr};
+use std::{collections::BTreeSet, fmt, io, str::FromStr, sync::Arc};
 
 use serde::{
-    ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple},
+    ser::{
+        self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple,
+    },
     Serialize,
 };
 
-use crate::{builder::Builder, datatypes::Element, Value};
+pub use crate::builder::LineView;
+use crate::{
+    builder::Builder,
+    datatypes::{Duplicates, Element, Precision},
+    raw,
+    timestamp::{self, Timestamp},
+    Number, Value,
+};
 
 use super::error::{Error, Result};
 
-pub struct Serializer {
+/// Options used to customize the serialization of line protocol
+///
+/// # Example
+///
+/// ```rust
+/// let options = Options::new()
+///     .precision(Precision::Milliseconds)
+///     .fill_timestamp(true);
+///
+/// let line = serde_influxlp::to_string_with_options(&metric, &options).unwrap();
+/// // Output: metric1 field1=123i 1577836800000
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Precision of the timestamps being written
+    pub(crate) precision: Precision,
+
+    /// Whether missing timestamps are filled with the current system time
+    pub(crate) fill_timestamp: bool,
+
+    /// Whether tags are sorted by their key
+    pub(crate) sort_tags: bool,
+
+    /// Tags added to every line
+    pub(crate) static_tags: Vec<(String, String)>,
+
+    /// How keys occurring more than once in a tag or field set are handled
+    pub(crate) duplicates: Duplicates,
+
+    /// Keys of the tags left out of every line
+    pub(crate) drop_tags: BTreeSet<String>,
+
+    /// Keys of the fields left out of every line
+    pub(crate) drop_fields: BTreeSet<String>,
+
+    /// Prefix added to every measurement
+    pub(crate) measurement_prefix: Option<String>,
+
+    /// Suffix added to every measurement
+    pub(crate) measurement_suffix: Option<String>,
+
+    /// Whether the name of a struct is used as the measurement of its lines
+    pub(crate) measurement_from_type: bool,
+
+    /// Whether unsigned integer field values are written with the `u` suffix
+    pub(crate) unsigned_suffix: bool,
+
+    /// Whether float field values are always written with a decimal point
+    pub(crate) decimal_point: bool,
+
+    /// Number of decimal places float field values are rounded to
+    pub(crate) float_decimals: Option<usize>,
+
+    /// How NaN and infinite float field values are handled
+    pub(crate) non_finite: NonFinite,
+
+    /// Maximum length of a line in bytes, excluding the newline
+    pub(crate) max_line_length: Option<usize>,
+
+    /// Whether the output should match Telegraf's line protocol serializer
+    pub(crate) telegraf: bool,
+
+    /// Hook checking each line before it is written
+    pub(crate) validate: Option<Validator>,
+}
+
+/// How NaN and infinite float field values are handled, see
+/// [Options::non_finite]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NonFinite {
+    /// Fail the serialization with
+    /// [ErrorCode::InfiniteFloat](crate::ErrorCode::InfiniteFloat)
+    #[default]
+    Error,
+
+    /// Skip the field, the line is still rejected if it has no fields left
+    Skip,
+
+    /// Write the given value instead, which has to be finite itself
+    Replace(f64),
+}
+
+/// A validation hook, see [Options::validate]
+#[derive(Clone)]
+pub(crate) struct Validator(Arc<dyn Fn(LineView) -> Result<()> + Send + Sync>);
+
+impl Validator {
+    pub(crate) fn call(&self, line: LineView) -> Result<()> {
+        (self.0)(line)
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
+
+impl Options {
+    /// Create a new set of options with the default values
+    pub const fn new() -> Self {
+        Self {
+            precision: Precision::Nanoseconds,
+            fill_timestamp: false,
+            sort_tags: false,
+            static_tags: Vec::new(),
+            duplicates: Duplicates::Error,
+            drop_tags: BTreeSet::new(),
+            drop_fields: BTreeSet::new(),
+            measurement_prefix: None,
+            measurement_suffix: None,
+            measurement_from_type: false,
+            unsigned_suffix: false,
+            decimal_point: false,
+            float_decimals: None,
+            non_finite: NonFinite::Error,
+            max_line_length: None,
+            telegraf: false,
+            validate: None,
+        }
+    }
+
+    /// Create a set of options producing the same output as Telegraf's line
+    /// protocol serializer
+    ///
+    /// This means that
+    /// - tags are sorted by their key and tags with an empty key or value are
+    ///   skipped
+    /// - tabs, newlines, form feeds, and carriage returns in measurements,
+    ///   keys, and tag values are escaped as `\t`, `\n`, `\f`, and `\r`
+    /// - fields with a NaN or infinite float value are skipped
+    /// - unsigned integers larger than i64::MAX are clamped to i64::MAX
+    ///
+    /// The remaining options can still be changed on the returned options
+    pub const fn telegraf() -> Self {
+        let mut options = Self::new();
+        options.sort_tags = true;
+        options.non_finite = NonFinite::Skip;
+        options.telegraf = true;
+        options
+    }
+
+    /// Set the precision of the timestamps being written
+    ///
+    /// Defaults to [Precision::Nanoseconds]
+    pub const fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sort the tags of each line by their key
+    ///
+    /// InfluxDB recommends sorted tags for the best write performance. Tags
+    /// from a `HashMap` are otherwise written in a random order
+    ///
+    /// Defaults to false
+    pub const fn sort_tags(mut self, sort: bool) -> Self {
+        self.sort_tags = sort;
+        self
+    }
+
+    /// Add the tag `key` with `value` to every line, can be repeated to add
+    /// multiple tags
+    ///
+    /// Lines which already have a tag with the same key keep their own value.
+    /// Useful for tags describing the source of the data, such as the host or
+    /// environment, which would otherwise have to be part of every struct
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new()
+    ///     .static_tag("host", "server01")
+    ///     .static_tag("env", "prod");
+    ///
+    /// let line = serde_influxlp::to_string_with_options(&metric, &options).unwrap();
+    /// // Output: metric1,host=server01,env=prod field1=123i
+    /// ```
+    pub fn static_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.static_tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set how keys occurring more than once in the same tag or field set,
+    /// e.g., from a list of pairs or multiple flattened members, are handled
+    ///
+    /// Defaults to [Duplicates::Error]
+    pub const fn duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Leave the tag `key` out of every line, can be repeated to drop
+    /// multiple tags
+    ///
+    /// Useful for removing high cardinality or sensitive tags without
+    /// changing the data being serialized
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new().drop_tag("session_id").drop_field("password");
+    ///
+    /// let line = serde_influxlp::to_string_with_options(&metric, &options).unwrap();
+    /// ```
+    pub fn drop_tag(mut self, key: impl Into<String>) -> Self {
+        self.drop_tags.insert(key.into());
+        self
+    }
+
+    /// Leave the field `key` out of every line, can be repeated to drop
+    /// multiple fields
+    ///
+    /// Lines without any fields left are rejected as usual, see
+    /// [Options::drop_tag]
+    pub fn drop_field(mut self, key: impl Into<String>) -> Self {
+        self.drop_fields.insert(key.into());
+        self
+    }
+
+    /// Add `prefix` to the measurement of every line, e.g., to namespace the
+    /// measurements of an environment
+    ///
+    /// See [de::Options::strip_measurement_prefix](crate::de::Options::strip_measurement_prefix)
+    /// for removing it again when deserializing
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new().measurement_prefix("prod_");
+    ///
+    /// let line = serde_influxlp::to_string_with_options(&metric, &options).unwrap();
+    /// // Output: prod_metric1 field1=123i
+    /// ```
+    pub fn measurement_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.measurement_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Add `suffix` to the measurement of every line
+    ///
+    /// See [Options::measurement_prefix]
+    pub fn measurement_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.measurement_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Use the name of the struct being serialized as the measurement of its
+    /// lines, unless the struct has a `measurement` member of its own
+    ///
+    /// The name can be changed with `#[serde(rename = "...")]` on the struct.
+    /// Struct variants use the name of the variant instead
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// #[serde(rename = "cpu")]
+    /// pub struct Cpu {
+    ///     pub fields: Fields,
+    /// }
+    ///
+    /// let options = Options::new().measurement_from_type(true);
+    /// let line = serde_influxlp::to_string_with_options(&cpu, &options).unwrap();
+    /// // Output: cpu usage=0.5
+    /// ```
+    ///
+    /// Defaults to false
+    pub const fn measurement_from_type(mut self, from_type: bool) -> Self {
+        self.measurement_from_type = from_type;
+        self
+    }
+
+    /// Write unsigned integer field values with the `u` suffix instead of the
+    /// `i` suffix used for signed integers
+    ///
+    /// Requires InfluxDB v2 or later. Without it values larger than i64::MAX
+    /// are rejected by InfluxDB
+    ///
+    /// Defaults to false
+    pub const fn unsigned_suffix(mut self, enable: bool) -> Self {
+        self.unsigned_suffix = enable;
+        self
+    }
+
+    /// Always write float field values with a decimal point, e.g., `1.0`
+    /// instead of `1`
+    ///
+    /// InfluxDB infers the type of a field from the first value written to
+    /// it, so a float field could otherwise be created as an integer field
+    /// when its first value happens to be whole
+    ///
+    /// Defaults to false
+    pub const fn decimal_point(mut self, enable: bool) -> Self {
+        self.decimal_point = enable;
+        self
+    }
+
+    /// Round float field values to `decimals` decimal places, writing
+    /// exactly that many digits after the decimal point
+    ///
+    /// Defaults to None, writing the shortest representation which round
+    /// trips
+    pub const fn float_decimals(mut self, decimals: Option<usize>) -> Self {
+        self.float_decimals = decimals;
+        self
+    }
+
+    /// Set how NaN and infinite float field values are handled, as InfluxDB
+    /// can not represent them
+    ///
+    /// Defaults to [NonFinite::Error]
+    pub const fn non_finite(mut self, policy: NonFinite) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Reject lines longer than `max` bytes, excluding the newline, with
+    /// [ErrorCode::LineTooLong](crate::ErrorCode::LineTooLong)
+    ///
+    /// Useful for catching lines InfluxDB would reject, e.g., due to the
+    /// request size limits of InfluxDB Cloud, before they are sent. The error
+    /// contains the number of the offending line, starting at 1
+    ///
+    /// Defaults to no limit
+    pub const fn max_line_length(mut self, max: usize) -> Self {
+        self.max_line_length = Some(max);
+        self
+    }
+
+    /// Check each line with `validate` before it is written, failing the
+    /// serialization with the returned error if the line is rejected
+    ///
+    /// Useful for enforcing rules the line protocol itself does not, such as
+    /// tags which are required by an organization
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let options = Options::new().validate(|line| match line.tag("env") {
+    ///     Some(_) => Ok(()),
+    ///     None => Err("missing tag `env`"),
+    /// });
+    ///
+    /// let result = serde_influxlp::to_string_with_options(&metric, &options);
+    /// ```
+    pub fn validate<F, E>(mut self, validate: F) -> Self
+    where
+        F: Fn(LineView) -> std::result::Result<(), E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        let validate = move |line: LineView| validate(line).map_err(ser::Error::custom);
+        self.validate = Some(Validator(Arc::new(validate)));
+        self
+    }
+
+    /// Fill in the current system time, in the configured precision, when
+    /// the timestamp is missing instead of omitting it
+    ///
+    /// Defaults to false
+    pub const fn fill_timestamp(mut self, fill: bool) -> Self {
+        self.fill_timestamp = fill;
+        self
+    }
+}
+
+/// A serializer writing InfluxDB v2 Line protocol into a writer
+///
+/// Each line is written to the writer, followed by a newline, as soon as it
+/// is complete instead of being buffered until the end of the serialization
+///
+/// # Example
+///
+/// ```rust
+/// let file = File::create("output.txt").unwrap();
+/// let mut serializer = Serializer::new(BufWriter::new(file));
+///
+/// for metric in metrics {
+///     metric.serialize(&mut serializer).unwrap();
+/// }
+/// ```
+pub struct Serializer<W> {
+    writer: W,
+
     builder: Builder,
 
+    /// Whether each line is followed by a newline, otherwise newlines are
+    /// only written between lines
+    terminate_lines: bool,
+
+    /// Whether a line has been written yet
+    has_written: bool,
+
     /// Current depth of the serialization
     ///
     /// Used to prevent map fields in tags / fields as they are not supported
     depth: usize,
+
+    /// Bytes of a value serialized as a sequence of bytes, e.g., a `Vec<u8>`
+    bytes: Option<Vec<u8>>,
 }
 
-impl Serializer {
-    fn new() -> Self {
+impl<W> Serializer<W>
+where
+    W: io::Write,
+{
+    /// Create a new serializer writing into `writer`
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, Options::default())
+    }
+
+    /// Create a new serializer writing into `writer` using the given [Options]
+    pub fn with_options(writer: W, options: Options) -> Self {
         Self {
-            builder: Builder::new(),
+            writer,
+            builder: Builder::new(options),
+            terminate_lines: true,
+            has_written: false,
             depth: 0,
+            bytes: None,
+        }
+    }
+
+    /// Create a new serializer which only writes newlines between lines, as
+    /// used by the `to_*` functions
+    fn joined(writer: W, options: Options) -> Self {
+        Self {
+            terminate_lines: false,
+            ..Self::with_options(writer, options)
         }
     }
 
-    fn output(&mut self) -> String {
-        self.builder.output()
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwrap the serializer, returning the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Discard any partially serialized line, e.g., after an error, so the
+    /// serializer can be reused
+    ///
+    /// The internal buffers are kept between lines and values, so reusing a
+    /// serializer avoids allocating them again
+    pub fn reset(&mut self) {
+        self.builder.reset();
+        self.depth = 0;
+        self.bytes = None;
     }
 
     fn build_line(&mut self) -> Result<()> {
-        self.builder.build_line()
+        let line = self.builder.build_line()?;
+        write_line(
+            &mut self.writer,
+            line,
+            self.terminate_lines,
+            self.has_written,
+        )?;
+
+        self.has_written = true;
+        Ok(())
+    }
+
+    /// Write a line exactly as given, see [RawLine](crate::RawLine)
+    fn write_raw_line(&mut self, line: &str) -> Result<()> {
+        write_line(
+            &mut self.writer,
+            line.as_bytes(),
+            self.terminate_lines,
+            self.has_written,
+        )?;
+
+        self.has_written = true;
+        Ok(())
     }
 
     fn set_element(&mut self, element: Element) {
@@ -49,27 +518,55 @@ impl Serializer {
     where
         T: Into<Value>,
     {
+        if let Some(bytes) = &mut self.bytes {
+            let value = value.into();
+            match value.as_int().and_then(|b| u8::try_from(b).ok()) {
+                Some(byte) => bytes.push(byte),
+                None => return Err(ser::Error::custom(format!("invalid byte `{value}`"))),
+            }
+
+            return Ok(());
+        }
+
         self.builder.add_value(value);
         Ok(())
     }
 
+    fn add_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.bytes.is_some() {
+            return Err(Error::unsupported("nested bytes serialization"));
+        }
+
+        self.builder.add_bytes(bytes);
+        Ok(())
+    }
+
+    /// Add the bytes collected from a sequence of bytes as a single value
+    fn end_bytes(&mut self) -> Result<()> {
+        if let Some(bytes) = self.bytes.take() {
+            self.builder.add_bytes(&bytes);
+        }
+
+        Ok(())
+    }
+
     fn remove_value(&mut self) -> Result<()> {
         self.builder.remove_value();
         Ok(())
     }
 }
 
-impl<'de> ser::Serializer for &'de mut Serializer {
+impl<'de, W: io::Write> ser::Serializer for &'de mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = TypeSerializer<'de>;
-    type SerializeTuple = TypeSerializer<'de>;
+    type SerializeSeq = TypeSerializer<'de, W>;
+    type SerializeTuple = TypeSerializer<'de, W>;
     type SerializeTupleStruct = Impossible<(), Error>;
     type SerializeTupleVariant = Impossible<(), Error>;
-    type SerializeMap = TypeSerializer<'de>;
-    type SerializeStruct = TypeSerializer<'de>;
-    type SerializeStructVariant = Impossible<(), Error>;
+    type SerializeMap = TypeSerializer<'de, W>;
+    type SerializeStruct = TypeSerializer<'de, W>;
+    type SerializeStructVariant = TypeSerializer<'de, W>;
 
     fn serialize_bool(self, b: bool) -> Result<Self::Ok> {
         self.add_value(b)
@@ -91,6 +588,13 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         self.add_value(v)
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        match Number::from_i128(v) {
+            Some(n) => self.add_value(Value::Number(n)),
+            None => Err(Error::integer_out_of_range(v)),
+        }
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
         self.add_value(v)
     }
@@ -107,6 +611,13 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         self.add_value(v)
     }
 
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        match Number::from_u128(v) {
+            Some(n) => self.add_value(Value::Number(n)),
+            None => Err(Error::integer_out_of_range(v)),
+        }
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
         self.add_value(v)
     }
@@ -123,8 +634,8 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         self.add_value(v)
     }
 
-    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
-        Err(Error::unsupported("bytes serialization"))
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        self.add_bytes(v)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
@@ -155,11 +666,37 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::unsupported("newtype struct serialization"))
+        // Timestamps are stored in nanoseconds and written in the configured precision
+        if name == timestamp::NAME && self.builder.element().is_timestamp() {
+            let nanos = crate::to_value(value)?
+                .as_int()
+                .ok_or_else(|| <Error as ser::Error>::custom("invalid timestamp"))?;
+            let timestamp = Timestamp::from_nanos(nanos).to_precision(self.builder.precision());
+            return self.add_value(timestamp);
+        }
+
+        // Raw lines and values are written exactly as given
+        if name == raw::LINE_NAME || name == raw::VALUE_NAME {
+            let Value::String(text) = crate::to_value(value)? else {
+                return Err(ser::Error::custom("invalid raw line protocol"));
+            };
+
+            return match self.depth {
+                0 if name == raw::LINE_NAME => self.write_raw_line(&text),
+                0 => Err(Error::invalid_field_type("raw value")),
+                _ => {
+                    self.builder.add_raw(text.into_bytes());
+                    Ok(())
+                }
+            };
+        }
+
+        // Other newtype structs are transparent wrappers around their value
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
@@ -175,7 +712,18 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         Err(Error::unsupported("newtype variant serialization"))
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        // Sequences in place of the tag or field set are lists of key and value pairs,
+        // whose keys and values are added in turn like those of a map
+        let element = self.builder.element();
+        let is_set = self.depth == 1 && (element.is_tags() || element.is_fields());
+
+        // Other sequences inside of a line can only be values serialized as a sequence
+        // of bytes, e.g., a `Vec<u8>`
+        if self.depth > 0 && !is_set {
+            self.bytes = Some(Vec::with_capacity(len.unwrap_or_default()));
+        }
+
         Ok(TypeSerializer { ser: self })
     }
 
@@ -210,26 +758,64 @@ impl<'de> ser::Serializer for &'de mut Serializer {
         Ok(TypeSerializer { ser: self })
     }
 
-    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        self.serialize_map(Some(len))
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        // The name is written as the measurement the same way as the variant of a
+        // struct variant, a measurement member of the struct replaces it
+        let from_type = self.depth == 0 && self.builder.measurement_from_type();
+
+        let serializer = self.serialize_map(Some(len))?;
+        if from_type {
+            serializer.ser.set_element(Element::Measurement);
+            serializer.ser.add_value(name)?;
+        }
+
+        Ok(serializer)
     }
 
     fn serialize_struct_variant(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::unsupported("struct variant serialization"))
+        // Only lines can be struct variants, the variant is used as the measurement
+        // unless the variant has a measurement of its own
+        if self.depth > 0 {
+            return Err(Error::invalid_field_type("struct variant"));
+        }
+
+        let serializer = self.serialize_map(Some(len))?;
+        serializer.ser.set_element(Element::Measurement);
+        serializer.ser.add_value(variant)?;
+
+        Ok(serializer)
+    }
+}
+
+/// Write `line` into `writer`, separated from the lines written before it, or
+/// followed by a newline if lines are terminated
+fn write_line<W>(writer: &mut W, line: &[u8], terminate: bool, has_written: bool) -> Result<()>
+where
+    W: io::Write,
+{
+    if has_written && !terminate {
+        writer.write_all(b"\n")?;
     }
+
+    writer.write_all(line)?;
+    if terminate {
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
 }
 
-pub struct TypeSerializer<'a> {
-    ser: &'a mut Serializer,
+pub struct TypeSerializer<'a, W> {
+    ser: &'a mut Serializer<W>,
 }
 
-impl<'a> SerializeSeq for TypeSerializer<'a> {
+impl<'a, W: io::Write> SerializeSeq for TypeSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -241,11 +827,11 @@ impl<'a> SerializeSeq for TypeSerializer<'a> {
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
+        self.ser.end_bytes()
     }
 }
 
-impl<'a> SerializeTuple for TypeSerializer<'a> {
+impl<'a, W: io::Write> SerializeTuple for TypeSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -257,7 +843,7 @@ impl<'a> SerializeTuple for TypeSerializer<'a> {
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
+        self.ser.end_bytes()
     }
 }
 
@@ -295,6 +881,10 @@ impl ser::Serializer for MapKeySerializer {
         Ok(itoa::Buffer::new().format(v).to_owned())
     }
 
+    fn serialize_i128(self, v: i128) -> Result<String> {
+        Ok(itoa::Buffer::new().format(v).to_owned())
+    }
+
     fn serialize_u8(self, v: u8) -> Result<String> {
         Ok(itoa::Buffer::new().format(v).to_owned())
     }
@@ -311,6 +901,10 @@ impl ser::Serializer for MapKeySerializer {
         Ok(itoa::Buffer::new().format(v).to_owned())
     }
 
+    fn serialize_u128(self, v: u128) -> Result<String> {
+        Ok(itoa::Buffer::new().format(v).to_owned())
+    }
+
     fn serialize_f32(self, v: f32) -> Result<String> {
         if v.is_finite() {
             Ok(ryu::Buffer::new().format_finite(v).to_owned())
@@ -367,11 +961,11 @@ impl ser::Serializer for MapKeySerializer {
         Err(Error::invalid_key())
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<String>
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::invalid_key())
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
@@ -432,7 +1026,7 @@ impl ser::Serializer for MapKeySerializer {
     }
 }
 
-impl<'a> SerializeMap for TypeSerializer<'a> {
+impl<'a, W: io::Write> SerializeMap for TypeSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -471,7 +1065,7 @@ impl<'a> SerializeMap for TypeSerializer<'a> {
     }
 }
 
-impl<'a> SerializeStruct for TypeSerializer<'a> {
+impl<'a, W: io::Write> SerializeStruct for TypeSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -492,6 +1086,22 @@ impl<'a> SerializeStruct for TypeSerializer<'a> {
     }
 }
 
+impl<'a, W: io::Write> SerializeStructVariant for TypeSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        SerializeStruct::end(self)
+    }
+}
+
 /// Serialize a valid data structure `T` to a InfluxDB v2 Line protocol written
 /// into the specified writer
 ///
@@ -524,16 +1134,68 @@ impl<'a> SerializeStruct for TypeSerializer<'a> {
 ///     // Output: metric1 field1=123i
 /// }
 /// ```
-pub fn to_writer<W, T>(mut writer: W, value: &T) -> Result<()>
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize,
+{
+    to_writer_with_options(writer, value, &Options::default())
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB v2 Line protocol written
+/// into the specified writer using the given [Options]
+///
+/// See [to_writer] for more information
+pub fn to_writer_with_options<W, T>(writer: W, value: &T, options: &Options) -> Result<()>
 where
     W: io::Write,
     T: Serialize,
 {
-    let mut serializer = Serializer::new();
-    value.serialize(&mut serializer)?;
+    let mut serializer = Serializer::joined(writer, options.clone());
+    value.serialize(&mut serializer)
+}
+
+/// Serialize each item of `values` to a InfluxDB v2 Line protocol written into
+/// the specified writer
+///
+/// Each item is written as soon as it is serialized, so `values` can be a
+/// lazy, or even unbounded, iterator. The lines are separated by newlines the
+/// same way as when serializing a slice with [to_writer]
+///
+/// # Example
+///
+/// ```rust
+/// let file = File::create("output.txt").unwrap();
+/// let metrics = (0..).map(|i| Metric {
+///     measurement: "metric1".to_string(),
+///     fields: Fields { field1: i },
+/// });
+///
+/// serde_influxlp::to_writer_iter(BufWriter::new(file), metrics.take(1_000_000)).unwrap();
+/// ```
+pub fn to_writer_iter<W, I>(writer: W, values: I) -> Result<()>
+where
+    W: io::Write,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    to_writer_iter_with_options(writer, values, &Options::default())
+}
 
-    let output = serializer.output();
-    writer.write_all(output.as_bytes())?;
+/// Serialize each item of `values` to a InfluxDB v2 Line protocol written into
+/// the specified writer using the given [Options]
+///
+/// See [to_writer_iter] for more information
+pub fn to_writer_iter_with_options<W, I>(writer: W, values: I, options: &Options) -> Result<()>
+where
+    W: io::Write,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut serializer = Serializer::joined(writer, options.clone());
+    for value in values {
+        value.serialize(&mut serializer)?;
+    }
 
     Ok(())
 }
@@ -566,18 +1228,29 @@ where
 ///
 ///     let vec = serde_influxlp::to_vec(&metric).unwrap();
 ///
-///     // Output should only be valid utf8
+///     // Output is valid utf8 unless values are serialized from raw bytes
 ///     let line = String::from_utf8(vec).unwrap();
 ///     println!("{line}");
 ///     // Output: metric1 field1=123i
 /// }
 /// ```
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize,
+{
+    to_vec_with_options(value, &Options::default())
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB v2 Line protocol encoded
+/// as a vector of bytes using the given [Options]
+///
+/// See [to_vec] for more information
+pub fn to_vec_with_options<T>(value: &T, options: &Options) -> Result<Vec<u8>>
 where
     T: Serialize,
 {
     let mut writer = Vec::new();
-    to_writer(&mut writer, value)?;
+    to_writer_with_options(&mut writer, value, options)?;
     Ok(writer)
 }
 
@@ -615,17 +1288,568 @@ pub fn to_string<T>(value: &T) -> Result<String>
 where
     T: Serialize,
 {
-    let result = to_vec(value)?;
-    let string = unsafe { String::from_utf8_unchecked(result) };
-
-    Ok(string)
+    to_string_with_options(value, &Options::default())
 }
 
-#[cfg(test)]
+/// Serialize a valid data structure `T` to a InfluxDB V2 Line protocol string
+/// using the given [Options]
+///
+/// See [to_string] for more information
+pub fn to_string_with_options<T>(value: &T, options: &Options) -> Result<String>
+where
+    T: Serialize,
+{
+    let mut string = String::new();
+    to_fmt_writer_with_options(&mut string, value, options)?;
+    Ok(string)
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB V2 Line protocol string
+/// written into `buf`
+///
+/// The contents of `buf` are replaced, but its allocation is reused, which
+/// avoids allocating a new string for every call when serializing repeatedly.
+/// On error `buf` is left empty
+///
+/// # Example
+///
+/// ```rust
+/// let mut buf = String::new();
+/// for metric in metrics {
+///     serde_influxlp::to_string_into(&mut buf, &metric).unwrap();
+///     println!("{buf}");
+/// }
+/// ```
+pub fn to_string_into<T>(buf: &mut String, value: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    to_string_into_with_options(buf, value, &Options::default())
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB V2 Line protocol string
+/// written into `buf` using the given [Options]
+///
+/// See [to_string_into] for more information
+pub fn to_string_into_with_options<T>(buf: &mut String, value: &T, options: &Options) -> Result<()>
+where
+    T: Serialize,
+{
+    buf.clear();
+
+    let result = to_fmt_writer_with_options(&mut *buf, value, options);
+    if result.is_err() {
+        buf.clear();
+    }
+
+    result
+}
+
+/// Adapter writing the output of a [Serializer] into a [fmt::Write]
+///
+/// The serializer writes whole lines at a time, so each write is checked to
+/// be valid utf8 on its own
+struct FmtWriter<W>(W);
+
+impl<W> io::Write for FmtWriter<W>
+where
+    W: fmt::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Values serialized as bytes are written as is and are not guaranteed to
+        // be valid utf8
+        let string =
+            std::str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0.write_str(string).map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB V2 Line protocol string
+/// written into the specified [fmt::Write]
+///
+/// Useful where [io::Write] is not available, or to write directly into a
+/// formatter. Fails if a value serialized as bytes is not valid utf8
+///
+/// # Example
+///
+/// ```rust
+/// use std::fmt::Write;
+///
+/// let mut output = String::from("# metrics\n");
+/// serde_influxlp::to_fmt_writer(&mut output, &metric).unwrap();
+/// // Output:
+/// // # metrics
+/// // metric1 field1=123i
+/// ```
+pub fn to_fmt_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: fmt::Write,
+    T: Serialize,
+{
+    to_fmt_writer_with_options(writer, value, &Options::default())
+}
+
+/// Serialize a valid data structure `T` to a InfluxDB V2 Line protocol string
+/// written into the specified [fmt::Write] using the given [Options]
+///
+/// See [to_fmt_writer] for more information
+pub fn to_fmt_writer_with_options<W, T>(writer: W, value: &T, options: &Options) -> Result<()>
+where
+    W: fmt::Write,
+    T: Serialize,
+{
+    to_writer_with_options(FmtWriter(writer), value, options)
+}
+
+/// Serialize each item of `values` to its own InfluxDB V2 Line protocol string
+///
+/// Useful when each line is sent as a separate message, e.g., on a queue. An
+/// item serializing to multiple lines, such as a vector, results in a single
+/// string with the lines separated by newlines
+///
+/// # Example
+///
+/// ```rust
+/// let metrics = vec![
+///     Metric {
+///         measurement: "metric1".to_string(),
+///         fields: Fields { field1: 123 },
+///     },
+///     Metric {
+///         measurement: "metric2".to_string(),
+///         fields: Fields { field1: 321 },
+///     },
+/// ];
+///
+/// let lines = serde_influxlp::to_lines(&metrics).unwrap();
+/// println!("{lines:?}");
+/// // Output: ["metric1 field1=123i", "metric2 field1=321i"]
+/// ```
+pub fn to_lines<I>(values: I) -> Result<Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    to_lines_with_options(values, &Options::default())
+}
+
+/// Serialize each item of `values` to its own InfluxDB V2 Line protocol string
+/// using the given [Options]
+///
+/// See [to_lines] for more information
+pub fn to_lines_with_options<I>(values: I, options: &Options) -> Result<Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    // The same serializer is used for all items to avoid setting it up again for
+    // each
+    let mut serializer = Serializer::joined(Vec::new(), options.clone());
+
+    values
+        .into_iter()
+        .map(|value| {
+            value.serialize(&mut serializer)?;
+
+            let output = std::mem::take(serializer.get_mut());
+            serializer.has_written = false;
+
+            String::from_utf8(output).map_err(ser::Error::custom)
+        })
+        .collect()
+}
+
+/// Serialize each item of `values` to InfluxDB V2 Line protocol, split into
+/// batches of at most `max_bytes` bytes each
+///
+/// Lines are never split across batches, and the lines of each batch are
+/// separated by newlines. Useful for staying below the request size limits
+/// of the InfluxDB write endpoints. Fails with
+/// [ErrorCode::LineTooLong](crate::ErrorCode::LineTooLong) if a single item
+/// does not fit in a batch
+///
+/// # Example
+///
+/// ```rust
+/// let batches = serde_influxlp::to_batches(&metrics, 5_000_000).unwrap();
+/// for batch in batches {
+///     client.write(batch).await?;
+/// }
+/// ```
+pub fn to_batches<I>(values: I, max_bytes: usize) -> Result<Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    to_batches_with_options(values, max_bytes, &Options::default())
+}
+
+/// Serialize each item of `values` to InfluxDB V2 Line protocol, split into
+/// batches of at most `max_bytes` bytes each, using the given [Options]
+///
+/// See [to_batches] for more information
+pub fn to_batches_with_options<I>(
+    values: I,
+    max_bytes: usize,
+    options: &Options,
+) -> Result<Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut serializer = Serializer::joined(Vec::new(), options.clone());
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+
+    for (index, value) in values.into_iter().enumerate() {
+        value.serialize(&mut serializer)?;
+        serializer.has_written = false;
+
+        let line = serializer.get_mut();
+        if line.len() > max_bytes {
+            return Err(Error::line_too_long(index + 1, max_bytes));
+        }
+
+        if !batch.is_empty() && batch.len() + 1 + line.len() > max_bytes {
+            batches.push(
+                String::from_utf8(std::mem::take(&mut batch))
+                    .map_err(<Error as ser::Error>::custom)?,
+            );
+        }
+
+        if !batch.is_empty() {
+            batch.push(b'\n');
+        }
+        batch.append(line);
+    }
+
+    if !batch.is_empty() {
+        batches.push(String::from_utf8(batch).map_err(<Error as ser::Error>::custom)?);
+    }
+
+    Ok(batches)
+}
+
+/// Serialize a valid data structure `T` to InfluxDB V2 Line protocol appended
+/// to the end of `buf`
+///
+/// Avoids the extra copy of [to_vec] when the output is sent from an existing
+/// buffer, e.g., one taken from a pool
+///
+/// # Example
+///
+/// ```rust
+/// let mut buf = BytesMut::with_capacity(4096);
+/// serde_influxlp::to_bytes_mut(&mut buf, &metric).unwrap();
+/// let body = buf.split().freeze();
+/// ```
+#[cfg(feature = "bytes")]
+pub fn to_bytes_mut<T>(buf: &mut bytes::BytesMut, value: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    to_bytes_mut_with_options(buf, value, &Options::default())
+}
+
+/// Serialize a valid data structure `T` to InfluxDB V2 Line protocol appended
+/// to the end of `buf` using the given [Options]
+///
+/// See [to_bytes_mut] for more information
+#[cfg(feature = "bytes")]
+pub fn to_bytes_mut_with_options<T>(
+    buf: &mut bytes::BytesMut,
+    value: &T,
+    options: &Options,
+) -> Result<()>
+where
+    T: Serialize,
+{
+    use bytes::BufMut;
+
+    to_writer_with_options(buf.writer(), value, options)
+}
+
+/// Serialize a valid data structure `T` to InfluxDB V2 Line protocol and write
+/// it into the specified async writer
+///
+/// The output is serialized into memory before being written, so the task is
+/// never blocked by the serialization itself. Use [to_writer_async_batched] to
+/// limit the memory used for large collections
+///
+/// # Example
+///
+/// ```rust
+/// let mut stream = TcpStream::connect("127.0.0.1:8086").await?;
+/// serde_influxlp::to_writer_async(&mut stream, &metrics).await?;
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn to_writer_async<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize,
+{
+    to_writer_async_with_options(writer, value, &Options::default()).await
+}
+
+/// Serialize a valid data structure `T` to InfluxDB V2 Line protocol and write
+/// it into the specified async writer using the given [Options]
+///
+/// See [to_writer_async] for more information
+#[cfg(feature = "tokio")]
+pub async fn to_writer_async_with_options<W, T>(
+    mut writer: W,
+    value: &T,
+    options: &Options,
+) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let output = to_vec_with_options(value, options)?;
+    writer.write_all(&output).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Serialize each item of `values` to InfluxDB V2 Line protocol and write them
+/// into the specified async writer, `batch_size` items at a time
+///
+/// At most one batch is kept in memory, which is written before the next one
+/// is serialized. The output is the same as when serializing all items with
+/// [to_writer_async]
+///
+/// # Example
+///
+/// ```rust
+/// let mut stream = TcpStream::connect("127.0.0.1:8086").await?;
+/// serde_influxlp::to_writer_async_batched(&mut stream, metrics, 1000).await?;
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn to_writer_async_batched<W, I>(writer: W, values: I, batch_size: usize) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    to_writer_async_batched_with_options(writer, values, batch_size, &Options::default()).await
+}
+
+/// Serialize each item of `values` to InfluxDB V2 Line protocol and write them
+/// into the specified async writer, `batch_size` items at a time, using the
+/// given [Options]
+///
+/// See [to_writer_async_batched] for more information
+#[cfg(feature = "tokio")]
+pub async fn to_writer_async_batched_with_options<W, I>(
+    mut writer: W,
+    values: I,
+    batch_size: usize,
+    options: &Options,
+) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    use tokio::io::AsyncWriteExt;
+
+    // The serializer keeps track of whether a line has been written, so the
+    // batches are joined by newlines the same way as the lines within them
+    let mut serializer = Serializer::joined(Vec::new(), options.clone());
+    for (index, value) in values.into_iter().enumerate() {
+        value.serialize(&mut serializer)?;
+
+        if (index + 1).is_multiple_of(batch_size.max(1)) {
+            writer.write_all(serializer.get_ref()).await?;
+            serializer.get_mut().clear();
+        }
+    }
+
+    writer.write_all(serializer.get_ref()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// A [futures_sink::Sink] serializing each item to InfluxDB V2 Line protocol
+/// and forwarding the bytes to an inner sink
+///
+/// Each item is sent as its own chunk of bytes, with every line followed by a
+/// newline, so the chunks can be concatenated as is, e.g., into a streaming
+/// request body. Errors of the inner sink are converted to an [Error]
+///
+/// # Example
+///
+/// ```rust
+/// let (tx, rx) = futures::channel::mpsc::channel::<Vec<u8>>(16);
+/// let mut sink = LineProtocolSink::new(tx);
+///
+/// for metric in metrics {
+///     sink.send(metric).await?;
+/// }
+/// ```
+#[cfg(feature = "futures")]
+pub struct LineProtocolSink<S, T> {
+    inner: S,
+
+    serializer: Serializer<Vec<u8>>,
+
+    item: std::marker::PhantomData<fn(T)>,
+}
+
+#[cfg(feature = "futures")]
+impl<S, T> LineProtocolSink<S, T> {
+    /// Create a new sink forwarding the serialized items to `inner`
+    pub fn new(inner: S) -> Self {
+        Self::with_options(inner, Options::default())
+    }
+
+    /// Create a new sink forwarding the serialized items to `inner` using the
+    /// given [Options]
+    pub fn with_options(inner: S, options: Options) -> Self {
+        Self {
+            inner,
+            serializer: Serializer::with_options(Vec::new(), options),
+            item: std::marker::PhantomData,
+        }
+    }
+
+    /// Get a reference to the inner sink
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap the sink, returning the inner sink
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S, T> futures_sink::Sink<T> for LineProtocolSink<S, T>
+where
+    S: futures_sink::Sink<Vec<u8>> + Unpin,
+    S::Error: fmt::Display,
+    T: Serialize,
+{
+    type Error = Error;
+
+    fn poll_ready(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        std::pin::Pin::new(&mut self.inner)
+            .poll_ready(cx)
+            .map_err(ser::Error::custom)
+    }
+
+    fn start_send(mut self: std::pin::Pin<&mut Self>, item: T) -> Result<()> {
+        let this = &mut *self;
+        if let Err(e) = item.serialize(&mut this.serializer) {
+            this.serializer.reset();
+            this.serializer.get_mut().clear();
+            return Err(e);
+        }
+
+        let bytes = std::mem::take(this.serializer.get_mut());
+        std::pin::Pin::new(&mut this.inner)
+            .start_send(bytes)
+            .map_err(ser::Error::custom)
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        std::pin::Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map_err(ser::Error::custom)
+    }
+
+    fn poll_close(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<()>> {
+        std::pin::Pin::new(&mut self.inner)
+            .poll_close(cx)
+            .map_err(ser::Error::custom)
+    }
+}
+
+/// Adapter implementing the `influxdb2` client's
+/// [WriteDataPoint](influxdb2::models::WriteDataPoint) for any `T:
+/// Serialize`
+///
+/// Allows writing serde-derived structs with the client without building a
+/// `DataPoint` for each of them. Every line written is followed by a newline
+///
+/// # Example
+///
+/// ```rust
+/// let client = influxdb2::Client::new(host, org, token);
+/// let points = metrics.into_iter().map(WritePoint::new);
+/// client.write(bucket, futures::stream::iter(points)).await?;
+/// ```
+#[cfg(feature = "influxdb2")]
+#[derive(Debug, Clone)]
+pub struct WritePoint<T> {
+    value: T,
+
+    options: Options,
+}
+
+#[cfg(feature = "influxdb2")]
+impl<T> WritePoint<T> {
+    /// Wrap `value` to be written with the default [Options]
+    pub fn new(value: T) -> Self {
+        Self::with_options(value, Options::default())
+    }
+
+    /// Wrap `value` to be written with the given [Options]
+    pub fn with_options(value: T, options: Options) -> Self {
+        Self { value, options }
+    }
+
+    /// Unwrap the adapter, returning the wrapped value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(feature = "influxdb2")]
+impl<T> influxdb2::models::WriteDataPoint for WritePoint<T>
+where
+    T: Serialize,
+{
+    fn write_data_point_to<W>(&self, w: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        // The client concatenates the output of each point, so every line
+        // has to be terminated
+        let mut serializer = Serializer::with_options(w, self.options.clone());
+        self.value
+            .serialize(&mut serializer)
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
 mod test {
     use std::collections::HashMap;
 
-    use crate::{de::from_str, Value};
+    use crate::{de::from_str, ErrorCode, Point, Value};
 
     use super::*;
 
@@ -637,6 +1861,7 @@ mod test {
     }
 
     #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    #[allow(dead_code)]
     struct Tags {
         pub tag1: i32,
     }
@@ -682,4 +1907,1015 @@ mod test {
         let metric = from_str::<Metric>(&line);
         assert!(metric.is_ok())
     }
+
+    #[test]
+    fn test_ser_timestamp_with() {
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Metric {
+            pub measurement: String,
+
+            pub fields: HashMap<String, Value>,
+
+            #[serde(with = "crate::ts_seconds")]
+            pub timestamp: i64,
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct OptionalMetric {
+            pub measurement: String,
+
+            pub fields: HashMap<String, Value>,
+
+            #[serde(default, with = "crate::ts_millis::option")]
+            pub timestamp: Option<i64>,
+        }
+
+        let line = "metric1 field1=1i 1577836800000000000";
+
+        let metric = from_str::<Metric>(line).unwrap();
+        assert_eq!(metric.timestamp, 1577836800);
+        assert_eq!(to_string(&metric).unwrap(), line);
+
+        let metric = from_str::<OptionalMetric>(line).unwrap();
+        assert_eq!(metric.timestamp, Some(1577836800000));
+        assert_eq!(to_string(&metric).unwrap(), line);
+
+        let line = "metric1 field1=1i";
+        let metric = from_str::<OptionalMetric>(line).unwrap();
+        assert_eq!(metric.timestamp, None);
+        assert_eq!(to_string(&metric).unwrap(), line);
+
+        let metric = Metric {
+            measurement: "metric1".to_string(),
+            fields: HashMap::from([("field1".to_string(), Value::from(1))]),
+            timestamp: i64::MAX,
+        };
+        assert!(to_string(&metric).is_err());
+    }
+
+    #[test]
+    fn test_ser_telegraf() {
+        let mut point = Point::new("cpu load");
+        point.tags.insert("z".to_string(), "a\tb".to_string());
+        point.tags.insert("a".to_string(), "x,y".to_string());
+        point.tags.insert("e".to_string(), String::new());
+        point.fields.insert("f".to_string(), Value::from(0.5));
+        point.fields.insert("i".to_string(), Value::from(u64::MAX));
+        point.fields.insert("n".to_string(), Value::from(f64::NAN));
+        point
+            .fields
+            .insert("s".to_string(), Value::from("say \"hi\""));
+
+        let line = to_string_with_options(&point, &Options::telegraf()).unwrap();
+        assert_eq!(
+            line,
+            r#"cpu\ load,a=x\,y,z=a\tb f=0.5,i=9223372036854775807i,s="say \"hi\"""#
+        );
+    }
+
+    #[test]
+    fn test_ser_to_lines() {
+        let points: Vec<Point> = (1..=3)
+            .map(|i| {
+                let mut point = Point::new(format!("metric{i}"));
+                point.fields.insert("field1".to_string(), Value::from(i));
+                point
+            })
+            .collect();
+
+        let lines = to_lines(&points).unwrap();
+        assert_eq!(
+            lines,
+            [
+                "metric1 field1=1i",
+                "metric2 field1=2i",
+                "metric3 field1=3i"
+            ]
+        );
+
+        assert!(to_lines(&[Point::new("metric1")]).is_err());
+    }
+
+    #[test]
+    fn test_ser_streaming() {
+        let mut serializer = Serializer::new(Vec::new());
+
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1));
+        point.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.get_ref(), b"metric1 field1=1i\n");
+
+        point.measurement = "metric2".to_string();
+        vec![point.clone(), point]
+            .serialize(&mut serializer)
+            .unwrap();
+        assert_eq!(
+            serializer.into_inner(),
+            b"metric1 field1=1i\nmetric2 field1=1i\nmetric2 field1=1i\n"
+        );
+    }
+
+    #[test]
+    fn test_ser_reuse() {
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1));
+
+        let mut buf = String::with_capacity(64);
+        to_string_into(&mut buf, &point).unwrap();
+        assert_eq!(buf, "metric1 field1=1i");
+        assert_eq!(buf.capacity(), 64);
+
+        assert!(to_string_into(&mut buf, &Point::new("metric2")).is_err());
+        assert!(buf.is_empty());
+
+        // A failed line does not leak into the next line
+        let mut serializer = Serializer::new(Vec::new());
+        let mut invalid = point.clone();
+        invalid.timestamp = Some(i64::MAX);
+        assert!(invalid.serialize(&mut serializer).is_err());
+
+        serializer.reset();
+        point.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.into_inner(), b"metric1 field1=1i\n");
+    }
+
+    #[test]
+    fn test_ser_validate() {
+        let options = Options::new().validate(|line| {
+            if line.tag("env").is_none() {
+                return Err(format!(
+                    "missing tag `env` in {}",
+                    line.measurement().unwrap()
+                ));
+            }
+
+            match line.field("value").and_then(|v| v.as_float()) {
+                Some(v) if v >= 0.0 => Ok(()),
+                _ => Err("negative value".to_string()),
+            }
+        });
+
+        let mut point = Point::new("metric1");
+        point.fields.insert("value".to_string(), Value::from(1.5));
+
+        let error = to_string_with_options(&point, &options).unwrap_err();
+        assert!(error.to_string().ends_with("missing tag `env` in metric1"));
+
+        point.tags.insert("env".to_string(), "prod".to_string());
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1,env=prod value=1.5");
+
+        point.fields.insert("value".to_string(), Value::from(-1.5));
+        assert!(to_string_with_options(&point, &options).is_err());
+    }
+
+    #[test]
+    fn test_ser_sort_tags() {
+        #[derive(serde::Serialize)]
+        struct Metric {
+            measurement: String,
+            tags: HashMap<String, String>,
+            fields: HashMap<String, i64>,
+        }
+
+        let metric = Metric {
+            measurement: "metric1".to_string(),
+            tags: ["d", "b", "a", "c"]
+                .into_iter()
+                .map(|k| (k.to_string(), k.to_uppercase()))
+                .collect(),
+            fields: HashMap::from([("field1".to_string(), 1)]),
+        };
+
+        let options = Options::new().sort_tags(true);
+        let line = to_string_with_options(&metric, &options).unwrap();
+        assert_eq!(line, "metric1,a=A,b=B,c=C,d=D field1=1i");
+    }
+
+    #[test]
+    fn test_ser_unsigned_suffix() {
+        let mut point = Point::new("metric1");
+        point
+            .fields
+            .insert("field1".to_string(), Value::from(u64::MAX));
+        point.fields.insert("field2".to_string(), Value::from(-1));
+
+        let line = to_string(&point).unwrap();
+        assert_eq!(line, "metric1 field1=18446744073709551615i,field2=-1i");
+
+        let options = Options::new().unsigned_suffix(true);
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=18446744073709551615u,field2=-1i");
+
+        #[derive(serde::Deserialize)]
+        struct Parsed {
+            fields: crate::value::Map,
+        }
+
+        assert_eq!(from_str::<Parsed>(&line).unwrap().fields, point.fields);
+
+        assert_eq!(format!("{:#}", Value::from(1u64)), "1u");
+        assert_eq!(format!("{}", Value::from(1u64)), "1i");
+    }
+
+    #[test]
+    fn test_ser_fill_timestamp() {
+        let metric = Metric {
+            metric: Measurement::Metric2,
+            tags: None,
+            fields: Fields {
+                field1: "hello".to_string(),
+                field2: Some(true),
+            },
+            timestamp: None,
+        };
+
+        let line = to_string(&metric).unwrap();
+        assert_eq!(line, "metric2 field1=\"hello\",field2=true");
+
+        let options = Options::new()
+            .precision(Precision::Seconds)
+            .fill_timestamp(true);
+        let line = to_string_with_options(&metric, &options).unwrap();
+
+        let metric = from_str::<Metric>(&line).unwrap();
+        let timestamp = metric.timestamp.unwrap();
+        assert!(timestamp > 1577836800 && timestamp < 1577836800 * 10);
+
+        let metric = Metric {
+            timestamp: Some(123),
+            ..metric
+        };
+        let line = to_string_with_options(&metric, &options).unwrap();
+        assert!(line.ends_with(" 123"));
+
+        let metric = Metric {
+            timestamp: Some(i64::MAX),
+            ..metric
+        };
+        let result = to_string(&metric);
+        assert!(matches!(
+            result.unwrap_err().code,
+            crate::ErrorCode::TimestampOutOfRange
+        ));
+
+        let metric = Metric {
+            timestamp: Some(crate::MAX_TIMESTAMP / 1_000_000_000 + 1),
+            ..metric
+        };
+        assert!(to_string(&metric).is_ok());
+        assert!(to_string_with_options(&metric, &options).is_err());
+    }
+
+    #[test]
+    fn test_ser_timestamp_type() {
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Stamped {
+            measurement: String,
+            fields: HashMap<String, Value>,
+            timestamp: Timestamp,
+        }
+
+        let metric = Stamped {
+            measurement: "metric1".to_string(),
+            fields: HashMap::from([("field1".to_string(), Value::from(1))]),
+            timestamp: Timestamp::from_millis(1704067200123).unwrap(),
+        };
+
+        let line = to_string(&metric).unwrap();
+        assert_eq!(line, "metric1 field1=1i 1704067200123000000");
+        assert_eq!(
+            from_str::<Stamped>(&line).unwrap().timestamp,
+            metric.timestamp
+        );
+
+        let options = Options::new().precision(Precision::Milliseconds);
+        let line = to_string_with_options(&metric, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1i 1704067200123");
+
+        let de_options = crate::de::Options::new().precision(Precision::Milliseconds);
+        let parsed = crate::from_str_with_options::<Stamped>(&line, &de_options).unwrap();
+        assert_eq!(parsed.timestamp, metric.timestamp);
+
+        let timestamp = metric.timestamp + std::time::Duration::from_secs(1);
+        assert_eq!(timestamp.as_secs(), 1704067201);
+        assert_eq!(
+            timestamp.duration_since(metric.timestamp),
+            Some(std::time::Duration::from_secs(1))
+        );
+        assert_eq!(Timestamp::from_secs(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_ser_float_format() {
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1.0));
+        point
+            .fields
+            .insert("field2".to_string(), Value::from(2.345));
+
+        let line = to_string(&point).unwrap();
+        assert_eq!(line, "metric1 field1=1,field2=2.345");
+
+        let options = Options::new().decimal_point(true);
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1.0,field2=2.345");
+
+        let options = Options::new().float_decimals(Some(2));
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1.00,field2=2.35");
+
+        let options = Options::new().float_decimals(Some(0)).decimal_point(true);
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1.0,field2=2.0");
+    }
+
+    #[test]
+    fn test_ser_non_finite() {
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1.5));
+        point
+            .fields
+            .insert("field2".to_string(), Value::from(f64::NAN));
+
+        let result = to_string(&point);
+        assert!(matches!(
+            result.unwrap_err().code,
+            crate::ErrorCode::InfiniteFloat
+        ));
+
+        let options = Options::new().non_finite(NonFinite::Skip);
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1.5");
+
+        let options = Options::new().non_finite(NonFinite::Replace(-1.0));
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1 field1=1.5,field2=-1");
+
+        let options = Options::new().non_finite(NonFinite::Replace(f64::INFINITY));
+        assert!(to_string_with_options(&point, &options).is_err());
+
+        point.fields.remove("field1");
+        let options = Options::new().non_finite(NonFinite::Skip);
+        assert!(to_string_with_options(&point, &options).is_err());
+    }
+
+    #[test]
+    fn test_ser_max_line_length() {
+        let points: Vec<Point> = ["metric1", "metric22", "metric333"]
+            .into_iter()
+            .map(|measurement| {
+                let mut point = Point::new(measurement);
+                point.fields.insert("field1".to_string(), Value::from(1.5));
+                point
+            })
+            .collect();
+
+        let options = Options::new().max_line_length(19);
+        let lines = to_string_with_options(&&points[..2], &options).unwrap();
+        assert_eq!(lines, "metric1 field1=1.5\nmetric22 field1=1.5");
+
+        let result = to_string_with_options(&points, &options);
+        assert!(matches!(
+            result.unwrap_err().code,
+            crate::ErrorCode::LineTooLong { line: 3, max: 19 }
+        ));
+    }
+
+    #[test]
+    fn test_ser_newtype_struct() {
+        #[derive(Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+        struct Host(String);
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Celsius(f64);
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Tags {
+            host: Host,
+        }
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Fields {
+            temperature: Celsius,
+        }
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Reading {
+            measurement: Host,
+            tags: Tags,
+            fields: Fields,
+        }
+
+        let reading = Reading {
+            measurement: Host("sensor".to_string()),
+            tags: Tags {
+                host: Host("server01".to_string()),
+            },
+            fields: Fields {
+                temperature: Celsius(21.5),
+            },
+        };
+
+        let line = to_string(&reading).unwrap();
+        assert_eq!(line, "sensor,host=server01 temperature=21.5");
+        assert_eq!(from_str::<Reading>(&line).unwrap(), reading);
+
+        let value = crate::to_value(Celsius(1.5)).unwrap();
+        assert_eq!(value, Value::from(1.5));
+        assert_eq!(crate::from_value::<Celsius>(value).unwrap(), Celsius(1.5));
+
+        // Newtype structs can be used as keys as well
+        #[derive(Debug, serde::Serialize)]
+        struct Labeled {
+            measurement: String,
+            tags: HashMap<Host, Host>,
+            fields: HashMap<Host, Celsius>,
+        }
+
+        let labeled = Labeled {
+            measurement: "sensor".to_string(),
+            tags: HashMap::from([(Host("host".to_string()), Host("server02".to_string()))]),
+            fields: HashMap::from([(Host("temperature".to_string()), Celsius(20.0))]),
+        };
+
+        let line = to_string(&labeled).unwrap();
+        assert_eq!(line, "sensor,host=server02 temperature=20");
+    }
+
+    #[test]
+    fn test_ser_struct_variant() {
+        #[derive(Debug, serde::Serialize)]
+        struct HostTags {
+            host: String,
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Line {
+            Cpu {
+                tags: HostTags,
+                fields: HashMap<String, f64>,
+                timestamp: i64,
+            },
+            Mem {
+                fields: HashMap<String, u64>,
+            },
+            Renamed {
+                measurement: String,
+                fields: HashMap<String, bool>,
+            },
+        }
+
+        let lines = vec![
+            Line::Cpu {
+                tags: HostTags {
+                    host: "server01".to_string(),
+                },
+                fields: HashMap::from([("usage".to_string(), 0.5)]),
+                timestamp: 1704067200,
+            },
+            Line::Mem {
+                fields: HashMap::from([("used".to_string(), 1024)]),
+            },
+            Line::Renamed {
+                measurement: "disk".to_string(),
+                fields: HashMap::from([("full".to_string(), false)]),
+            },
+        ];
+
+        let output = to_string(&lines).unwrap();
+        assert_eq!(
+            output,
+            "cpu,host=server01 usage=0.5 1704067200\nmem used=1024i\ndisk full=false"
+        );
+
+        #[derive(Debug, serde::Serialize)]
+        enum Nested {
+            Variant { value: i32 },
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        struct Outer {
+            measurement: String,
+            fields: HashMap<String, Nested>,
+        }
+
+        let outer = Outer {
+            measurement: "metric1".to_string(),
+            fields: HashMap::from([("field1".to_string(), Nested::Variant { value: 1 })]),
+        };
+        assert!(to_string(&outer).is_err());
+    }
+
+    #[test]
+    fn test_ser_static_tags() {
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1));
+
+        let options = Options::new()
+            .static_tag("host", "server01")
+            .static_tag("env", "prod");
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1,host=server01,env=prod field1=1i");
+
+        point.tags.insert("env".to_string(), "dev".to_string());
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1,env=dev,host=server01 field1=1i");
+
+        let options = options.sort_tags(true);
+        let lines = to_string_with_options(&vec![point.clone(), point], &options).unwrap();
+        assert_eq!(
+            lines,
+            "metric1,env=dev,host=server01 field1=1i\nmetric1,env=dev,host=server01 field1=1i"
+        );
+    }
+
+    #[test]
+    fn test_ser_measurement_prefix() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Parsed {
+            measurement: String,
+            #[allow(dead_code)]
+            fields: HashMap<String, Value>,
+        }
+
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(1));
+
+        let options = Options::new()
+            .measurement_prefix("prod_")
+            .measurement_suffix("_v2");
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "prod_metric1_v2 field1=1i");
+
+        let de_options = crate::de::Options::new()
+            .strip_measurement_prefix("prod_")
+            .strip_measurement_suffix("_v2");
+        let parsed: Parsed = crate::from_str_with_options(&line, &de_options).unwrap();
+        assert_eq!(parsed.measurement, "metric1");
+
+        let line = "dev_metric1,tag1=prod_v2 field1=1i";
+        let parsed: Parsed = crate::from_str_with_options(line, &de_options).unwrap();
+        assert_eq!(parsed.measurement, "dev_metric1");
+    }
+
+    #[test]
+    fn test_ser_drop_keys() {
+        let mut point = Point::new("metric1");
+        point
+            .tags
+            .insert("host".to_string(), "server01".to_string());
+        point.tags.insert("session".to_string(), "abc".to_string());
+        point.fields.insert("field1".to_string(), Value::from(1));
+        point
+            .fields
+            .insert("secret".to_string(), Value::from("hunter2"));
+
+        let options = Options::new().drop_tag("session").drop_field("secret");
+        let line = to_string_with_options(&point, &options).unwrap();
+        assert_eq!(line, "metric1,host=server01 field1=1i");
+
+        let options = options.drop_field("field1");
+        assert!(to_string_with_options(&point, &options).is_err());
+    }
+
+    #[test]
+    fn test_ser_duplicates() {
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, serde::Serialize)]
+        struct Merged {
+            #[serde(flatten)]
+            first: BTreeMap<String, i64>,
+
+            #[serde(flatten)]
+            second: BTreeMap<String, i64>,
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        struct Line {
+            measurement: String,
+            tags: BTreeMap<String, String>,
+            fields: Merged,
+        }
+
+        let line = Line {
+            measurement: "metric1".to_string(),
+            tags: BTreeMap::from([("tag1".to_string(), "a".to_string())]),
+            fields: Merged {
+                first: BTreeMap::from([("field1".to_string(), 1), ("field2".to_string(), 2)]),
+                second: BTreeMap::from([("field1".to_string(), 3)]),
+            },
+        };
+
+        let error = to_string(&line).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "an error occured: duplicate key `field1`"
+        );
+        assert!(matches!(
+            error.code,
+            crate::ErrorCode::DuplicateKey(key) if key == "field1"
+        ));
+
+        let options = Options::new().duplicates(Duplicates::KeepFirst);
+        let output = to_string_with_options(&line, &options).unwrap();
+        assert_eq!(output, "metric1,tag1=a field1=1i,field2=2i");
+
+        let options = Options::new().duplicates(Duplicates::KeepLast);
+        let output = to_string_with_options(&line, &options).unwrap();
+        assert_eq!(output, "metric1,tag1=a field2=2i,field1=3i");
+
+        let options = options.static_tag("tag2", "b");
+        let output = to_string_with_options(&line, &options).unwrap();
+        assert_eq!(output, "metric1,tag1=a,tag2=b field2=2i,field1=3i");
+    }
+
+    #[test]
+    fn test_ser_escaping() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Escaped {
+            measurement: String,
+            tags: HashMap<String, String>,
+            fields: HashMap<String, String>,
+        }
+
+        let escaped = Escaped {
+            measurement: "my metric,v1".to_string(),
+            tags: HashMap::from([("tag key".to_string(), "hello world,a=b".to_string())]),
+            fields: HashMap::from([("field=1".to_string(), r#"say "hi" \o/"#.to_string())]),
+        };
+
+        let line = to_string(&escaped).unwrap();
+        assert_eq!(
+            line,
+            r#"my\ metric\,v1,tag\ key=hello\ world\,a\=b field\=1="say \"hi\" \\o/""#
+        );
+        assert_eq!(from_str::<Escaped>(&line).unwrap(), escaped);
+    }
+
+    #[test]
+    fn test_ser_to_writer_iter() {
+        let points = (1..=3).map(|i| {
+            let mut point = Point::new(format!("metric{i}"));
+            point.fields.insert("field1".to_string(), Value::from(i));
+            point
+        });
+
+        let mut output = Vec::new();
+        to_writer_iter(&mut output, points).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "metric1 field1=1i\nmetric2 field1=2i\nmetric3 field1=3i"
+        );
+
+        let points = [Point::new("metric1")];
+        let mut output = Vec::new();
+        assert!(to_writer_iter(&mut output, &points).is_err());
+
+        let mut output = Vec::new();
+        to_writer_iter(&mut output, Vec::<Point>::new()).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_ser_to_batches() {
+        let points: Vec<Point> = (1..=5)
+            .map(|i| {
+                let mut point = Point::new(format!("metric{i}"));
+                point.fields.insert("field1".to_string(), Value::from(i));
+                point
+            })
+            .collect();
+
+        // Each line is 17 bytes long, so two lines and a newline fit in 35 bytes
+        let batches = to_batches(&points, 35).unwrap();
+        assert_eq!(
+            batches,
+            [
+                "metric1 field1=1i\nmetric2 field1=2i",
+                "metric3 field1=3i\nmetric4 field1=4i",
+                "metric5 field1=5i",
+            ]
+        );
+
+        let batches = to_batches(&points, 34).unwrap();
+        assert_eq!(batches.len(), 5);
+        assert!(batches.iter().all(|batch| batch.len() == 17));
+
+        let result = to_batches(&points, 16);
+        assert!(matches!(
+            result.unwrap_err().code,
+            crate::ErrorCode::LineTooLong { line: 1, .. }
+        ));
+
+        assert!(to_batches(Vec::<Point>::new(), 16).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ser_to_fmt_writer() {
+        struct Display<'a>(&'a Point);
+
+        impl fmt::Display for Display<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                to_fmt_writer(f, self.0).map_err(|_| fmt::Error)
+            }
+        }
+
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(123));
+
+        let mut output = String::from("# metrics\n");
+        to_fmt_writer(&mut output, &point).unwrap();
+        assert_eq!(output, "# metrics\nmetric1 field1=123i");
+
+        assert_eq!(Display(&point).to_string(), "metric1 field1=123i");
+
+        let mut buf = String::from("stale");
+        to_string_into(&mut buf, &point).unwrap();
+        assert_eq!(buf, "metric1 field1=123i");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_ser_to_writer_async() {
+        let points: Vec<Point> = (1..=5)
+            .map(|i| {
+                let mut point = Point::new(format!("metric{i}"));
+                point.fields.insert("field1".to_string(), Value::from(i));
+                point
+            })
+            .collect();
+        let expected = to_string(&points).unwrap();
+
+        let mut output = Vec::new();
+        to_writer_async(&mut output, &points).await.unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        for batch_size in [0, 1, 2, 5, 10] {
+            let mut output = Vec::new();
+            to_writer_async_batched(&mut output, &points, batch_size)
+                .await
+                .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_ser_to_bytes_mut() {
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(123));
+
+        let mut buf = bytes::BytesMut::from("# metrics\n");
+        to_bytes_mut(&mut buf, &point).unwrap();
+        assert_eq!(&buf[..], b"# metrics\nmetric1 field1=123i");
+
+        let line = buf.split_off(10).freeze();
+        assert_eq!(line, "metric1 field1=123i");
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_ser_line_protocol_sink() {
+        use futures::SinkExt;
+
+        let mut point = Point::new("metric1");
+        point.fields.insert("field1".to_string(), Value::from(123));
+
+        let mut sink = LineProtocolSink::new(Vec::<Vec<u8>>::new());
+        futures::executor::block_on(async {
+            sink.send(point.clone()).await.unwrap();
+            sink.send(point.clone()).await.unwrap();
+
+            // Invalid items are rejected without affecting the following items
+            sink.send(Point::new("metric2")).await.unwrap_err();
+            sink.send(point).await.unwrap();
+        });
+
+        assert_eq!(
+            sink.into_inner(),
+            [
+                b"metric1 field1=123i\n".to_vec(),
+                b"metric1 field1=123i\n".to_vec(),
+                b"metric1 field1=123i\n".to_vec(),
+            ]
+        );
+    }
+
+    #[cfg(feature = "influxdb2")]
+    #[test]
+    fn test_ser_write_point() {
+        use influxdb2::models::{DataPoint, WriteDataPoint};
+
+        let mut point = Point::new("metric1");
+        point.tags.insert("tag1".to_string(), "a b".to_string());
+        point.fields.insert("field1".to_string(), Value::from(123));
+        point
+            .fields
+            .insert("field2".to_string(), Value::from("text"));
+        point.timestamp = Some(1577836800);
+
+        let mut output = Vec::new();
+        WritePoint::new(&point)
+            .write_data_point_to(&mut output)
+            .unwrap();
+
+        let mut expected = Vec::new();
+        DataPoint::builder("metric1")
+            .tag("tag1", "a b")
+            .field("field1", 123)
+            .field("field2", "text")
+            .timestamp(1577836800)
+            .build()
+            .unwrap()
+            .write_data_point_to(&mut expected)
+            .unwrap();
+
+        assert_eq!(String::from_utf8(output), String::from_utf8(expected));
+
+        let result = WritePoint::new(Point::new("metric2")).write_data_point_to(Vec::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_ser_measurement_from_type() {
+        use crate::de::{self, from_str_with_options};
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        #[serde(rename = "cpu")]
+        struct Cpu {
+            fields: HashMap<String, f64>,
+
+            timestamp: Option<i64>,
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        struct Mem {
+            measurement: String,
+
+            fields: HashMap<String, u64>,
+        }
+
+        let cpu = Cpu {
+            fields: HashMap::from([("usage".to_string(), 0.5)]),
+            timestamp: Some(1704067200),
+        };
+
+        let options = Options::new().measurement_from_type(true);
+        let output = to_string_with_options(&cpu, &options).unwrap();
+        assert_eq!(output, "cpu usage=0.5 1704067200");
+
+        // Without the option there is no measurement to write
+        assert!(to_string(&cpu).is_err());
+
+        let mem = Mem {
+            measurement: "memory".to_string(),
+            fields: HashMap::from([("used".to_string(), 1024)]),
+        };
+        let output = to_string_with_options(&mem, &options).unwrap();
+        assert_eq!(output, "memory used=1024i");
+
+        let de_options = de::Options::new().measurement_from_type(true);
+        let parsed: Cpu = from_str_with_options("cpu usage=0.5 1704067200", &de_options).unwrap();
+        assert_eq!(parsed, cpu);
+
+        let parsed: Vec<Cpu> =
+            from_str_with_options("cpu usage=0.5 1704067200\ncpu usage=0.7", &de_options).unwrap();
+        assert_eq!(parsed.len(), 2);
+
+        let result: Result<Cpu> = from_str_with_options("mem usage=0.5", &de_options);
+        assert!(matches!(
+            result.unwrap_err().code,
+            crate::ErrorCode::InvalidValue(v) if v == "mem"
+        ));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn test_ser_preserve_order() {
+        let mut point = Point::new("metric");
+        point.tags.insert("b".to_string(), "1".to_string());
+        point.tags.insert("a".to_string(), "2".to_string());
+        point.fields.insert("y".to_string(), Value::from(1i64));
+        point.fields.insert("x".to_string(), Value::from(2i64));
+        assert_eq!(to_string(&point).unwrap(), "metric,b=1,a=2 y=1i,x=2i");
+
+        // Removing a key keeps the order of the remaining keys
+        point.fields.insert("z".to_string(), Value::from(3i64));
+        point.fields.remove("y");
+        assert_eq!(to_string(&point).unwrap(), "metric,b=1,a=2 x=2i,z=3i");
+
+        #[derive(Debug, Serialize, serde::Deserialize)]
+        struct Metric {
+            measurement: String,
+            tags: crate::value::Map<String>,
+            fields: crate::value::Map,
+        }
+
+        // Keys read from the input are kept in the same order when written again
+        let line = "metric,host=a,dc=b used=1i,free=2i";
+        let metric: Metric = from_str(line).unwrap();
+        assert_eq!(metric.tags.keys().collect::<Vec<_>>(), ["host", "dc"]);
+        assert_eq!(to_string(&metric).unwrap(), line);
+    }
+
+    #[test]
+    fn test_ser_128_bit_integers() {
+        #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+        struct Fields {
+            bytes: u128,
+            delta: i128,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+        struct Counter {
+            measurement: String,
+            fields: Fields,
+            timestamp: i128,
+        }
+
+        let counter = Counter {
+            measurement: "net".to_string(),
+            fields: Fields {
+                bytes: u64::MAX as u128,
+                delta: -5,
+            },
+            timestamp: 1577836800,
+        };
+        let line = to_string(&counter).unwrap();
+        assert_eq!(line, "net bytes=18446744073709551615i,delta=-5i 1577836800");
+        assert_eq!(from_str::<Counter>(&line).unwrap(), counter);
+
+        let counter = Counter {
+            fields: Fields {
+                bytes: u64::MAX as u128 + 1,
+                delta: 0,
+            },
+            ..counter
+        };
+        let error = to_string(&counter).unwrap_err();
+        assert!(
+            matches!(error.code, ErrorCode::IntegerOutOfRange(v) if v == "18446744073709551616")
+        );
+
+        assert_eq!(
+            crate::to_value(i64::MIN as i128 - 1)
+                .unwrap_err()
+                .to_string(),
+            "an error occured: integer out of range: `-9223372036854775809` does not fit a 64 bit \
+             integer"
+        );
+        assert_eq!(crate::to_value(12u128).unwrap(), Value::from(12u64));
+        assert_eq!(crate::from_value::<i128>(Value::from(-12i64)).unwrap(), -12);
+    }
+
+    #[test]
+    fn test_ser_to_values() {
+        use crate::to_values;
+
+        assert_eq!(
+            to_values(vec![1, 2, 3]).unwrap(),
+            [Value::from(1), Value::from(2), Value::from(3)]
+        );
+        assert_eq!(
+            to_values(("a", 0.5, true)).unwrap(),
+            [Value::from("a"), Value::from(0.5), Value::from(true)]
+        );
+        assert_eq!(to_values(Some([1u64])).unwrap(), [Value::from(1u64)]);
+        assert_eq!(to_values(Vec::<i64>::new()).unwrap(), []);
+
+        assert!(to_values(1).is_err());
+        assert!(to_values(vec![vec![1]]).is_err());
+        assert!(crate::to_value(vec![1]).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_ser_arbitrary_points() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Simple linear congruential generator, enough to vary the input
+        let mut state = 0x2545f4914f6cdd1du64;
+        let bytes: Vec<u8> = (0..1 << 16)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 56) as u8
+            })
+            .collect();
+
+        // Unsigned integers are written with their own suffix to keep their type
+        let options = Options::new().unsigned_suffix(true);
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..100 {
+            let point = Point::arbitrary(&mut u).unwrap();
+            let line = to_string_with_options(&point, &options).unwrap();
+            let parsed: Point = line.parse().unwrap();
+            assert_eq!(parsed, point, "{line}");
+        }
+
+        assert!(Value::arbitrary(&mut u).is_ok());
+        assert!(Number::arbitrary(&mut u).is_ok());
+    }
 }
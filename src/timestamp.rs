@@ -0,0 +1,525 @@
+//! Conversions between line protocol timestamps and the types used to
+//! represent them
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Precision;
+
+/// Name used by the serializer and deserializer to recognize [Timestamp]
+pub(crate) const NAME: &str = "$serde_influxlp::Timestamp";
+
+/// A line protocol timestamp, stored as a unix timestamp in nanoseconds
+///
+/// Unlike a plain i64 the precision is part of the type. When used as the
+/// timestamp element it is converted from and into the precision configured
+/// in [de::Options](crate::de::Options) and [ser::Options](crate::ser::Options)
+/// automatically. With other formats it is serialized as nanoseconds
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: HashMap<String, Value>,
+///
+///     pub timestamp: Timestamp,
+/// }
+///
+/// let metric = Metric {
+///     measurement: "metric1".to_string(),
+///     fields: HashMap::from([("field1".to_string(), Value::from(1))]),
+///     timestamp: Timestamp::from_secs(1704067200).unwrap(),
+/// };
+///
+/// let options = ser::Options::new().precision(Precision::Milliseconds);
+/// let line = serde_influxlp::to_string_with_options(&metric, &options).unwrap();
+/// // Output: metric1 field1=1i 1704067200000
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// The unix epoch, 1970-01-01T00:00:00Z
+    pub const UNIX_EPOCH: Timestamp = Timestamp(0);
+
+    /// Create a timestamp from a unix timestamp in the given precision
+    ///
+    /// Returns None if the timestamp does not fit in nanoseconds
+    pub const fn new(timestamp: i64, precision: Precision) -> Option<Self> {
+        match precision.convert(timestamp, Precision::Nanoseconds) {
+            Some(nanos) => Some(Timestamp(nanos)),
+            None => None,
+        }
+    }
+
+    /// Create a timestamp from a unix timestamp in seconds
+    pub const fn from_secs(secs: i64) -> Option<Self> {
+        Self::new(secs, Precision::Seconds)
+    }
+
+    /// Create a timestamp from a unix timestamp in milliseconds
+    pub const fn from_millis(millis: i64) -> Option<Self> {
+        Self::new(millis, Precision::Milliseconds)
+    }
+
+    /// Create a timestamp from a unix timestamp in microseconds
+    pub const fn from_micros(micros: i64) -> Option<Self> {
+        Self::new(micros, Precision::Microseconds)
+    }
+
+    /// Create a timestamp from a unix timestamp in nanoseconds
+    pub const fn from_nanos(nanos: i64) -> Self {
+        Timestamp(nanos)
+    }
+
+    /// The current system time
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system time is not representable, i.e., before the unix
+    /// epoch or after the year 2262
+    pub fn now() -> Self {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| i64::try_from(d.as_nanos()).ok())
+            .map(Timestamp)
+            .expect("system time is out of the timestamp range")
+    }
+
+    /// The timestamp in the given precision, truncated if needed
+    pub const fn to_precision(&self, precision: Precision) -> i64 {
+        match Precision::Nanoseconds.convert(self.0, precision) {
+            Some(timestamp) => timestamp,
+            // Converting into a coarser precision never overflows
+            None => unreachable!(),
+        }
+    }
+
+    /// The timestamp in seconds, truncated
+    pub const fn as_secs(&self) -> i64 {
+        self.to_precision(Precision::Seconds)
+    }
+
+    /// The timestamp in milliseconds, truncated
+    pub const fn as_millis(&self) -> i64 {
+        self.to_precision(Precision::Milliseconds)
+    }
+
+    /// The timestamp in microseconds, truncated
+    pub const fn as_micros(&self) -> i64 {
+        self.to_precision(Precision::Microseconds)
+    }
+
+    /// The timestamp in nanoseconds
+    pub const fn as_nanos(&self) -> i64 {
+        self.0
+    }
+
+    /// Add `duration` to the timestamp, returning None on overflow
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        let nanos = i64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_add(nanos).map(Timestamp)
+    }
+
+    /// Subtract `duration` from the timestamp, returning None on overflow
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        let nanos = i64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_sub(nanos).map(Timestamp)
+    }
+
+    /// The amount of time elapsed from `earlier` to this timestamp, or None
+    /// if `earlier` is later than this timestamp
+    pub fn duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        let nanos = self.0.checked_sub(earlier.0)?;
+        u64::try_from(nanos).ok().map(Duration::from_nanos)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see [Timestamp::checked_add] for a non panicking
+    /// version
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// # Panics
+    ///
+    /// Panics on overflow, see [Timestamp::checked_sub] for a non panicking
+    /// version
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl From<Timestamp> for i64 {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TimestampVisitor;
+
+        impl<'de> de::Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a unix timestamp in nanoseconds")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                i64::deserialize(deserializer).map(Timestamp)
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Timestamp(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                i64::try_from(v)
+                    .map(Timestamp)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(NAME, TimestampVisitor)
+    }
+}
+
+/// Serialize and deserialize a `chrono::DateTime<Utc>` as a line protocol
+/// timestamp
+///
+/// Used with serde's `with` attribute. The date and time is converted from and
+/// into the precision configured in [de::Options](crate::de::Options) and
+/// [ser::Options](crate::ser::Options) like a [Timestamp]. With other formats
+/// it is serialized as nanoseconds
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: HashMap<String, Value>,
+///
+///     #[serde(with = "serde_influxlp::ts_chrono")]
+///     pub timestamp: DateTime<Utc>,
+/// }
+/// ```
+#[cfg(feature = "chrono")]
+pub mod ts_chrono {
+    use chrono::{DateTime, Utc};
+    use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Timestamp;
+
+    pub fn serialize<S>(dt: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match dt.timestamp_nanos_opt() {
+            Some(nanos) => Timestamp::from_nanos(nanos).serialize(serializer),
+            None => Err(ser::Error::custom(format!(
+                "date and time `{dt}` is out of the timestamp range"
+            ))),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let timestamp = Timestamp::deserialize(deserializer)?;
+        Ok(DateTime::from_timestamp_nanos(timestamp.as_nanos()))
+    }
+
+    /// Same as the parent module but for optional date and times
+    ///
+    /// A missing timestamp is only deserialized as None if the field is also
+    /// marked with `#[serde(default)]`
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Timestamp;
+
+        pub fn serialize<S>(dt: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match dt {
+                Some(dt) => super::serialize(dt, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let timestamp = Option::<Timestamp>::deserialize(deserializer)?;
+            Ok(timestamp.map(|t| DateTime::from_timestamp_nanos(t.as_nanos())))
+        }
+    }
+}
+
+/// Serialize and deserialize a `time::OffsetDateTime` as a line protocol
+/// timestamp
+///
+/// Used with serde's `with` attribute, see [ts_chrono] for the details, and
+/// deserializes into UTC
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Metric {
+///     pub measurement: String,
+///
+///     pub fields: HashMap<String, Value>,
+///
+///     #[serde(with = "serde_influxlp::ts_time")]
+///     pub timestamp: OffsetDateTime,
+/// }
+/// ```
+#[cfg(feature = "time")]
+pub mod ts_time {
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use time::OffsetDateTime;
+
+    use super::Timestamp;
+
+    pub fn serialize<S>(dt: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match i64::try_from(dt.unix_timestamp_nanos()) {
+            Ok(nanos) => Timestamp::from_nanos(nanos).serialize(serializer),
+            Err(_) => Err(ser::Error::custom(format!(
+                "date and time `{dt}` is out of the timestamp range"
+            ))),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let timestamp = Timestamp::deserialize(deserializer)?;
+        from_timestamp(timestamp)
+    }
+
+    fn from_timestamp<E>(timestamp: Timestamp) -> Result<OffsetDateTime, E>
+    where
+        E: de::Error,
+    {
+        OffsetDateTime::from_unix_timestamp_nanos(timestamp.as_nanos().into())
+            .map_err(|_| de::Error::custom(format!("timestamp `{timestamp}` is out of range")))
+    }
+
+    /// Same as the parent module but for optional date and times
+    ///
+    /// A missing timestamp is only deserialized as None if the field is also
+    /// marked with `#[serde(default)]`
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use time::OffsetDateTime;
+
+        use super::Timestamp;
+
+        pub fn serialize<S>(dt: &Option<OffsetDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match dt {
+                Some(dt) => super::serialize(dt, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<OffsetDateTime>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<Timestamp>::deserialize(deserializer)?
+                .map(super::from_timestamp)
+                .transpose()
+        }
+    }
+}
+
+macro_rules! timestamp_module {
+    ($(#[$doc:meta])* $name:ident, $precision:expr) => {
+        $(#[$doc])*
+        pub mod $name {
+            use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+            use crate::Precision;
+
+            pub fn serialize<S>(timestamp: &i64, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                match $precision.convert(*timestamp, Precision::Nanoseconds) {
+                    Some(timestamp) => serializer.serialize_i64(timestamp),
+                    None => Err(ser::Error::custom(format!(
+                        "timestamp `{timestamp}` is out of range"
+                    ))),
+                }
+            }
+
+            pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let timestamp = i64::deserialize(deserializer)?;
+                Precision::Nanoseconds
+                    .convert(timestamp, $precision)
+                    .ok_or_else(|| {
+                        de::Error::custom(format!("timestamp `{timestamp}` is out of range"))
+                    })
+            }
+
+            /// Same as the parent module but for optional timestamps
+            ///
+            /// A missing timestamp is only deserialized as None if the field is
+            /// also marked with `#[serde(default)]`
+            pub mod option {
+                use serde::{de, Deserialize, Deserializer, Serializer};
+
+                use crate::Precision;
+
+                pub fn serialize<S>(
+                    timestamp: &Option<i64>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error>
+                where
+                    S: Serializer,
+                {
+                    match timestamp {
+                        Some(timestamp) => super::serialize(timestamp, serializer),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    match Option::<i64>::deserialize(deserializer)? {
+                        Some(timestamp) => Precision::Nanoseconds
+                            .convert(timestamp, $precision)
+                            .map(Some)
+                            .ok_or_else(|| {
+                                de::Error::custom(format!(
+                                    "timestamp `{timestamp}` is out of range"
+                                ))
+                            }),
+                        None => Ok(None),
+                    }
+                }
+            }
+        }
+    };
+}
+
+timestamp_module! {
+    /// Serialize and deserialize an i64 timestamp stored in seconds as a line
+    /// protocol timestamp in nanoseconds
+    ///
+    /// Used with serde's `with` attribute
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// pub struct Metric {
+    ///     pub measurement: String,
+    ///
+    ///     pub fields: HashMap<String, Value>,
+    ///
+    ///     #[serde(with = "serde_influxlp::ts_seconds")]
+    ///     pub timestamp: i64,
+    /// }
+    /// ```
+    ts_seconds,
+    Precision::Seconds
+}
+
+timestamp_module! {
+    /// Serialize and deserialize an i64 timestamp stored in milliseconds as a
+    /// line protocol timestamp in nanoseconds
+    ///
+    /// See [ts_seconds] for an example
+    ts_millis,
+    Precision::Milliseconds
+}
+
+timestamp_module! {
+    /// Serialize and deserialize an i64 timestamp stored in microseconds as a
+    /// line protocol timestamp in nanoseconds
+    ///
+    /// See [ts_seconds] for an example
+    ts_micros,
+    Precision::Microseconds
+}
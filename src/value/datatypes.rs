@@ -1,10 +1,19 @@
-use std::{fmt::Display, hash::Hash};
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::Hash,
+    num::{
+        NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+        NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+    ops::{Add, Div, Mul, Sub},
+    str::FromStr,
+};
 
 use conv::*;
-use regex::Regex;
-use serde::de;
+use serde::de::{self, Unexpected};
 
-use crate::error::Error;
+use crate::{builder, error::Error, reader::datatypes::Position};
 
 #[derive(Debug, Clone)]
 pub enum Number {
@@ -18,10 +27,15 @@ pub enum Number {
     UInteger(u64),
 }
 
+/// Floats are equal if [f64::total_cmp] considers them equal, except that
+/// `-0.0` equals `0.0`, which is consistent with [Ord] and [Hash]. Unlike with
+/// f64, NaN therefore equals itself
 impl PartialEq for Number {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Number::Float(n1), Number::Float(n2)) => n1 == n2,
+            (Number::Float(n1), Number::Float(n2)) => {
+                normalize_zero(*n1).total_cmp(&normalize_zero(*n2)).is_eq()
+            }
             (Number::Integer(n1), Number::Integer(n2)) => n1 == n2,
             (Number::UInteger(n1), Number::UInteger(n2)) => n1 == n2,
             _ => false,
@@ -34,28 +48,129 @@ impl Eq for Number {}
 impl Hash for Number {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match *self {
-            Number::Float(n) => {
-                match n == 0.0 {
-                    true => 0.0f64.to_bits(),
-                    false => n.to_bits(),
-                }
-                .hash(state);
-            }
+            Number::Float(n) => normalize_zero(n).to_bits().hash(state),
             Number::Integer(n) => n.hash(state),
             Number::UInteger(n) => n.hash(state),
         }
     }
 }
 
-impl Display for Number {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let number = match self {
-            Number::Float(n) => format!("{n}"),
-            Number::Integer(n) => format!("{n}i"),
-            Number::UInteger(n) => format!("{n}i"),
+/// Numbers are ordered by their numeric value, regardless of their type
+///
+/// Floats are compared with [f64::total_cmp], except that `-0.0` equals
+/// `0.0`, so NaN is larger than any other number, or smaller if negative.
+/// Numbers of different types with the same value are ordered floats first,
+/// then integers, then unsigned integers, as they are not equal
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = match (self, other) {
+            (Number::Float(n1), Number::Float(n2)) => {
+                normalize_zero(*n1).total_cmp(&normalize_zero(*n2))
+            }
+            (Number::Float(n1), n2) => cmp_float_int(*n1, n2.as_i128()),
+            (n1, Number::Float(n2)) => cmp_float_int(*n2, n1.as_i128()).reverse(),
+            (n1, n2) => n1.as_i128().cmp(&n2.as_i128()),
         };
 
-        write!(f, "{number}")
+        ordering.then_with(|| self.rank().cmp(&other.rank()))
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Implements an arithmetic operator for numbers with its checked version
+///
+/// Results which do not fit an integer are computed with floats instead
+macro_rules! impl_number_op {
+    ($trait:ident, $method:ident, $checked:ident, $op:tt) => {
+        impl $trait for Number {
+            type Output = Number;
+
+            /// Follows the rules of the checked version, except that a result
+            /// which does not fit either integer type is computed as a float,
+            /// losing precision
+            fn $method(self, rhs: Number) -> Number {
+                self.$checked(&rhs)
+                    .unwrap_or_else(|| Number::Float(self.to_f64() $op rhs.to_f64()))
+            }
+        }
+    };
+}
+
+impl_number_op!(Add, add, checked_add, +);
+impl_number_op!(Sub, sub, checked_sub, -);
+impl_number_op!(Mul, mul, checked_mul, *);
+
+impl Div for Number {
+    type Output = Number;
+
+    /// Follows the rules of [Number::checked_div], except that a result which
+    /// does not fit either integer type is computed as a float, and dividing a
+    /// float by zero results in an infinite float or NaN like f64 does
+    ///
+    /// # Panics
+    ///
+    /// Panics if both numbers are integers and `rhs` is zero, like integer
+    /// division does
+    fn div(self, rhs: Number) -> Number {
+        if !self.is_float() && !rhs.is_float() && rhs.as_i128() == 0 {
+            panic!("attempt to divide by zero");
+        }
+
+        self.checked_div(&rhs)
+            .unwrap_or_else(|| Number::Float(self.to_f64() / rhs.to_f64()))
+    }
+}
+
+/// Replace `-0.0` with `0.0`, as they are equal
+fn normalize_zero(n: f64) -> f64 {
+    match n == 0.0 {
+        true => 0.0,
+        false => n,
+    }
+}
+
+/// Compare a float and an integer exactly, without converting the integer into
+/// a float
+fn cmp_float_int(float: f64, int: i128) -> Ordering {
+    if float.is_nan() {
+        return match float.is_sign_negative() {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        };
+    }
+
+    // Every integer lies within the range of a 64 bit signed or unsigned integer
+    if float >= 18446744073709551616.0 {
+        return Ordering::Greater;
+    }
+    if float < -9223372036854775808.0 {
+        return Ordering::Less;
+    }
+
+    let whole = float.trunc();
+    (whole as i128)
+        .cmp(&int)
+        .then_with(|| (float - whole).partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+}
+
+/// Formats the number in its line protocol representation
+///
+/// Unsigned integers are formatted with the `i` suffix, as signed integers,
+/// unless the alternate flag is used, e.g., `{:#}`, in which case the `u`
+/// suffix is used instead
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Float(n) => write!(f, "{n}"),
+            Number::Integer(n) => write!(f, "{n}i"),
+            Number::UInteger(n) if f.alternate() => write!(f, "{n}u"),
+            Number::UInteger(n) => write!(f, "{n}i"),
+        }
     }
 }
 
@@ -159,6 +274,175 @@ impl Number {
         }
     }
 
+    /// Scales the number by `factor`
+    ///
+    /// Integers multiplied by an integer factor keep their type as long as
+    /// the result is exact, i.e., fits within the range of the type. In all
+    /// other cases the result is a float
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let number = Number::Integer(1500);
+    ///
+    /// println!("{:?}", number.scale(1000));
+    /// // Output: Integer(1500000)
+    ///
+    /// println!("{:?}", number.scale(0.001));
+    /// // Output: Float(1.5)
+    /// ```
+    pub fn scale(&self, factor: impl Into<Number>) -> Number {
+        let factor = factor.into();
+
+        let exact = match (self, &factor) {
+            (Number::Integer(n), Number::Integer(f)) => n.checked_mul(*f).map(Number::Integer),
+            (Number::Integer(n), Number::UInteger(f)) => i64::value_from(*f)
+                .ok()
+                .and_then(|f| n.checked_mul(f))
+                .map(Number::Integer),
+            (Number::UInteger(n), Number::UInteger(f)) => n.checked_mul(*f).map(Number::UInteger),
+            (Number::UInteger(n), Number::Integer(f)) => u64::value_from(*f)
+                .ok()
+                .and_then(|f| n.checked_mul(f))
+                .map(Number::UInteger),
+            _ => None,
+        };
+
+        exact.unwrap_or_else(|| Number::Float(self.to_f64() * factor.to_f64()))
+    }
+
+    /// Adds `rhs` to the number
+    ///
+    /// Rules:
+    /// 1. If either number is a float, the result is a float
+    /// 2. Otherwise the result has the type of the number, or the other integer
+    ///    type if it does not fit, e.g., `1u - 2i` is `-1i`
+    /// 3. None is returned if the result does not fit either integer type
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let number = Number::UInteger(2).checked_add(&Number::Float(0.5));
+    ///
+    /// println!("{number:?}");
+    /// // Output: Some(Float(2.5))
+    /// ```
+    pub fn checked_add(&self, rhs: &Number) -> Option<Number> {
+        self.checked_op(rhs, i128::checked_add, |a, b| a + b)
+    }
+
+    /// Subtracts `rhs` from the number, see [Number::checked_add] for the
+    /// rules on the type of the result
+    pub fn checked_sub(&self, rhs: &Number) -> Option<Number> {
+        self.checked_op(rhs, i128::checked_sub, |a, b| a - b)
+    }
+
+    /// Multiplies the number by `rhs`, see [Number::checked_add] for the rules
+    /// on the type of the result
+    pub fn checked_mul(&self, rhs: &Number) -> Option<Number> {
+        self.checked_op(rhs, i128::checked_mul, |a, b| a * b)
+    }
+
+    /// Divides the number by `rhs`, see [Number::checked_add] for the rules on
+    /// the type of the result. Division of integers is truncated, and None
+    /// is returned when dividing by zero, including floats
+    pub fn checked_div(&self, rhs: &Number) -> Option<Number> {
+        if rhs.to_f64() == 0.0 {
+            return None;
+        }
+
+        self.checked_op(rhs, i128::checked_div, |a, b| a / b)
+    }
+
+    /// Applies an arithmetic operation to the number and `rhs`, see
+    /// [Number::checked_add]
+    fn checked_op(
+        &self,
+        rhs: &Number,
+        int: fn(i128, i128) -> Option<i128>,
+        float: fn(f64, f64) -> f64,
+    ) -> Option<Number> {
+        if self.is_float() || rhs.is_float() {
+            return Some(Number::Float(float(self.to_f64(), rhs.to_f64())));
+        }
+
+        let n = int(self.as_i128(), rhs.as_i128())?;
+        let integer = (i64::MIN as i128..=i64::MAX as i128)
+            .contains(&n)
+            .then_some(Number::Integer(n as i64));
+        let uinteger = (0..=u64::MAX as i128)
+            .contains(&n)
+            .then_some(Number::UInteger(n as u64));
+
+        match self {
+            Number::UInteger(_) => uinteger.or(integer),
+            _ => integer.or(uinteger),
+        }
+    }
+
+    /// Lossy conversion of the inner value into a f64
+    fn to_f64(&self) -> f64 {
+        match *self {
+            Number::Float(n) => n,
+            Number::Integer(n) => n as f64,
+            Number::UInteger(n) => n as f64,
+        }
+    }
+
+    /// The integer value of an integer or unsigned integer, floats are
+    /// truncated
+    fn as_i128(&self) -> i128 {
+        match *self {
+            Number::Float(n) => n as i128,
+            Number::Integer(n) => n.into(),
+            Number::UInteger(n) => n.into(),
+        }
+    }
+
+    /// Position of the type of the number when ordering numbers of equal
+    /// value
+    fn rank(&self) -> u8 {
+        match self {
+            Number::Float(_) => 0,
+            Number::Integer(_) => 1,
+            Number::UInteger(_) => 2,
+        }
+    }
+
+    /// Convert a 128 bit integer into a number, or None if it does not fit
+    /// a line protocol integer or unsigned integer
+    ///
+    /// Negative numbers become integers, and positive numbers unsigned
+    /// integers only when they are too large for an integer
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// println!("{:?}", Number::from_i128(-12));
+    /// // Output: Some(Integer(-12))
+    ///
+    /// println!("{:?}", Number::from_i128(i128::MAX));
+    /// // Output: None
+    /// ```
+    pub fn from_i128(n: i128) -> Option<Number> {
+        if (i64::MIN as i128..=i64::MAX as i128).contains(&n) {
+            Some(Number::Integer(n as i64))
+        } else if (0..=u64::MAX as i128).contains(&n) {
+            Some(Number::UInteger(n as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Convert a 128 bit unsigned integer into an unsigned integer, or None if
+    /// it does not fit a line protocol unsigned integer
+    pub fn from_u128(n: u128) -> Option<Number> {
+        match n <= u64::MAX as u128 {
+            true => Some(Number::UInteger(n as u64)),
+            false => None,
+        }
+    }
+
     /// An alternative to [Values](Value) `to_string` function. Instead uses the
     /// inner values `to_string` function to convert self to a string.
     pub fn as_string(&self) -> String {
@@ -177,6 +461,148 @@ impl Number {
     }
 }
 
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number::Float(n)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        Number::Integer(n)
+    }
+}
+
+impl From<i32> for Number {
+    fn from(n: i32) -> Self {
+        Number::Integer(n.into())
+    }
+}
+
+impl From<u64> for Number {
+    fn from(n: u64) -> Self {
+        Number::UInteger(n)
+    }
+}
+
+impl From<f32> for Number {
+    fn from(n: f32) -> Self {
+        Number::Float(n.into())
+    }
+}
+
+impl From<i8> for Number {
+    fn from(n: i8) -> Self {
+        Number::Integer(n.into())
+    }
+}
+
+impl From<i16> for Number {
+    fn from(n: i16) -> Self {
+        Number::Integer(n.into())
+    }
+}
+
+impl From<isize> for Number {
+    fn from(n: isize) -> Self {
+        Number::Integer(n as i64)
+    }
+}
+
+impl From<u8> for Number {
+    fn from(n: u8) -> Self {
+        Number::UInteger(n.into())
+    }
+}
+
+impl From<u16> for Number {
+    fn from(n: u16) -> Self {
+        Number::UInteger(n.into())
+    }
+}
+
+impl From<u32> for Number {
+    fn from(n: u32) -> Self {
+        Number::UInteger(n.into())
+    }
+}
+
+impl From<usize> for Number {
+    fn from(n: usize) -> Self {
+        Number::UInteger(n as u64)
+    }
+}
+
+/// The data types supported by InfluxDB v2 Line protocol field values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LpType {
+    Float,
+
+    Integer,
+
+    UInteger,
+
+    String,
+
+    Boolean,
+}
+
+impl LpType {
+    /// Returns the type of the value, or None if the value is [Value::None]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value = Value::from(12.5);
+    ///
+    /// println!("{:?}", LpType::of(&value));
+    /// // Output: Some(Float)
+    /// ```
+    pub fn of(value: &Value) -> Option<LpType> {
+        let typ = match value {
+            Value::None => return None,
+            Value::Number(Number::Float(_)) => LpType::Float,
+            Value::Number(Number::Integer(_)) => LpType::Integer,
+            Value::Number(Number::UInteger(_)) => LpType::UInteger,
+            Value::String(_) => LpType::String,
+            Value::Boolean(_) => LpType::Boolean,
+        };
+
+        Some(typ)
+    }
+
+    /// Detects the type of a raw line protocol token using the same rules as
+    /// the deserializer does when inferring the type of a value
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// println!("{:?}", LpType::detect("12i"));
+    /// // Output: Integer
+    ///
+    /// println!("{:?}", LpType::detect("hello"));
+    /// // Output: String
+    /// ```
+    pub fn detect(token: &str) -> LpType {
+        // An inferred value is never `Value::None` so a type will always be found
+        LpType::of(&Value::from_any_str(token)).unwrap_or(LpType::String)
+    }
+}
+
+impl Display for LpType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let typ = match self {
+            LpType::Float => "float",
+            LpType::Integer => "integer",
+            LpType::UInteger => "unsigned integer",
+            LpType::String => "string",
+            LpType::Boolean => "boolean",
+        };
+
+        write!(f, "{typ}")
+    }
+}
+
 /// Represents any supported InfluxDB v2 Line protocol value
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Value {
@@ -197,39 +623,32 @@ pub enum Value {
 }
 
 impl Value {
+    /// Parse a number as written in the line protocol
+    ///
+    /// Integers end with an `i`, unsigned integers with an `u`, and other
+    /// numbers are floats. Integers ending with an `i` which are too large for
+    /// an integer are unsigned integers, as written without
+    /// [unsigned_suffix](crate::ser::Options::unsigned_suffix)
     pub(crate) fn from_number_str(s: &str) -> Option<Self> {
-        let mut value = s.to_string();
-
-        // Check if string is a number that ends with an i
-        let re = Regex::new(r"^-?\d+i$").unwrap();
-        let number = match re.is_match(&value) {
-            true => {
-                // Remove the `i`
-                value.pop();
-
-                match value.starts_with("-") {
-                    true => {
-                        let number = match value.parse() {
-                            Ok(number) => number,
-                            Err(_) => return None,
-                        };
-
-                        Number::Integer(number)
-                    }
-                    false => {
-                        let number = match value.parse() {
-                            Ok(number) => number,
-                            Err(_) => return None,
-                        };
-
-                        Number::UInteger(number)
-                    }
-                }
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit());
+        let number = if let Some(value) = s.strip_suffix('i') {
+            match (
+                is_digits(value.strip_prefix('-').unwrap_or(value)),
+                value.parse(),
+            ) {
+                (true, Ok(n)) => Number::Integer(n),
+                (true, Err(_)) => Number::UInteger(value.parse().ok()?),
+                (false, _) => return None,
             }
-            false => match value.parse::<f64>() {
-                Ok(value) => Number::Float(value),
-                Err(_) => return None,
-            },
+        } else if let Some(value) = s.strip_suffix('u') {
+            match is_digits(value) {
+                true => Number::UInteger(value.parse().ok()?),
+                false => return None,
+            }
+        } else if is_float_str(s) {
+            Number::Float(s.parse().ok()?)
+        } else {
+            return None;
         };
 
         Some(Value::Number(number))
@@ -245,6 +664,38 @@ impl Value {
         Some(Value::Boolean(bool))
     }
 
+    /// Parse the string as the given type, ignoring what type the string
+    /// would normally be inferred as
+    pub(crate) fn from_typed_str(s: &str, typ: LpType) -> Option<Self> {
+        // Numbers are allowed to keep their type suffix
+        let number = s.strip_suffix(['i', 'u']).unwrap_or(s);
+
+        let value = match typ {
+            LpType::Float if is_float_str(number) => {
+                Value::Number(Number::Float(number.parse().ok()?))
+            }
+            LpType::Float => return None,
+            LpType::Integer => Value::Number(Number::Integer(number.parse().ok()?)),
+            LpType::UInteger => Value::Number(Number::UInteger(number.parse().ok()?)),
+            LpType::String => Value::String(s.to_owned()),
+            LpType::Boolean => Value::from_bool_str(s)?,
+        };
+
+        Some(value)
+    }
+
+    /// Parse a field value as written in the line protocol, keeping the type
+    /// it is written with instead of inferring one
+    ///
+    /// Quoted strings are strings, numbers are parsed as by
+    /// [Value::from_number_str]
+    pub(crate) fn from_field_str(s: &str) -> Option<Self> {
+        match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(quoted) => unescape_string(quoted).map(Value::String),
+            None => Value::from_number_str(s).or_else(|| Value::from_bool_str(s)),
+        }
+    }
+
     pub(crate) fn from_any_str(s: &str) -> Value {
         let mut char = s.chars();
         let char = match char.next() {
@@ -261,6 +712,18 @@ impl Value {
         value.unwrap_or(Value::String(s.to_owned()))
     }
 
+    /// The value as an unexpected value in a serde error
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::None => Unexpected::Unit,
+            Value::Number(Number::Float(n)) => Unexpected::Float(*n),
+            Value::Number(Number::Integer(n)) => Unexpected::Signed(*n),
+            Value::Number(Number::UInteger(n)) => Unexpected::Unsigned(*n),
+            Value::String(s) => Unexpected::Str(s),
+            Value::Boolean(b) => Unexpected::Bool(*b),
+        }
+    }
+
     pub(crate) fn visit<'de, V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
@@ -274,17 +737,192 @@ impl Value {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Number {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let number = match u.int_in_range(0..=2)? {
+            0 => Number::Float(u.arbitrary()?),
+            1 => Number::Integer(u.arbitrary()?),
+            _ => Number::UInteger(u.arbitrary()?),
+        };
+
+        Ok(number)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let value = match u.int_in_range(0..=3)? {
+            0 => Value::None,
+            1 => Value::Number(u.arbitrary()?),
+            2 => Value::String(u.arbitrary()?),
+            _ => Value::Boolean(u.arbitrary()?),
+        };
+
+        Ok(value)
+    }
+}
+
+/// Any JSON number, integers and floats alike
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Number {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Number".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "number",
+        })
+    }
+}
+
+/// Null for [Value::None], or a boolean, number, or string
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Value {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Value".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": ["null", "boolean", "number", "string"],
+        })
+    }
+}
+
+/// Implements `TryFrom<Value>` for a primitive, failing for values of any
+/// other type
+macro_rules! impl_try_from_value {
+    ($typ:ty, $expected:literal, $($pattern:pat => $value:expr),*) => {
+        impl std::convert::TryFrom<Value> for $typ {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    $($pattern => $value,)*
+                    value => Err(de::Error::invalid_type(value.unexpected(), &$expected)),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_value!(
+    i64, "an integer",
+    Value::Number(Number::Integer(n)) => Ok(n),
+    Value::Number(Number::UInteger(n)) => match n <= i64::MAX as u64 {
+        true => Ok(n as i64),
+        false => Err(de::Error::invalid_value(Unexpected::Unsigned(n), &"an integer")),
+    }
+);
+
+impl_try_from_value!(
+    u64, "an unsigned integer",
+    Value::Number(Number::UInteger(n)) => Ok(n),
+    Value::Number(Number::Integer(n)) => match n >= 0 {
+        true => Ok(n as u64),
+        false => Err(de::Error::invalid_value(Unexpected::Signed(n), &"an unsigned integer")),
+    }
+);
+
+impl_try_from_value!(f64, "a float", Value::Number(Number::Float(n)) => Ok(n));
+
+impl_try_from_value!(bool, "a boolean", Value::Boolean(b) => Ok(b));
+
+impl_try_from_value!(String, "a string", Value::String(s) => Ok(s));
+
+/// Values are ordered by their type first, [Value::None], booleans, numbers,
+/// and then strings. Values of the same type are ordered by their inner value,
+/// see [Number] for how numbers are ordered
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let rank = |value: &Value| match value {
+            Value::None => 0,
+            Value::Boolean(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+        };
+
+        match (self, other) {
+            (Value::Boolean(b1), Value::Boolean(b2)) => b1.cmp(b2),
+            (Value::Number(n1), Value::Number(n2)) => n1.cmp(n2),
+            (Value::String(s1), Value::String(s2)) => s1.cmp(s2),
+            (v1, v2) => rank(v1).cmp(&rank(v2)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let value = match self {
-            Value::None => String::new(),
-            Value::Number(n) => n.to_string(),
-            Value::String(s) => s.to_string(),
-            Value::Boolean(b) => b.to_string(),
-        };
+        match self {
+            Value::None => Ok(()),
+            // Pass on the alternate flag, see [Number]
+            Value::Number(n) if f.alternate() => write!(f, "{n:#}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "{s}"),
+            Value::Boolean(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    /// Parse a field value as written in the line protocol, e.g., `12i`,
+    /// `3.5`, `t`, or `"a \"quoted\" string"`
+    ///
+    /// Numbers keep the type they are written with, the same way as the
+    /// fields of a [Point](crate::Point) do. Strings have to be quoted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value: Value = "12i".parse().unwrap();
+    ///
+    /// println!("{value:?}");
+    /// // Output: Number(Integer(12))
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Value::from_field_str(s).ok_or_else(|| {
+            let position = Position {
+                column: s.len(),
+                ..Position::new()
+            };
+            Error::invalid_value(s, position)
+        })
+    }
+}
 
-        write!(f, "{}", value)
+/// Remove the escape characters from the contents of a quoted string field
+/// value, or None if it contains an unescaped quote
+fn unescape_string(s: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c @ ('"' | '\\')) => unescaped.push(c),
+                Some(c) => {
+                    unescaped.push('\\');
+                    unescaped.push(c);
+                }
+                // The closing quote is escaped
+                None => return None,
+            },
+            '"' => return None,
+            c => unescaped.push(c),
+        }
     }
+
+    Some(unescaped)
 }
 
 impl From<char> for Value {
@@ -371,14 +1009,85 @@ impl From<u64> for Value {
     }
 }
 
+impl From<isize> for Value {
+    fn from(n: isize) -> Self {
+        Value::Number(n.into())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Number(n.into())
+    }
+}
+
 impl From<bool> for Value {
     fn from(n: bool) -> Self {
         Value::Boolean(n)
     }
 }
 
+impl From<&bool> for Value {
+    fn from(n: &bool) -> Self {
+        Value::Boolean(*n)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(n: Number) -> Self {
+        Value::Number(n)
+    }
+}
+
+/// None becomes [Value::None]
+impl<T> From<Option<T>> for Value
+where
+    T: Into<Value>,
+{
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::None, Into::into)
+    }
+}
+
+/// Implements `From` for references to the given primitives, and for their
+/// non-zero versions, for types the primitives already convert into
+macro_rules! impl_from_primitives {
+    ($target:ty; $($typ:ty),*; $($nonzero:ty),*) => {
+        $(
+            impl From<&$typ> for $target {
+                fn from(n: &$typ) -> Self {
+                    (*n).into()
+                }
+            }
+        )*
+
+        $(
+            impl From<$nonzero> for $target {
+                fn from(n: $nonzero) -> Self {
+                    n.get().into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitives!(
+    Number;
+    f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroIsize,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize
+);
+
+impl_from_primitives!(
+    Value;
+    f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroIsize,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize
+);
+
 impl Value {
     /// Converts this type into a shared reference of itself
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &Self {
         self
     }
@@ -487,6 +1196,26 @@ impl Value {
         matches!(self, Value::String(_))
     }
 
+    /// Borrows the inner string if the value is a string
+    ///
+    /// Unlike [Value::as_string] no other types are converted, and nothing is
+    /// allocated
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value = Value::from("server01");
+    ///
+    /// println!("{:?}", value.as_str());
+    /// // Output: Some("server01")
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
     /// Converts the inner value of self into a string and returns it
     ///
     /// This should not be confused with the Values' `to_string`
@@ -517,6 +1246,66 @@ impl Value {
         }
     }
 
+    /// Formats the value as a tag value, the way the serializer writes it
+    ///
+    /// Commas, equal signs, and spaces are escaped with a backslash
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value = Value::from("eu west,1");
+    ///
+    /// println!("{}", value.to_tag_value_string());
+    /// // Output: eu\ west\,1
+    /// ```
+    pub fn to_tag_value_string(&self) -> String {
+        let mut buf = Vec::new();
+        builder::write_tag_value(self.clone(), &mut buf);
+        // Only valid utf8 has been escaped
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// Formats the value as a field value, the way the serializer writes it
+    ///
+    /// Strings are quoted, with quotes and backslashes escaped with a
+    /// backslash, and numbers are written with their type suffix
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value = Value::from(r#"a "quoted" string"#);
+    ///
+    /// println!("{}", value.to_field_value_string());
+    /// // Output: "a \"quoted\" string"
+    /// ```
+    pub fn to_field_value_string(&self) -> String {
+        let mut buf = Vec::new();
+        builder::write_field_value(self.clone(), &mut buf);
+        // Only valid utf8 has been escaped
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// Scales the value by `factor` if it is a number. Returns None for any
+    /// other type of value
+    ///
+    /// See [Number::scale] for the rules on how the type of the number is
+    /// preserved
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let value = Value::from(2097152u64);
+    ///
+    /// println!("{:?}", value.scale(1.0 / 1048576.0));
+    /// // Output: Some(Number(Float(2.0)))
+    /// ```
+    pub fn scale(&self, factor: impl Into<Number>) -> Option<Value> {
+        match self {
+            Value::Number(n) => Some(Value::Number(n.scale(factor))),
+            _ => None,
+        }
+    }
+
     /// Checks if value is a float
     pub fn is_bool(&self) -> bool {
         matches!(self, Value::Boolean(_))
@@ -559,3 +1348,243 @@ impl Value {
         }
     }
 }
+
+/// Whether the string is a float as written in the line protocol, e.g., `1`,
+/// `-1.5`, or `1.5E-3`
+///
+/// Unlike [str::parse] this rejects `inf` and `NaN`, which InfluxDB does not
+/// support
+pub(crate) fn is_float_str(s: &str) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s).as_bytes();
+    let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+
+    // Mantissa with at least one digit before the optional fraction
+    let mut i = digits(s);
+    if i == 0 {
+        return false;
+    }
+
+    if s.get(i) == Some(&b'.') {
+        i += 1 + digits(&s[i + 1..]);
+    }
+
+    // Optional exponent with at least one digit
+    if let Some(b'e' | b'E') = s.get(i) {
+        i += 1;
+        if let Some(b'+' | b'-') = s.get(i) {
+            i += 1;
+        }
+
+        let exponent = digits(&s[i..]);
+        if exponent == 0 {
+            return false;
+        }
+        i += exponent;
+    }
+
+    i == s.len()
+}
+
+#[cfg(test)]
+mod test {
+    // Shadows the TryFrom of conv imported along with the parent module
+    use std::convert::TryFrom;
+
+    use crate::Point;
+
+    use super::*;
+
+    #[test]
+    fn test_value_ordering() {
+        use std::collections::BTreeSet;
+
+        let mut numbers = vec![
+            Number::UInteger(u64::MAX),
+            Number::Float(f64::NAN),
+            Number::Float(2.5),
+            Number::Integer(2),
+            Number::UInteger(2),
+            Number::Float(2.0),
+            Number::Float(-0.0),
+            Number::Integer(-3),
+            Number::Float(f64::NEG_INFINITY),
+            Number::Float(18446744073709551615.5),
+        ];
+        numbers.sort();
+        assert_eq!(
+            numbers[..8],
+            [
+                Number::Float(f64::NEG_INFINITY),
+                Number::Integer(-3),
+                Number::Float(0.0),
+                Number::Float(2.0),
+                Number::Integer(2),
+                Number::UInteger(2),
+                Number::Float(2.5),
+                Number::UInteger(u64::MAX),
+            ]
+        );
+        assert!(numbers[9].as_float().is_some_and(f64::is_nan));
+        assert_eq!(
+            Number::Float(0.0).cmp(&Number::Float(-0.0)),
+            std::cmp::Ordering::Equal
+        );
+        assert!(Number::Integer(i64::MIN) > Number::Float(-9223372036854777856.0));
+
+        // Equality and hashing agree with the ordering
+        let (nan, zero) = (Number::Float(f64::NAN), Number::Float(-0.0));
+        assert_eq!(nan, nan);
+        assert_eq!(nan.cmp(&nan), std::cmp::Ordering::Equal);
+        assert_eq!(zero, Number::Float(0.0));
+        assert_ne!(nan, Number::Float(-f64::NAN));
+        assert_eq!(Value::from(f64::NAN), Value::from(f64::NAN));
+
+        let state = std::hash::RandomState::new();
+        let hash = |n: &Number| std::hash::BuildHasher::hash_one(&state, n);
+        assert_eq!(hash(&nan), hash(&Number::Float(f64::NAN)));
+        assert_eq!(hash(&zero), hash(&Number::Float(0.0)));
+
+        let values: BTreeSet<Value> = [
+            Value::from("a"),
+            Value::from(1i64),
+            Value::from(true),
+            Value::None,
+            Value::from(false),
+            Value::from(0.5),
+        ]
+        .into_iter()
+        .collect();
+        let values: Vec<Value> = values.into_iter().collect();
+        assert_eq!(
+            values,
+            [
+                Value::None,
+                Value::from(false),
+                Value::from(true),
+                Value::from(0.5),
+                Value::from(1i64),
+                Value::from("a")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number_arithmetic() {
+        use Number::{Float, Integer, UInteger};
+
+        assert_eq!(Integer(2) + Float(0.5), Float(2.5));
+        assert_eq!(UInteger(2) + Integer(3), UInteger(5));
+        assert_eq!(UInteger(1) - Integer(2), Integer(-1));
+        assert_eq!(Integer(-4) * UInteger(3), Integer(-12));
+        assert_eq!(Integer(7) / Integer(2), Integer(3));
+        assert_eq!(Float(7.0) / Integer(2), Float(3.5));
+        assert_eq!(
+            Integer(i64::MAX) + Integer(1),
+            UInteger(i64::MAX as u64 + 1)
+        );
+
+        assert_eq!(UInteger(u64::MAX).checked_add(&UInteger(1)), None);
+        assert_eq!(Integer(1).checked_div(&Integer(0)), None);
+        assert_eq!(
+            UInteger(u64::MAX) + UInteger(1),
+            Float(u64::MAX as f64 + 1.0)
+        );
+        assert_eq!(Float(1.0).checked_div(&Integer(0)), None);
+        assert_eq!(Integer(1).checked_div(&Float(-0.0)), None);
+        assert_eq!(Float(1.0) / Integer(0), Float(f64::INFINITY));
+        assert_eq!(Integer(-1) / Float(0.0), Float(f64::NEG_INFINITY));
+        assert!(std::panic::catch_unwind(|| Integer(1) / Integer(0)).is_err());
+        assert!(std::panic::catch_unwind(|| UInteger(0) / UInteger(0)).is_err());
+
+        // Rate of a counter over 10 seconds
+        let rate = (UInteger(1500) - UInteger(1000)) / Float(10.0);
+        assert_eq!(rate, Float(50.0));
+    }
+
+    #[test]
+    fn test_value_context_strings() {
+        let value = Value::from(r#"eu west,1="a\b""#);
+        assert_eq!(value.to_tag_value_string(), r#"eu\ west\,1\="a\b""#);
+        assert_eq!(value.to_field_value_string(), r#""eu west,1=\"a\\b\"""#);
+
+        assert_eq!(Value::from(-12i64).to_field_value_string(), "-12i");
+        assert_eq!(Value::from(0.5).to_field_value_string(), "0.5");
+        assert_eq!(Value::from(true).to_field_value_string(), "true");
+        assert_eq!(Value::from(true).to_tag_value_string(), "true");
+
+        let field = value.to_field_value_string();
+        assert_eq!(field.parse::<Value>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        use std::num::{NonZeroI32, NonZeroUsize};
+
+        let count = 3usize;
+        let delta = NonZeroI32::new(-2).unwrap();
+        let mut point = Point::new("queue");
+        point.fields.insert("delta".to_string(), Value::from(delta));
+        point.fields.insert("len".to_string(), Value::from(count));
+        point.fields.insert("load".to_string(), Value::from(&0.5));
+        point
+            .fields
+            .insert("max".to_string(), Value::from(Some(10isize)));
+        point
+            .fields
+            .insert("min".to_string(), Value::from(None::<u8>));
+        assert_eq!(point.to_string(), "queue delta=-2i,len=3i,load=0.5,max=10i");
+
+        assert_eq!(Value::from(&7u64), Value::from(7u64));
+        assert_eq!(Value::from(Number::from(1.5f32)), Value::from(1.5));
+        assert_eq!(
+            Value::from(NonZeroUsize::new(4).unwrap()),
+            Value::from(4u64)
+        );
+        assert_eq!(Value::from(Some("a")), Value::from("a"));
+    }
+
+    #[test]
+    fn test_value_try_into_primitives() {
+        assert_eq!(i64::try_from(Value::from(-3i64)).unwrap(), -3);
+        assert_eq!(i64::try_from(Value::from(3u64)).unwrap(), 3);
+        assert_eq!(u64::try_from(Value::from(3i64)).unwrap(), 3);
+        assert_eq!(f64::try_from(Value::from(0.5)).unwrap(), 0.5);
+        assert!(bool::try_from(Value::from(true)).unwrap());
+        assert_eq!(String::try_from(Value::from("a")).unwrap(), "a");
+
+        let error = i64::try_from(Value::from(u64::MAX)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "an error occured: invalid value: integer `18446744073709551615`, expected an integer"
+        );
+
+        let error = u64::try_from(Value::from(-1i64)).unwrap_err();
+        assert!(error.to_string().contains("expected an unsigned integer"));
+
+        let error = f64::try_from(Value::from(1i64)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "an error occured: invalid type: integer `1`, expected a float"
+        );
+
+        assert!(bool::try_from(Value::from("true")).is_err());
+        assert!(String::try_from(Value::None).is_err());
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_value_json_schema() {
+        let schema = schemars::schema_for!(Value);
+        let types: Vec<&str> = schema.as_value()["type"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|ty| ty.as_str())
+            .collect();
+        assert_eq!(types, ["null", "boolean", "number", "string"]);
+
+        let schema = schemars::schema_for!(Number);
+        assert_eq!(schema.as_value()["type"], "number");
+        assert_eq!(schema.as_value()["title"], "Number");
+    }
+}
@@ -130,10 +130,12 @@ impl<'de> de::Deserializer<'de> for Value {
     deserialize_value!(deserialize_i16);
     deserialize_value!(deserialize_i32);
     deserialize_value!(deserialize_i64);
+    deserialize_value!(deserialize_i128);
     deserialize_value!(deserialize_u8);
     deserialize_value!(deserialize_u16);
     deserialize_value!(deserialize_u32);
     deserialize_value!(deserialize_u64);
+    deserialize_value!(deserialize_u128);
     deserialize_value!(deserialize_f32);
     deserialize_value!(deserialize_f64);
     deserialize_value!(deserialize_char);
@@ -182,12 +184,12 @@ impl<'de> de::Deserializer<'de> for Value {
     fn deserialize_newtype_struct<V>(
         self,
         _name: &'static str,
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(Error::unsupported("newtype struct deserialization"))
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -0,0 +1,98 @@
+//! Conversions between [Value] and `serde_json::Value`
+
+use serde::de::{self, Unexpected};
+
+use super::datatypes::{Number, Value};
+use crate::error::Error;
+
+/// Non-finite floats, which JSON can not represent, become null
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::None => serde_json::Value::Null,
+            Value::Number(Number::Float(n)) => serde_json::Number::from_f64(n)
+                .map_or(serde_json::Value::Null, serde_json::Value::Number),
+            Value::Number(Number::Integer(n)) => serde_json::Value::Number(n.into()),
+            Value::Number(Number::UInteger(n)) => serde_json::Value::Number(n.into()),
+            Value::String(s) => serde_json::Value::String(s),
+            Value::Boolean(b) => serde_json::Value::Bool(b),
+        }
+    }
+}
+
+/// Null becomes [Value::None], and whole numbers become integers unless they
+/// only fit an unsigned integer. Arrays and objects are rejected, as line
+/// protocol values can not be nested
+impl TryFrom<serde_json::Value> for Value {
+    type Error = Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        let value = match value {
+            serde_json::Value::Null => Value::None,
+            serde_json::Value::Bool(b) => Value::Boolean(b),
+            serde_json::Value::Number(n) => {
+                let number = match (n.as_i64(), n.as_u64(), n.as_f64()) {
+                    (Some(n), _, _) => Number::Integer(n),
+                    (None, Some(n), _) => Number::UInteger(n),
+                    (None, None, Some(n)) => Number::Float(n),
+                    _ => {
+                        return Err(de::Error::invalid_value(
+                            Unexpected::Other(&n.to_string()),
+                            &"a number",
+                        ))
+                    }
+                };
+                Value::Number(number)
+            }
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(_) => {
+                return Err(de::Error::invalid_type(
+                    Unexpected::Seq,
+                    &"a line protocol value",
+                ))
+            }
+            serde_json::Value::Object(_) => {
+                return Err(de::Error::invalid_type(
+                    Unexpected::Map,
+                    &"a line protocol value",
+                ))
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_value_json() {
+        use serde_json::json;
+
+        assert_eq!(serde_json::Value::from(Value::from(-3i64)), json!(-3));
+        assert_eq!(
+            serde_json::Value::from(Value::from(u64::MAX)),
+            json!(u64::MAX)
+        );
+        assert_eq!(serde_json::Value::from(Value::from(0.5)), json!(0.5));
+        assert_eq!(serde_json::Value::from(Value::from(f64::NAN)), json!(null));
+        assert_eq!(serde_json::Value::from(Value::from("a")), json!("a"));
+        assert_eq!(serde_json::Value::from(Value::None), json!(null));
+
+        for (json, value) in [
+            (json!(null), Value::None),
+            (json!(true), Value::from(true)),
+            (json!(12), Value::from(12i64)),
+            (json!(u64::MAX), Value::from(u64::MAX)),
+            (json!(1.5), Value::from(1.5)),
+            (json!("a"), Value::from("a")),
+        ] {
+            assert_eq!(Value::try_from(json).unwrap(), value);
+        }
+
+        assert!(Value::try_from(json!([1, 2])).is_err());
+        assert!(Value::try_from(json!({ "a": 1 })).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+//! A map of tag or field keys to their values
+
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::Hash,
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use serde::{
+    de::{self, MapAccess},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::Value;
+
+#[cfg(not(feature = "preserve_order"))]
+type MapImpl<K, V> = std::collections::BTreeMap<K, V>;
+#[cfg(feature = "preserve_order")]
+type MapImpl<K, V> = indexmap::IndexMap<K, V>;
+
+/// A map of tag or field keys to their values, as used by
+/// [Point](crate::point::Point)
+///
+/// Keys are sorted, unless the `preserve_order` feature is enabled, in which
+/// case they keep the order they were inserted in. The API is the same either
+/// way, so enabling the feature does not break code using the map
+pub struct Map<V = Value> {
+    map: MapImpl<String, V>,
+}
+
+impl<V> Map<V> {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self {
+            map: MapImpl::new(),
+        }
+    }
+
+    /// Create an empty map with room for at least `capacity` entries
+    ///
+    /// The capacity is ignored unless the `preserve_order` feature is enabled
+    pub fn with_capacity(capacity: usize) -> Self {
+        #[cfg(not(feature = "preserve_order"))]
+        let _ = capacity;
+
+        Self {
+            #[cfg(not(feature = "preserve_order"))]
+            map: MapImpl::new(),
+            #[cfg(feature = "preserve_order")]
+            map: MapImpl::with_capacity(capacity),
+        }
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// A reference to the value of `key`, if any
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns true if the map has an entry for `key`
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// A mutable reference to the value of `key`, if any
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// The stored key and value of `key`, if any
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&String, &V)>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get_key_value(key)
+    }
+
+    /// Insert `value` for `key`, returning the previous value, if any
+    ///
+    /// A replaced entry keeps its position
+    pub fn insert(&mut self, key: String, value: V) -> Option<V> {
+        self.map.insert(key, value)
+    }
+
+    /// Remove the entry of `key`, returning its value, if any
+    ///
+    /// The remaining entries keep their order
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove(key);
+    }
+
+    /// Remove the entry of `key`, returning its stored key and value, if any
+    ///
+    /// The remaining entries keep their order
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(String, V)>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove_entry(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove_entry(key);
+    }
+
+    /// Keep only the entries for which `f` returns true
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&String, &mut V) -> bool,
+    {
+        self.map.retain(|k, v| f(k, v));
+    }
+
+    /// Move all entries of `other` into this map, leaving `other` empty
+    pub fn append(&mut self, other: &mut Self) {
+        #[cfg(not(feature = "preserve_order"))]
+        self.map.append(&mut other.map);
+        #[cfg(feature = "preserve_order")]
+        self.map.extend(other.map.drain(..));
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// An iterator over the entries
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// An iterator over the entries, with mutable values
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        IterMut {
+            iter: self.map.iter_mut(),
+        }
+    }
+
+    /// An iterator over the keys
+    pub fn keys(&self) -> Keys<'_, V> {
+        Keys {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// An iterator over the values
+    pub fn values(&self) -> Values<'_, V> {
+        Values {
+            iter: self.map.values(),
+        }
+    }
+
+    /// An iterator over mutable values
+    pub fn values_mut(&mut self) -> ValuesMut<'_, V> {
+        ValuesMut {
+            iter: self.map.values_mut(),
+        }
+    }
+
+    /// An iterator over the values, consuming the map
+    pub fn into_values(self) -> IntoValues<V> {
+        IntoValues {
+            iter: self.map.into_values(),
+        }
+    }
+}
+
+impl<V> Default for Map<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone> Clone for Map<V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<V: PartialEq> PartialEq for Map<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<V: Eq> Eq for Map<V> {}
+
+impl<V: fmt::Debug> fmt::Debug for Map<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<Q, V> Index<&Q> for Map<V>
+where
+    String: Borrow<Q>,
+    Q: ?Sized + Ord + Eq + Hash,
+{
+    type Output = V;
+
+    /// # Panics
+    ///
+    /// Panics if the map has no entry for `key`
+    fn index(&self, key: &Q) -> &V {
+        self.map.index(key)
+    }
+}
+
+impl<Q, V> IndexMut<&Q> for Map<V>
+where
+    String: Borrow<Q>,
+    Q: ?Sized + Ord + Eq + Hash,
+{
+    /// # Panics
+    ///
+    /// Panics if the map has no entry for `key`
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.map.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<V> FromIterator<(String, V)> for Map<V> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (String, V)>,
+    {
+        Self {
+            map: MapImpl::from_iter(iter),
+        }
+    }
+}
+
+impl<V> Extend<(String, V)> for Map<V> {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (String, V)>,
+    {
+        self.map.extend(iter);
+    }
+}
+
+impl<V, const N: usize> From<[(String, V); N]> for Map<V> {
+    fn from(entries: [(String, V); N]) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+macro_rules! delegate_iterator {
+    ($(#[$doc:meta])* $name:ident $(<$lt:lifetime>)?, $inner:ident, $item:ty) => {
+        $(#[$doc])*
+        pub struct $name<$($lt,)? V> {
+            #[cfg(not(feature = "preserve_order"))]
+            iter: std::collections::btree_map::$inner<$($lt,)? String, V>,
+            #[cfg(feature = "preserve_order")]
+            iter: indexmap::map::$inner<$($lt,)? String, V>,
+        }
+
+        impl<$($lt,)? V> Iterator for $name<$($lt,)? V> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<$($lt,)? V> DoubleEndedIterator for $name<$($lt,)? V> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()
+            }
+        }
+
+        impl<$($lt,)? V> ExactSizeIterator for $name<$($lt,)? V> {
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl<$($lt,)? V> FusedIterator for $name<$($lt,)? V> {}
+    };
+}
+
+delegate_iterator! {
+    /// An iterator over the entries of a [Map], see [Map::iter]
+    Iter<'a>, Iter, (&'a String, &'a V)
+}
+
+delegate_iterator! {
+    /// An iterator over the entries of a [Map] with mutable values, see
+    /// [Map::iter_mut]
+    IterMut<'a>, IterMut, (&'a String, &'a mut V)
+}
+
+delegate_iterator! {
+    /// An owning iterator over the entries of a [Map]
+    IntoIter, IntoIter, (String, V)
+}
+
+delegate_iterator! {
+    /// An iterator over the keys of a [Map], see [Map::keys]
+    Keys<'a>, Keys, &'a String
+}
+
+delegate_iterator! {
+    /// An iterator over the values of a [Map], see [Map::values]
+    Values<'a>, Values, &'a V
+}
+
+delegate_iterator! {
+    /// An iterator over the mutable values of a [Map], see [Map::values_mut]
+    ValuesMut<'a>, ValuesMut, &'a mut V
+}
+
+delegate_iterator! {
+    /// An owning iterator over the values of a [Map], see [Map::into_values]
+    IntoValues, IntoValues, V
+}
+
+impl<'a, V> IntoIterator for &'a Map<V> {
+    type Item = (&'a String, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut Map<V> {
+    type Item = (&'a String, &'a mut V);
+    type IntoIter = IterMut<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<V> IntoIterator for Map<V> {
+    type Item = (String, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+impl<V: Serialize> Serialize for Map<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for Map<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MapVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> de::Visitor<'de> for MapVisitor<V> {
+            type Value = Map<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map")
+            }
+
+            fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut map = Map::with_capacity(access.size_hint().unwrap_or(0));
+                while let Some((key, value)) = access.next_entry()? {
+                    map.insert(key, value);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
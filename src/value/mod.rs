@@ -1,3 +1,15 @@
-pub mod datatypes;
-pub mod de;
-pub mod ser;
+//! Untyped representation of line protocol values
+
+pub(crate) mod datatypes;
+pub(crate) mod de;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+pub mod map;
+pub(crate) mod ser;
+
+pub use self::{
+    datatypes::{LpType, Number, Value},
+    de::from_value,
+    map::Map,
+    ser::{to_value, to_values},
+};
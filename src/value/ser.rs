@@ -67,6 +67,13 @@ impl ser::Serializer for Serializer {
         Ok(Value::Number(Number::Integer(v)))
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        match Number::from_i128(v) {
+            Some(n) => Ok(Value::Number(n)),
+            None => Err(Error::integer_out_of_range(v)),
+        }
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.serialize_u64(v as u64)
     }
@@ -83,6 +90,13 @@ impl ser::Serializer for Serializer {
         Ok(Value::Number(Number::UInteger(v)))
     }
 
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        match Number::from_u128(v) {
+            Some(n) => Ok(Value::Number(n)),
+            None => Err(Error::integer_out_of_range(v)),
+        }
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(v as f64)
     }
@@ -134,12 +148,12 @@ impl ser::Serializer for Serializer {
     fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::unsupported("newtype struct serialization"))
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
@@ -204,6 +218,142 @@ impl ser::Serializer for Serializer {
     }
 }
 
+/// Serializer of sequences of values, each element is serialized with
+/// [Serializer]
+pub struct ValuesSerializer;
+
+/// Error of a value which is not a sequence being serialized by
+/// [ValuesSerializer]
+fn not_a_sequence() -> Error {
+    ser::Error::custom("expected a sequence of values")
+}
+
+/// Implements the serializer methods of values which are not sequences
+macro_rules! reject_values {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> {
+                Err(not_a_sequence())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValuesSerializer {
+    type Ok = Vec<Value>;
+    type Error = Error;
+
+    type SerializeSeq = VecSerializer;
+    type SerializeTuple = VecSerializer;
+    type SerializeTupleStruct = VecSerializer;
+    type SerializeTupleVariant = Impossible<Vec<Value>, Error>;
+    type SerializeMap = Impossible<Vec<Value>, Error>;
+    type SerializeStruct = Impossible<Vec<Value>, Error>;
+    type SerializeStructVariant = Impossible<Vec<Value>, Error>;
+
+    reject_values!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(VecSerializer {
+            vec: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(not_a_sequence())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(not_a_sequence())
+    }
+}
+
 pub struct VecSerializer {
     vec: Vec<Value>,
 }
@@ -225,6 +375,38 @@ impl SerializeSeq for VecSerializer {
     }
 }
 
+impl ser::SerializeTuple for VecSerializer {
+    type Ok = Vec<Value>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for VecSerializer {
+    type Ok = Vec<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeSeq::end(self)
+    }
+}
+
 /// Attempt to serialize type `T` into Value. Can only convert into from values
 /// which are supported by InfluxDB v2 Line protocol
 ///
@@ -248,3 +430,20 @@ where
 {
     value.serialize(Serializer)
 }
+
+/// Attempt to serialize a sequence of `T`, e.g., a `Vec` or a tuple, into a
+/// Vec of values. Each element is serialized as with [to_value]
+///
+/// # Example
+///
+/// ```rust
+/// let values = serde_influxlp::to_values(vec![1, 2, 3]).unwrap();
+/// println!("{values:?}");
+/// // Output: [Number(Integer(1)), Number(Integer(2)), Number(Integer(3))]
+/// ```
+pub fn to_values<T>(value: T) -> Result<Vec<Value>, Error>
+where
+    T: Serialize,
+{
+    value.serialize(ValuesSerializer)
+}